@@ -4,18 +4,149 @@ fn main() {
         Windows::Win32::UI::HiDpi::*,
         Windows::Win32::Graphics::Gdi::{
             MonitorFromPoint,
+            MonitorFromWindow,
+            HMONITOR,
+            GetDC,
+            ReleaseDC,
+            GetDeviceCaps,
+            LOGPIXELSX,
             GetMonitorInfoW,
             EnumDisplayMonitors,
             BeginPaint,
             EndPaint,
             GetStockObject,
+            CreateSolidBrush,
+            FillRect,
+            HBRUSH,
+            HDC,
             RedrawWindow,
+            RDW_INVALIDATE,
+            RDW_UPDATENOW,
+            ScreenToClient,
+            ClientToScreen,
+            CreateDIBSection,
+            DeleteObject,
+            CreateCompatibleDC,
+            DeleteDC,
+            SelectObject,
+            BitBlt,
+            GdiFlush,
+            InvalidateRect,
+            SRCCOPY,
+            BITMAPINFO,
+            BITMAPINFOHEADER,
+            BI_RGB,
+            DIB_RGB_COLORS,
+            HBITMAP,
             MONITORINFO,
             PAINTSTRUCT,
         },
+        Windows::Win32::Devices::Display::{
+            GetDisplayConfigBufferSizes,
+            QueryDisplayConfig,
+            DisplayConfigGetDeviceInfo,
+            DISPLAYCONFIG_PATH_INFO,
+            DISPLAYCONFIG_MODE_INFO,
+            DISPLAYCONFIG_DEVICE_INFO_HEADER,
+            DISPLAYCONFIG_SOURCE_DEVICE_NAME,
+            DISPLAYCONFIG_TARGET_DEVICE_NAME,
+            DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO,
+            DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+            DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+            DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO,
+            QDC_ONLY_ACTIVE_PATHS,
+        },
+        Windows::Win32::Graphics::OpenGL::{
+            ChoosePixelFormat,
+            SetPixelFormat,
+            SwapBuffers,
+            wglCreateContext,
+            wglDeleteContext,
+            wglMakeCurrent,
+            wglGetCurrentContext,
+            wglGetProcAddress,
+            HGLRC,
+            PIXELFORMATDESCRIPTOR,
+            PFD_DRAW_TO_WINDOW,
+            PFD_SUPPORT_OPENGL,
+            PFD_DOUBLEBUFFER,
+            PFD_TYPE_RGBA,
+            PFD_MAIN_PLANE,
+        },
         Windows::Win32::UI::KeyboardAndMouseInput::*,
         Windows::Win32::System::LibraryLoader::{
             GetModuleHandleW,
+            GetProcAddress,
+            LoadLibraryW,
+        },
+        Windows::Win32::Graphics::Dwm::{
+            DwmEnableBlurBehindWindow,
+            DwmSetWindowAttribute,
+            DwmGetWindowAttribute,
+            DwmSetIconicThumbnail,
+            DwmSetIconicLivePreviewBitmap,
+            DwmInvalidateIconicBitmaps,
+            DWM_BLURBEHIND,
+            DWM_BB_ENABLE,
+            DwmGetCompositionTimingInfo,
+            DwmFlush,
+            DWM_TIMING_INFO,
+        },
+        Windows::Win32::System::Performance::{
+            QueryPerformanceFrequency,
+        },
+        Windows::Win32::System::Threading::{
+            GetCurrentProcessId,
+            GetCurrentThreadId,
+            CreateWaitableTimerExW,
+            SetWaitableTimer,
+            WaitForSingleObject,
+        },
+        Windows::Win32::Media::Multimedia::{
+            timeBeginPeriod,
+            timeEndPeriod,
+        },
+        Windows::Win32::Graphics::Dxgi::{
+            CreateDXGIFactory1,
+            IDXGIFactory1,
+            IDXGIAdapter1,
+            IDXGIOutput,
+            IDXGIOutput1,
+            IDXGIOutputDuplication,
+            IDXGIResource,
+            DXGI_OUTPUT_DESC,
+            DXGI_OUTDUPL_FRAME_INFO,
+            DXGI_SAMPLE_DESC,
+        },
+        Windows::Win32::Graphics::Direct3D11::{
+            D3D11CreateDevice,
+            ID3D11Device,
+            ID3D11DeviceContext,
+            ID3D11Texture2D,
+            D3D11_TEXTURE2D_DESC,
+            D3D11_MAPPED_SUBRESOURCE,
+            D3D11_USAGE_STAGING,
+            D3D11_CPU_ACCESS_READ,
+            D3D11_MAP_READ,
+            D3D11_BIND_FLAG,
+            D3D11_RESOURCE_MISC_FLAG,
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            D3D11_SDK_VERSION,
+            D3D_DRIVER_TYPE_UNKNOWN,
+        },
+        Windows::Win32::Graphics::Direct2D::{
+            D2D1CreateFactory,
+            ID2D1Factory,
+            ID2D1HwndRenderTarget,
+            D2D1_FACTORY_TYPE_SINGLE_THREADED,
+            D2D1_RENDER_TARGET_PROPERTIES,
+            D2D1_HWND_RENDER_TARGET_PROPERTIES,
+            D2D_SIZE_U,
+        },
+        Windows::Win32::Storage::Xps::{
+            PrintWindow,
+            PRINT_WINDOW_FLAGS,
+            PW_CLIENTONLY,
         },
         Windows::Win32::System::Memory::{
             LocalFree,
@@ -31,7 +162,9 @@ fn main() {
             PWSTR,
             HWND,
             HINSTANCE,
+            BSTR,
             CloseHandle,
+            ERROR_SUCCESS,
         },
         Windows::Win32::System::Diagnostics::Debug::{
             FormatMessageW,
@@ -42,10 +175,83 @@ fn main() {
             DragAcceptFiles,
             DragQueryFileW,
             DragQueryPoint,
-            DragFinish
+            DragFinish,
+            ICustomDestinationList,
+            IObjectArray,
+            IObjectCollection,
+            IShellLinkW,
+            DestinationList,
+            EnumerableObjectCollection,
+            ShellLink,
+            KDC_RECENT,
+            KDC_FREQUENT,
+            SHAddToRecentDocs,
+            SHARD_PATHW,
+            SetCurrentProcessExplicitAppUserModelID,
+            GetCurrentProcessExplicitAppUserModelID,
+            Shell_NotifyIconW,
+            IVirtualDesktopManager,
+            VirtualDesktopManager,
+            NOTIFYICONDATAW,
+            NIM_ADD,
+            NIM_DELETE,
+            NIF_ICON,
+            NIF_MESSAGE,
+            NIF_TIP,
+        },
+        Windows::Win32::System::Com::{
+            CoCreateInstance,
+            CoTaskMemFree,
+            CLSCTX_INPROC_SERVER,
+        },
+        Windows::Win32::System::PropertiesSystem::{
+            IPropertyStore,
+            PKEY_Title,
+        },
+        Windows::Win32::Storage::StructuredStorage::PROPVARIANT,
+        Windows::Win32::UI::Controls::{
+            WM_MOUSELEAVE,
+            InitCommonControlsEx,
+            INITCOMMONCONTROLSEX,
+            ICC_BAR_CLASSES,
         },
-        Windows::Win32::UI::Controls::WM_MOUSELEAVE,
         Windows::Win32::Storage::FileSystem::*,
         Windows::Win32::Devices::HumanInterfaceDevice::*,
+        Windows::Win32::UI::Accessibility::{
+            SetWinEventHook,
+            UnhookWinEvent,
+            HWINEVENTHOOK,
+            HIGHCONTRASTW,
+            HCF_HIGHCONTRASTON,
+            UiaHostProviderFromHwnd,
+            UiaReturnRawElementProvider,
+            IRawElementProviderSimple,
+            IRawElementProviderFragment,
+            IRawElementProviderFragmentRoot,
+            IAccPropServices,
+            CLSID_AccPropServices,
+            PROPID_ACC_NAME,
+            PROPID_ACC_ROLE,
+        },
+        Windows::Win32::System::OleAutomation::{
+            VARIANT,
+            SafeArrayCreateVector,
+            SafeArrayPutElement,
+        },
+        Windows::Win32::System::Power::{
+            HPOWERNOTIFY,
+            GetSystemPowerStatus,
+            SYSTEM_POWER_STATUS,
+        },
+        Windows::Win32::System::DataExchange::COPYDATASTRUCT,
+        Windows::Win32::System::Shutdown::{
+            ShutdownBlockReasonCreate,
+            ShutdownBlockReasonDestroy,
+        },
+        Windows::Win32::System::Registry::{
+            RegGetValueW,
+            HKEY_CURRENT_USER,
+            RRF_RT_REG_DWORD,
+        },
     );
 }
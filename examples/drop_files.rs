@@ -1,5 +1,3 @@
-use std::path::Path;
-
 struct Application {
     accept_drag_files: bool,
 }
@@ -20,10 +18,17 @@ impl wita::EventHandler for Application {
     fn drop_files(
         &mut self,
         _: &wita::Window,
-        paths: &[&Path],
-        position: wita::PhysicalPosition<f32>,
+        items: &[wita::DroppedItem],
+        position: wita::PhysicalPosition<i32>,
     ) {
-        println!("drop files: {:?}, {:?}", paths, position);
+        for item in items {
+            if item.is_dir() {
+                println!("drop directory: {:?}", item.path);
+            } else {
+                println!("drop file: {:?}, {:?} bytes", item.path, item.size());
+            }
+        }
+        println!("position: {:?}", position);
     }
 
     fn key_input(
@@ -12,9 +12,9 @@ impl Application {
 impl wita::EventHandler for Application {
     fn key_input(&mut self, _: &wita::Window, _: wita::KeyCode, state: wita::KeyState, _: bool) {
         if state == wita::KeyState::Pressed {
-            let mut ks = vec![];
-            wita::keyboard_state(&mut ks);
-            dbg!(wita::get_key_state(wita::VirtualKey::Char('A')));
+            let ks = wita::KeyboardState::get();
+            dbg!(ks.is_pressed(wita::VirtualKey::Char('A')));
+            dbg!(ks.caps_lock());
             println!("{:?}", ks);
         }
     }
@@ -0,0 +1,719 @@
+use crate::api::{initialize_com, load_proc};
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Gdi::ClientToScreen, System::Com::*, System::LibraryLoader::*,
+    System::OleAutomation::*, UI::Accessibility::*,
+};
+use crate::error::ApiError;
+use crate::geometry::*;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Once;
+use std::sync::{Arc, RwLock};
+use windows::{Guid, IUnknown, Interface, HRESULT};
+
+const OBJID_CLIENT: i32 = -4;
+const CHILDID_SELF: u32 = 0;
+const VT_I4: u16 = 3;
+const ERROR_PROC_NOT_FOUND: u32 = 127;
+
+const NOTIFICATION_KIND_OTHER: i32 = 4;
+const NOTIFICATION_PROCESSING_IMPORTANT_ALL: i32 = 0;
+const NOTIFICATION_PROCESSING_ALL: i32 = 2;
+const NOTIFICATION_PROCESSING_MOST_RECENT: i32 = 3;
+
+type UiaRaiseNotificationEventFn =
+    unsafe extern "system" fn(*mut c_void, i32, i32, *mut u16, *mut u16) -> HRESULT;
+
+/// Describes how screen readers handle an announcement.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum AnnouncementPriority {
+    /// Only the most recent one is read when announcements are queued.
+    Low,
+    /// All announcements are read in order.
+    Normal,
+    /// The announcement may interrupt the current speech.
+    High,
+}
+
+/// Describes the role of a window for screen readers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum AccessibleRole {
+    /// The client area of a window. This is the default role.
+    Client,
+    /// The main window of an application.
+    Application,
+    /// A window that shows a document such as an editor.
+    Document,
+    /// A separate area in a window such as a panel.
+    Pane,
+    /// A dialog box or a message box.
+    Dialog,
+    /// A picture such as a canvas.
+    Graphic,
+    /// A window that shows an animation.
+    Animation,
+}
+
+impl AccessibleRole {
+    fn raw(self) -> i32 {
+        match self {
+            Self::Client => 0x0a,
+            Self::Application => 0x0e,
+            Self::Document => 0x0f,
+            Self::Pane => 0x10,
+            Self::Dialog => 0x12,
+            Self::Graphic => 0x28,
+            Self::Animation => 0x36,
+        }
+    }
+}
+
+fn raise_notification_event_fn() -> Option<UiaRaiseNotificationEventFn> {
+    static LOAD: Once = Once::new();
+    static mut FUNCTION: Option<UiaRaiseNotificationEventFn> = None;
+    unsafe {
+        LOAD.call_once(|| {
+            FUNCTION = load_proc(
+                LoadLibraryW("uiautomationcore.dll"),
+                "UiaRaiseNotificationEvent",
+            );
+        });
+        FUNCTION
+    }
+}
+
+/// `UiaRaiseNotificationEvent` is supported from Windows 10 1709.
+pub(crate) fn announce(
+    hwnd: HWND,
+    text: &str,
+    priority: AnnouncementPriority,
+) -> Result<(), ApiError> {
+    let f = raise_notification_event_fn().ok_or_else(|| {
+        ApiError::from_win32(ERROR_PROC_NOT_FOUND).with_function("UiaRaiseNotificationEvent")
+    })?;
+    let processing = match priority {
+        AnnouncementPriority::Low => NOTIFICATION_PROCESSING_MOST_RECENT,
+        AnnouncementPriority::Normal => NOTIFICATION_PROCESSING_ALL,
+        AnnouncementPriority::High => NOTIFICATION_PROCESSING_IMPORTANT_ALL,
+    };
+    unsafe {
+        let mut provider: Option<IRawElementProviderSimple> = None;
+        UiaHostProviderFromHwnd(hwnd, &mut provider).ok()?;
+        let provider = provider.unwrap();
+        let text = BSTR::from(text);
+        let activity = BSTR::from("wita.announce");
+        f(
+            std::mem::transmute_copy(&provider),
+            NOTIFICATION_KIND_OTHER,
+            processing,
+            text.0,
+            activity.0,
+        )
+        .ok()?;
+    }
+    Ok(())
+}
+
+fn prop_services() -> Result<IAccPropServices, ApiError> {
+    initialize_com()?;
+    unsafe {
+        Ok(CoCreateInstance(
+            &CLSID_AccPropServices,
+            None,
+            CLSCTX_INPROC_SERVER,
+        )?)
+    }
+}
+
+pub(crate) fn set_name(hwnd: HWND, name: &str) -> Result<(), ApiError> {
+    unsafe {
+        prop_services()?
+            .SetHwndPropStr(hwnd, OBJID_CLIENT, CHILDID_SELF, PROPID_ACC_NAME, name)
+            .ok()?;
+    }
+    Ok(())
+}
+
+pub(crate) fn set_role(hwnd: HWND, role: AccessibleRole) -> Result<(), ApiError> {
+    unsafe {
+        let mut value = VARIANT::default();
+        value.Anonymous.Anonymous.vt = VT_I4;
+        value.Anonymous.Anonymous.Anonymous.lVal = role.raw();
+        prop_services()?
+            .SetHwndProp(hwnd, OBJID_CLIENT, CHILDID_SELF, PROPID_ACC_ROLE, value)
+            .ok()?;
+    }
+    Ok(())
+}
+
+const UIA_ROOT_OBJECT_ID: i32 = -25;
+const UIA_APPEND_RUNTIME_ID: i32 = 3;
+const UIA_CONTROL_TYPE_PROPERTY_ID: i32 = 30003;
+const UIA_NAME_PROPERTY_ID: i32 = 30005;
+const PROVIDER_OPTIONS_SERVER_SIDE_PROVIDER: i32 = 1;
+
+const NAVIGATE_DIRECTION_PARENT: i32 = 0;
+const NAVIGATE_DIRECTION_NEXT_SIBLING: i32 = 1;
+const NAVIGATE_DIRECTION_PREVIOUS_SIBLING: i32 = 2;
+const NAVIGATE_DIRECTION_FIRST_CHILD: i32 = 3;
+const NAVIGATE_DIRECTION_LAST_CHILD: i32 = 4;
+
+const VT_EMPTY: u16 = 0;
+const VT_BSTR: u16 = 8;
+
+const S_OK: HRESULT = HRESULT(0);
+const E_NOINTERFACE: HRESULT = HRESULT(0x80004002);
+const E_INVALIDARG: HRESULT = HRESULT(0x80070057);
+const UIA_E_ELEMENTNOTAVAILABLE: HRESULT = HRESULT(0x80040201);
+
+/// Describes the type of an `AccessibleElement`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum ElementRole {
+    Button,
+    CheckBox,
+    RadioButton,
+    Edit,
+    Text,
+    Image,
+    List,
+    ListItem,
+    Slider,
+    ProgressBar,
+    Group,
+    Custom,
+}
+
+impl ElementRole {
+    fn control_type(self) -> i32 {
+        match self {
+            Self::Button => 50000,
+            Self::CheckBox => 50002,
+            Self::Edit => 50004,
+            Self::Image => 50006,
+            Self::ListItem => 50007,
+            Self::List => 50008,
+            Self::ProgressBar => 50012,
+            Self::RadioButton => 50013,
+            Self::Slider => 50015,
+            Self::Text => 50020,
+            Self::Custom => 50025,
+            Self::Group => 50026,
+        }
+    }
+}
+
+/// An element drawn in the client area that `Window::set_accessible_tree` exposes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AccessibleElement {
+    pub role: ElementRole,
+    pub name: String,
+    /// The position in the client area.
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub children: Vec<AccessibleElement>,
+}
+
+impl AccessibleElement {
+    pub fn new(
+        role: ElementRole,
+        name: impl Into<String>,
+        position: impl Into<PhysicalPosition<i32>>,
+        size: impl Into<PhysicalSize<u32>>,
+    ) -> Self {
+        Self {
+            role,
+            name: name.into(),
+            position: position.into(),
+            size: size.into(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn child(mut self, child: AccessibleElement) -> Self {
+        self.children.push(child);
+        self
+    }
+}
+
+struct Node {
+    role: ElementRole,
+    name: String,
+    rect: RECT,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// The flattened `AccessibleElement`s.
+///
+/// `generation` is incremented on every update so that providers of old elements become unavailable.
+#[derive(Default)]
+pub(crate) struct Tree {
+    generation: i32,
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+pub(crate) type SharedTree = Arc<RwLock<Tree>>;
+
+impl Tree {
+    fn push(&mut self, element: AccessibleElement, parent: Option<usize>) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            role: element.role,
+            name: element.name,
+            rect: RECT {
+                left: element.position.x,
+                top: element.position.y,
+                right: element.position.x + element.size.width as i32,
+                bottom: element.position.y + element.size.height as i32,
+            },
+            parent,
+            children: Vec::new(),
+        });
+        for child in element.children {
+            let child = self.push(child, Some(index));
+            self.nodes[index].children.push(child);
+        }
+        index
+    }
+
+    fn siblings(&self, index: usize) -> &[usize] {
+        match self.nodes[index].parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        }
+    }
+
+    /// Returns the deepest element that contains the point in the client area.
+    fn hit_test(&self, elements: &[usize], pt: POINT) -> Option<usize> {
+        elements.iter().rev().find_map(|&i| {
+            let rc = &self.nodes[i].rect;
+            (pt.x >= rc.left && pt.x < rc.right && pt.y >= rc.top && pt.y < rc.bottom)
+                .then(|| self.hit_test(&self.nodes[i].children, pt).unwrap_or(i))
+        })
+    }
+}
+
+pub(crate) fn set_tree(tree: &SharedTree, elements: Vec<AccessibleElement>) {
+    let mut tree = tree.write().unwrap();
+    tree.generation = tree.generation.wrapping_add(1);
+    tree.nodes.clear();
+    tree.roots.clear();
+    for element in elements {
+        let index = tree.push(element, None);
+        tree.roots.push(index);
+    }
+}
+
+/// `VARIANT`
+#[repr(C)]
+struct Variant {
+    vt: u16,
+    reserved: [u16; 3],
+    data: [usize; 2],
+}
+
+/// `UiaRect`
+#[repr(C)]
+struct UiaRect {
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+}
+
+#[repr(C)]
+struct UnknownVtbl {
+    query_interface:
+        unsafe extern "system" fn(*mut c_void, *const Guid, *mut *mut c_void) -> HRESULT,
+    add_ref: unsafe extern "system" fn(*mut c_void) -> u32,
+    release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+/// `IRawElementProviderSimple`
+#[repr(C)]
+struct SimpleVtbl {
+    unknown: UnknownVtbl,
+    provider_options: unsafe extern "system" fn(*mut c_void, *mut i32) -> HRESULT,
+    get_pattern_provider: unsafe extern "system" fn(*mut c_void, i32, *mut *mut c_void) -> HRESULT,
+    get_property_value: unsafe extern "system" fn(*mut c_void, i32, *mut Variant) -> HRESULT,
+    host_raw_element_provider: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+}
+
+/// `IRawElementProviderFragment`
+#[repr(C)]
+struct FragmentVtbl {
+    unknown: UnknownVtbl,
+    navigate: unsafe extern "system" fn(*mut c_void, i32, *mut *mut c_void) -> HRESULT,
+    get_runtime_id: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+    bounding_rectangle: unsafe extern "system" fn(*mut c_void, *mut UiaRect) -> HRESULT,
+    get_embedded_fragment_roots:
+        unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+    set_focus: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+    fragment_root: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+}
+
+/// `IRawElementProviderFragmentRoot`
+#[repr(C)]
+struct FragmentRootVtbl {
+    unknown: UnknownVtbl,
+    element_provider_from_point:
+        unsafe extern "system" fn(*mut c_void, f64, f64, *mut *mut c_void) -> HRESULT,
+    get_focus: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+}
+
+const SIMPLE: usize = 0;
+const FRAGMENT: usize = 1;
+const FRAGMENT_ROOT: usize = 2;
+
+static SIMPLE_VTBL: SimpleVtbl = SimpleVtbl {
+    unknown: UnknownVtbl {
+        query_interface: query_interface::<SIMPLE>,
+        add_ref: add_ref::<SIMPLE>,
+        release: release::<SIMPLE>,
+    },
+    provider_options,
+    get_pattern_provider,
+    get_property_value,
+    host_raw_element_provider,
+};
+
+static FRAGMENT_VTBL: FragmentVtbl = FragmentVtbl {
+    unknown: UnknownVtbl {
+        query_interface: query_interface::<FRAGMENT>,
+        add_ref: add_ref::<FRAGMENT>,
+        release: release::<FRAGMENT>,
+    },
+    navigate,
+    get_runtime_id,
+    bounding_rectangle,
+    get_embedded_fragment_roots,
+    set_focus,
+    fragment_root,
+};
+
+static FRAGMENT_ROOT_VTBL: FragmentRootVtbl = FragmentRootVtbl {
+    unknown: UnknownVtbl {
+        query_interface: query_interface::<FRAGMENT_ROOT>,
+        add_ref: add_ref::<FRAGMENT_ROOT>,
+        release: release::<FRAGMENT_ROOT>,
+    },
+    element_provider_from_point,
+    get_focus,
+};
+
+/// A UI Automation provider of the client area or an element in it.
+///
+/// Each field of the vtables is the interface pointer that `QueryInterface` returns.
+#[repr(C)]
+struct Provider {
+    vtbls: [*const c_void; 3],
+    refs: AtomicU32,
+    hwnd: HWND,
+    tree: SharedTree,
+    generation: i32,
+    /// `None` is the client area that is the root of the tree.
+    index: Option<usize>,
+}
+
+impl Provider {
+    /// Returns the interface pointer of `N` with the reference count `1`.
+    fn create<const N: usize>(
+        hwnd: HWND,
+        tree: &SharedTree,
+        generation: i32,
+        index: Option<usize>,
+    ) -> *mut c_void {
+        let p = Box::into_raw(Box::new(Provider {
+            vtbls: [
+                &SIMPLE_VTBL as *const _ as _,
+                &FRAGMENT_VTBL as *const _ as _,
+                &FRAGMENT_ROOT_VTBL as *const _ as _,
+            ],
+            refs: AtomicU32::new(1),
+            hwnd,
+            tree: tree.clone(),
+            generation,
+            index,
+        }));
+        unsafe { (*p).vtbls.as_mut_ptr().add(N) as _ }
+    }
+
+    fn create_related<const N: usize>(&self, index: Option<usize>) -> *mut c_void {
+        Self::create::<N>(self.hwnd, &self.tree, self.generation, index)
+    }
+
+    unsafe fn from_interface<'a, const N: usize>(this: *mut c_void) -> &'a Provider {
+        &*((this as *mut *const c_void).sub(N) as *const Provider)
+    }
+
+    /// Returns `None` for the root or if the element has been removed.
+    fn node<'a>(&self, tree: &'a Tree) -> Option<&'a Node> {
+        match self.index {
+            Some(index) if tree.generation == self.generation => tree.nodes.get(index),
+            _ => None,
+        }
+    }
+
+    fn is_available(&self, tree: &Tree) -> bool {
+        self.index.is_none() || self.node(tree).is_some()
+    }
+
+    fn client_origin(&self) -> POINT {
+        let mut pt = POINT { x: 0, y: 0 };
+        unsafe {
+            ClientToScreen(self.hwnd, &mut pt);
+        }
+        pt
+    }
+}
+
+unsafe extern "system" fn query_interface<const N: usize>(
+    this: *mut c_void,
+    iid: *const Guid,
+    out: *mut *mut c_void,
+) -> HRESULT {
+    if out.is_null() {
+        return E_INVALIDARG;
+    }
+    let p = Provider::from_interface::<N>(this);
+    let iid = &*iid;
+    let n = if *iid == IUnknown::IID || *iid == IRawElementProviderSimple::IID {
+        SIMPLE
+    } else if *iid == IRawElementProviderFragment::IID {
+        FRAGMENT
+    } else if *iid == IRawElementProviderFragmentRoot::IID && p.index.is_none() {
+        FRAGMENT_ROOT
+    } else {
+        *out = std::ptr::null_mut();
+        return E_NOINTERFACE;
+    };
+    p.refs.fetch_add(1, Ordering::Relaxed);
+    *out = (this as *mut *const c_void).sub(N).add(n) as _;
+    S_OK
+}
+
+unsafe extern "system" fn add_ref<const N: usize>(this: *mut c_void) -> u32 {
+    let p = Provider::from_interface::<N>(this);
+    p.refs.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release<const N: usize>(this: *mut c_void) -> u32 {
+    let p = Provider::from_interface::<N>(this);
+    let refs = p.refs.fetch_sub(1, Ordering::Release) - 1;
+    if refs == 0 {
+        std::sync::atomic::fence(Ordering::Acquire);
+        drop(Box::from_raw(p as *const Provider as *mut Provider));
+    }
+    refs
+}
+
+unsafe extern "system" fn provider_options(_: *mut c_void, out: *mut i32) -> HRESULT {
+    *out = PROVIDER_OPTIONS_SERVER_SIDE_PROVIDER;
+    S_OK
+}
+
+unsafe extern "system" fn get_pattern_provider(
+    _: *mut c_void,
+    _: i32,
+    out: *mut *mut c_void,
+) -> HRESULT {
+    *out = std::ptr::null_mut();
+    S_OK
+}
+
+unsafe extern "system" fn get_property_value(
+    this: *mut c_void,
+    id: i32,
+    out: *mut Variant,
+) -> HRESULT {
+    let p = Provider::from_interface::<SIMPLE>(this);
+    let out = &mut *out;
+    out.vt = VT_EMPTY;
+    let tree = p.tree.read().unwrap();
+    if !p.is_available(&tree) {
+        return UIA_E_ELEMENTNOTAVAILABLE;
+    }
+    // The root leaves the properties to the host provider of the window.
+    if let Some(node) = p.node(&tree) {
+        match id {
+            UIA_CONTROL_TYPE_PROPERTY_ID => {
+                out.vt = VT_I4;
+                *(out.data.as_mut_ptr() as *mut i32) = node.role.control_type();
+            }
+            UIA_NAME_PROPERTY_ID => {
+                let name = BSTR::from(node.name.as_str());
+                out.vt = VT_BSTR;
+                out.data[0] = name.0 as _;
+                // The caller frees the string with the VARIANT.
+                std::mem::forget(name);
+            }
+            _ => {}
+        }
+    }
+    S_OK
+}
+
+unsafe extern "system" fn host_raw_element_provider(
+    this: *mut c_void,
+    out: *mut *mut c_void,
+) -> HRESULT {
+    let p = Provider::from_interface::<SIMPLE>(this);
+    *out = std::ptr::null_mut();
+    if p.index.is_none() {
+        let mut provider: Option<IRawElementProviderSimple> = None;
+        let hr = UiaHostProviderFromHwnd(p.hwnd, &mut provider);
+        if hr.is_err() {
+            return hr;
+        }
+        *out = std::mem::transmute(provider);
+    }
+    S_OK
+}
+
+unsafe extern "system" fn navigate(
+    this: *mut c_void,
+    direction: i32,
+    out: *mut *mut c_void,
+) -> HRESULT {
+    let p = Provider::from_interface::<FRAGMENT>(this);
+    *out = std::ptr::null_mut();
+    let tree = p.tree.read().unwrap();
+    if !p.is_available(&tree) {
+        return UIA_E_ELEMENTNOTAVAILABLE;
+    }
+    // `Some(None)` is the root.
+    let target = match (p.index, direction) {
+        (None, NAVIGATE_DIRECTION_FIRST_CHILD) => tree.roots.first().map(|&i| Some(i)),
+        (None, NAVIGATE_DIRECTION_LAST_CHILD) => tree.roots.last().map(|&i| Some(i)),
+        (None, _) => None,
+        (Some(i), NAVIGATE_DIRECTION_PARENT) => Some(tree.nodes[i].parent),
+        (Some(i), NAVIGATE_DIRECTION_NEXT_SIBLING) => {
+            let siblings = tree.siblings(i);
+            let pos = siblings.iter().position(|&s| s == i).unwrap();
+            siblings.get(pos + 1).map(|&s| Some(s))
+        }
+        (Some(i), NAVIGATE_DIRECTION_PREVIOUS_SIBLING) => {
+            let siblings = tree.siblings(i);
+            let pos = siblings.iter().position(|&s| s == i).unwrap();
+            pos.checked_sub(1).map(|pos| Some(siblings[pos]))
+        }
+        (Some(i), NAVIGATE_DIRECTION_FIRST_CHILD) => {
+            tree.nodes[i].children.first().map(|&c| Some(c))
+        }
+        (Some(i), NAVIGATE_DIRECTION_LAST_CHILD) => tree.nodes[i].children.last().map(|&c| Some(c)),
+        _ => None,
+    };
+    if let Some(index) = target {
+        *out = p.create_related::<FRAGMENT>(index);
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_runtime_id(this: *mut c_void, out: *mut *mut c_void) -> HRESULT {
+    let p = Provider::from_interface::<FRAGMENT>(this);
+    *out = std::ptr::null_mut();
+    let tree = p.tree.read().unwrap();
+    if !p.is_available(&tree) {
+        return UIA_E_ELEMENTNOTAVAILABLE;
+    }
+    // The root uses the runtime id of the window.
+    if let Some(index) = p.index {
+        let values = [UIA_APPEND_RUNTIME_ID, p.generation, index as i32];
+        let psa = SafeArrayCreateVector(VT_I4, 0, values.len() as _);
+        if psa.is_null() {
+            return E_INVALIDARG;
+        }
+        for (i, v) in values.iter().enumerate() {
+            SafeArrayPutElement(psa, &(i as i32), v as *const i32 as _);
+        }
+        *out = psa as _;
+    }
+    S_OK
+}
+
+unsafe extern "system" fn bounding_rectangle(this: *mut c_void, out: *mut UiaRect) -> HRESULT {
+    let p = Provider::from_interface::<FRAGMENT>(this);
+    *out = UiaRect {
+        left: 0.0,
+        top: 0.0,
+        width: 0.0,
+        height: 0.0,
+    };
+    let tree = p.tree.read().unwrap();
+    if !p.is_available(&tree) {
+        return UIA_E_ELEMENTNOTAVAILABLE;
+    }
+    // The root uses the rect of the window.
+    if let Some(node) = p.node(&tree) {
+        let origin = p.client_origin();
+        *out = UiaRect {
+            left: (origin.x + node.rect.left) as _,
+            top: (origin.y + node.rect.top) as _,
+            width: (node.rect.right - node.rect.left) as _,
+            height: (node.rect.bottom - node.rect.top) as _,
+        };
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_embedded_fragment_roots(
+    _: *mut c_void,
+    out: *mut *mut c_void,
+) -> HRESULT {
+    *out = std::ptr::null_mut();
+    S_OK
+}
+
+unsafe extern "system" fn set_focus(_: *mut c_void) -> HRESULT {
+    S_OK
+}
+
+unsafe extern "system" fn fragment_root(this: *mut c_void, out: *mut *mut c_void) -> HRESULT {
+    let p = Provider::from_interface::<FRAGMENT>(this);
+    *out = p.create_related::<FRAGMENT_ROOT>(None);
+    S_OK
+}
+
+unsafe extern "system" fn element_provider_from_point(
+    this: *mut c_void,
+    x: f64,
+    y: f64,
+    out: *mut *mut c_void,
+) -> HRESULT {
+    let p = Provider::from_interface::<FRAGMENT_ROOT>(this);
+    let tree = p.tree.read().unwrap();
+    let origin = p.client_origin();
+    let pt = POINT {
+        x: x as i32 - origin.x,
+        y: y as i32 - origin.y,
+    };
+    *out = p.create_related::<SIMPLE>(tree.hit_test(&tree.roots, pt));
+    S_OK
+}
+
+unsafe extern "system" fn get_focus(_: *mut c_void, out: *mut *mut c_void) -> HRESULT {
+    *out = std::ptr::null_mut();
+    S_OK
+}
+
+/// Returns the root provider for `WM_GETOBJECT` if the tree has been set.
+pub(crate) fn get_object(
+    hwnd: HWND,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    tree: &SharedTree,
+) -> Option<LRESULT> {
+    let generation = {
+        let tree = tree.read().unwrap();
+        if lparam.0 as i32 != UIA_ROOT_OBJECT_ID || tree.roots.is_empty() {
+            return None;
+        }
+        tree.generation
+    };
+    unsafe {
+        let provider: IRawElementProviderSimple =
+            std::mem::transmute(Provider::create::<SIMPLE>(hwnd, tree, generation, None));
+        Some(UiaReturnRawElementProvider(hwnd, wparam, lparam, &provider))
+    }
+}
@@ -1,24 +1,147 @@
 use crate::bindings::Windows::Win32::{
-    Foundation::*, Graphics::Gdi::*, UI::HiDpi::*, UI::WindowsAndMessaging::*,
+    Foundation::*, Graphics::Gdi::*, System::LibraryLoader::*, System::Registry::*, UI::HiDpi::*,
+    UI::WindowsAndMessaging::*,
 };
+use crate::context::DpiAwareness;
+use crate::error::ApiError;
 use crate::geometry::*;
+use crate::DEFAULT_DPI;
 use std::sync::Once;
+use windows::HRESULT;
+
+const RPC_E_CHANGED_MODE: HRESULT = HRESULT(0x80010106_u32 as _);
+
+/// Initializes COM in the single-threaded apartment on the current thread.
+///
+/// `RPC_E_CHANGED_MODE` is ignored because the objects can also be used in a thread that has initialized COM differently.
+pub(crate) fn initialize_com() -> Result<(), ApiError> {
+    match windows::initialize_sta() {
+        Err(e) if e.code() != RPC_E_CHANGED_MODE => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+type GetDpiForWindowFn = unsafe extern "system" fn(HWND) -> u32;
+type GetDpiForMonitorFn =
+    unsafe extern "system" fn(HMONITOR, MONITOR_DPI_TYPE, *mut u32, *mut u32) -> HRESULT;
+type AdjustWindowRectExForDpiFn = unsafe extern "system" fn(*mut RECT, u32, BOOL, u32, u32) -> BOOL;
+type EnableNonClientDpiScalingFn = unsafe extern "system" fn(HWND) -> BOOL;
+type GetSystemMetricsForDpiFn = unsafe extern "system" fn(SYSTEM_METRICS_INDEX, u32) -> i32;
+type SetProcessDpiAwarenessContextFn = unsafe extern "system" fn(DPI_AWARENESS_CONTEXT) -> BOOL;
+type SetProcessDpiAwarenessFn = unsafe extern "system" fn(PROCESS_DPI_AWARENESS) -> HRESULT;
+
+// These functions don't exist before Windows 10 (or 8.1 for shcore.dll),
+// so they are loaded at runtime instead of being linked.
+struct DpiFunctions {
+    get_dpi_for_window: Option<GetDpiForWindowFn>,
+    get_dpi_for_monitor: Option<GetDpiForMonitorFn>,
+    adjust_window_rect_ex_for_dpi: Option<AdjustWindowRectExForDpiFn>,
+    enable_non_client_dpi_scaling: Option<EnableNonClientDpiScalingFn>,
+    get_system_metrics_for_dpi: Option<GetSystemMetricsForDpiFn>,
+    set_process_dpi_awareness_context: Option<SetProcessDpiAwarenessContextFn>,
+    set_process_dpi_awareness: Option<SetProcessDpiAwarenessFn>,
+}
+
+pub(crate) unsafe fn load_proc<T: Copy>(module: HINSTANCE, name: &str) -> Option<T> {
+    if module.0 == 0 {
+        return None;
+    }
+    GetProcAddress(module, name).map(|f| std::mem::transmute_copy(&f))
+}
+
+impl DpiFunctions {
+    fn load() -> Self {
+        unsafe {
+            let user32 = GetModuleHandleW("user32.dll");
+            let shcore = LoadLibraryW("shcore.dll");
+            Self {
+                get_dpi_for_window: load_proc(user32, "GetDpiForWindow"),
+                get_dpi_for_monitor: load_proc(shcore, "GetDpiForMonitor"),
+                adjust_window_rect_ex_for_dpi: load_proc(user32, "AdjustWindowRectExForDpi"),
+                enable_non_client_dpi_scaling: load_proc(user32, "EnableNonClientDpiScaling"),
+                get_system_metrics_for_dpi: load_proc(user32, "GetSystemMetricsForDpi"),
+                set_process_dpi_awareness_context: load_proc(
+                    user32,
+                    "SetProcessDpiAwarenessContext",
+                ),
+                set_process_dpi_awareness: load_proc(shcore, "SetProcessDpiAwareness"),
+            }
+        }
+    }
+}
+
+fn dpi_functions() -> &'static DpiFunctions {
+    static LOAD: Once = Once::new();
+    static mut FUNCTIONS: Option<DpiFunctions> = None;
+    unsafe {
+        LOAD.call_once(|| FUNCTIONS = Some(DpiFunctions::load()));
+        FUNCTIONS.as_ref().unwrap()
+    }
+}
+
+fn get_device_caps_dpi(hwnd: HWND) -> u32 {
+    unsafe {
+        let hdc = GetDC(hwnd);
+        if hdc.0 == 0 {
+            return DEFAULT_DPI as u32;
+        }
+        let dpi = GetDeviceCaps(hdc, LOGPIXELSX);
+        ReleaseDC(hwnd, hdc);
+        if dpi > 0 {
+            dpi as u32
+        } else {
+            DEFAULT_DPI as u32
+        }
+    }
+}
 
 pub fn get_dpi_from_point(pt: ScreenPosition) -> u32 {
+    let f = match dpi_functions().get_dpi_for_monitor {
+        Some(f) => f,
+        None => return get_device_caps_dpi(HWND::default()),
+    };
     unsafe {
         let mut dpi_x = 0;
         let mut _dpi_y = 0;
-        GetDpiForMonitor(
+        let ret = f(
             MonitorFromPoint(POINT { x: pt.x, y: pt.y }, MONITOR_DEFAULTTOPRIMARY),
             MDT_DEFAULT,
             &mut dpi_x,
             &mut _dpi_y,
-        )
-        .ok();
+        );
+        if ret.is_err() {
+            return get_device_caps_dpi(HWND::default());
+        }
         dpi_x
     }
 }
 
+pub fn get_dpi_for_window(hwnd: HWND) -> u32 {
+    match dpi_functions().get_dpi_for_window {
+        Some(f) => unsafe { f(hwnd) },
+        None => get_device_caps_dpi(hwnd),
+    }
+}
+
+pub fn enable_non_client_dpi_scaling(hwnd: HWND) {
+    if let Some(f) = dpi_functions().enable_non_client_dpi_scaling {
+        unsafe {
+            f(hwnd);
+        }
+    }
+}
+
+pub fn get_system_metrics_for_dpi(index: SYSTEM_METRICS_INDEX, dpi: u32) -> i32 {
+    unsafe {
+        match dpi_functions().get_system_metrics_for_dpi {
+            Some(f) => f(index, dpi),
+            None => {
+                GetSystemMetrics(index) * dpi as i32 / get_device_caps_dpi(HWND::default()) as i32
+            }
+        }
+    }
+}
+
 pub fn adjust_window_rect(size: PhysicalSize<u32>, style: u32, ex_style: u32, dpi: u32) -> RECT {
     unsafe {
         let mut rc = RECT {
@@ -27,19 +150,53 @@ pub fn adjust_window_rect(size: PhysicalSize<u32>, style: u32, ex_style: u32, dp
             right: size.width as i32,
             bottom: size.height as i32,
         };
-        AdjustWindowRectExForDpi(&mut rc, style, false, ex_style, dpi);
+        match dpi_functions().adjust_window_rect_ex_for_dpi {
+            Some(f) => {
+                f(&mut rc, style, false.into(), ex_style, dpi);
+            }
+            None => {
+                AdjustWindowRectEx(&mut rc, style, false, ex_style);
+            }
+        }
         rc
     }
 }
 
-pub fn enable_dpi_awareness() {
-    static ENABLE_DPI_AWARENESS: Once = Once::new();
+pub fn set_dpi_awareness(awareness: DpiAwareness) {
+    static SET_DPI_AWARENESS: Once = Once::new();
+    let functions = dpi_functions();
     unsafe {
-        ENABLE_DPI_AWARENESS.call_once(|| {
-            if !SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2).as_bool()
-                && !SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE).as_bool()
-            {
-                SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE).ok();
+        SET_DPI_AWARENESS.call_once(|| {
+            let (contexts, process): (&[DPI_AWARENESS_CONTEXT], _) = match awareness {
+                DpiAwareness::PerMonitorV2 => (
+                    &[
+                        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+                        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE,
+                    ],
+                    PROCESS_PER_MONITOR_DPI_AWARE,
+                ),
+                DpiAwareness::PerMonitor => (
+                    &[DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE],
+                    PROCESS_PER_MONITOR_DPI_AWARE,
+                ),
+                DpiAwareness::System => (
+                    &[DPI_AWARENESS_CONTEXT_SYSTEM_AWARE],
+                    PROCESS_SYSTEM_DPI_AWARE,
+                ),
+                DpiAwareness::Unaware => (&[DPI_AWARENESS_CONTEXT_UNAWARE], PROCESS_DPI_UNAWARE),
+            };
+            if let Some(f) = functions.set_process_dpi_awareness_context {
+                if contexts.iter().any(|&ctx| f(ctx).as_bool()) {
+                    return;
+                }
+            }
+            if let Some(f) = functions.set_process_dpi_awareness {
+                if f(process).is_ok() {
+                    return;
+                }
+            }
+            if awareness != DpiAwareness::Unaware {
+                SetProcessDPIAware();
             }
         });
     }
@@ -50,3 +207,20 @@ pub fn enable_gui_thread() {
         IsGUIThread(true);
     }
 }
+
+pub fn get_registry_dword(subkey: &str, value: &str) -> Option<u32> {
+    unsafe {
+        let mut data = 0u32;
+        let mut len = std::mem::size_of::<u32>() as u32;
+        let ret = RegGetValueW(
+            HKEY_CURRENT_USER,
+            subkey,
+            value,
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            &mut data as *mut u32 as _,
+            &mut len,
+        );
+        (ret == ERROR_SUCCESS).then(|| data)
+    }
+}
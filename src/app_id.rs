@@ -0,0 +1,28 @@
+use crate::bindings::Windows::Win32::{Foundation::*, System::Com::*, UI::Shell::*};
+use crate::error::ApiError;
+
+/// Sets the AppUserModelID of the current process.
+///
+/// This must be called before any window is shown
+/// to group taskbar buttons and attribute notifications and jump lists to the application.
+pub fn set_app_user_model_id(id: impl AsRef<str>) -> Result<(), ApiError> {
+    unsafe {
+        SetCurrentProcessExplicitAppUserModelID(id.as_ref()).ok()?;
+    }
+    Ok(())
+}
+
+/// Return the AppUserModelID of the current process if it is set.
+pub fn app_user_model_id() -> Option<String> {
+    unsafe {
+        let mut p = PWSTR::NULL;
+        GetCurrentProcessExplicitAppUserModelID(&mut p).ok().ok()?;
+        if p.is_null() {
+            return None;
+        }
+        let len = (0..isize::MAX).position(|i| *p.0.offset(i) == 0).unwrap();
+        let id = String::from_utf16_lossy(std::slice::from_raw_parts(p.0, len));
+        CoTaskMemFree(p.0 as _);
+        Some(id)
+    }
+}
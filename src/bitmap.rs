@@ -0,0 +1,148 @@
+use crate::bindings::Windows::Win32::{Foundation::*, Graphics::Gdi::*};
+use crate::geometry::*;
+use crate::last_error;
+
+/// A 32-bit bitmap in BGRA order.
+///
+/// Each row has `size.width * 4` bytes from top to bottom.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Bitmap {
+    pub size: PhysicalSize<u32>,
+    pub data: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Creates a transparent bitmap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bytes overflows `usize`.
+    pub fn new(size: PhysicalSize<u32>) -> Self {
+        let len = data_len(size).expect("the bitmap size overflows usize");
+        Self {
+            size,
+            data: vec![0; len],
+        }
+    }
+
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.size.width as usize * 4
+    }
+}
+
+/// Returns the number of bytes of a 32-bit bitmap, or `None` if it overflows `usize`.
+pub(crate) fn data_len(size: PhysicalSize<u32>) -> Option<usize> {
+    (size.width as usize)
+        .checked_mul(size.height as usize)
+        .and_then(|n| n.checked_mul(4))
+}
+
+pub(crate) fn bitmap_info(size: PhysicalSize<u32>) -> BITMAPINFO {
+    BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as _,
+            biWidth: size.width as i32,
+            biHeight: -(size.height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB as _,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// The returned bitmap must be deleted by `DeleteObject`.
+pub(crate) fn create_dib_section(bitmap: &Bitmap) -> Option<HBITMAP> {
+    unsafe {
+        let info = bitmap_info(bitmap.size);
+        let mut bits = std::ptr::null_mut();
+        let hbmp = CreateDIBSection(HDC::NULL, &info, DIB_RGB_COLORS, &mut bits, HANDLE::NULL, 0);
+        if hbmp == HBITMAP::NULL || bits.is_null() {
+            last_error!("CreateDIBSection");
+            return None;
+        }
+        let len = bitmap
+            .data
+            .len()
+            .min(bitmap.stride() * bitmap.size.height as usize);
+        std::ptr::copy_nonoverlapping(bitmap.data.as_ptr(), bits as *mut u8, len);
+        Some(hbmp)
+    }
+}
+
+/// Captures the client area of the window with `PrintWindow`, or `BitBlt` if it fails.
+#[cfg(feature = "window_capture")]
+pub(crate) fn capture_window(hwnd: HWND) -> Result<Bitmap, crate::error::ApiError> {
+    use crate::bindings::Windows::Win32::{
+        Storage::Xps::*, UI::WindowsAndMessaging::GetClientRect,
+    };
+    use crate::error::ApiError;
+
+    // Renders windows that use DirectComposition such as DirectX swap chains.
+    const PW_RENDERFULLCONTENT: u32 = 0x00000002;
+
+    unsafe {
+        let mut rc = RECT::default();
+        GetClientRect(hwnd, &mut rc);
+        let size = PhysicalSize::new((rc.right - rc.left) as u32, (rc.bottom - rc.top) as u32);
+        let mut bitmap = Bitmap::new(size);
+        if size.width == 0 || size.height == 0 {
+            return Ok(bitmap);
+        }
+        let hdc = GetDC(hwnd);
+        if hdc.0 == 0 {
+            return Err(ApiError::new().with_function("GetDC"));
+        }
+        let mem_dc = CreateCompatibleDC(hdc);
+        let info = bitmap_info(size);
+        let mut bits = std::ptr::null_mut();
+        let hbmp = CreateDIBSection(mem_dc, &info, DIB_RGB_COLORS, &mut bits, HANDLE::NULL, 0);
+        if hbmp == HBITMAP::NULL || bits.is_null() {
+            let e = ApiError::new().with_function("CreateDIBSection");
+            DeleteDC(mem_dc);
+            ReleaseDC(hwnd, hdc);
+            return Err(e);
+        }
+        let prev = SelectObject(mem_dc, hbmp);
+        let printed = PrintWindow(
+            hwnd,
+            mem_dc,
+            PRINT_WINDOW_FLAGS(PW_CLIENTONLY.0 | PW_RENDERFULLCONTENT),
+        )
+        .as_bool();
+        let ret = if printed
+            || BitBlt(
+                mem_dc,
+                0,
+                0,
+                size.width as _,
+                size.height as _,
+                hdc,
+                0,
+                0,
+                SRCCOPY,
+            )
+            .as_bool()
+        {
+            std::ptr::copy_nonoverlapping(
+                bits as *const u8,
+                bitmap.data.as_mut_ptr(),
+                bitmap.data.len(),
+            );
+            // GDI does not write the alpha channel.
+            for pixel in bitmap.data.chunks_exact_mut(4) {
+                pixel[3] = 0xff;
+            }
+            Ok(bitmap)
+        } else {
+            Err(ApiError::new().with_function("BitBlt"))
+        };
+        SelectObject(mem_dc, prev);
+        DeleteObject(hbmp);
+        DeleteDC(mem_dc);
+        ReleaseDC(hwnd, hdc);
+        ret
+    }
+}
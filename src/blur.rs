@@ -0,0 +1,103 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Dwm::*, Graphics::Gdi::HRGN, System::LibraryLoader::*,
+};
+use crate::last_error;
+use crate::theme::Color;
+use std::ffi::c_void;
+
+/// Describes blur effects behind the window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlurEffect {
+    Disabled,
+    Blur,
+    /// `alpha` is the opacity of `tint`.
+    Acrylic {
+        tint: Color,
+        alpha: u8,
+    },
+}
+
+const WCA_ACCENT_POLICY: u32 = 19;
+
+const ACCENT_DISABLED: u32 = 0;
+const ACCENT_ENABLE_BLURBEHIND: u32 = 3;
+const ACCENT_ENABLE_ACRYLICBLURBEHIND: u32 = 4;
+
+#[repr(C)]
+struct AccentPolicy {
+    accent_state: u32,
+    accent_flags: u32,
+    gradient_color: u32,
+    animation_id: u32,
+}
+
+#[repr(C)]
+struct WindowCompositionAttribData {
+    attrib: u32,
+    data: *mut c_void,
+    size: usize,
+}
+
+type SetWindowCompositionAttribute =
+    unsafe extern "system" fn(HWND, *mut WindowCompositionAttribData) -> BOOL;
+
+pub(crate) fn enable_blur_behind(hwnd: HWND, enable: bool) {
+    unsafe {
+        let bb = DWM_BLURBEHIND {
+            dwFlags: DWM_BB_ENABLE,
+            fEnable: enable.into(),
+            hRgnBlur: HRGN::NULL,
+            fTransitionOnMaximized: false.into(),
+        };
+        if DwmEnableBlurBehindWindow(hwnd, &bb).is_err() {
+            log::error!("DwmEnableBlurBehindWindow failed");
+        }
+    }
+}
+
+pub(crate) fn set_blur_effect(hwnd: HWND, effect: BlurEffect) {
+    unsafe {
+        // SetWindowCompositionAttribute is undocumented, so it is not in the import library.
+        let f = match GetProcAddress(
+            GetModuleHandleW("user32.dll"),
+            "SetWindowCompositionAttribute",
+        ) {
+            Some(f) => std::mem::transmute::<_, SetWindowCompositionAttribute>(f),
+            None => {
+                last_error!("GetProcAddress");
+                return;
+            }
+        };
+        let mut policy = match effect {
+            BlurEffect::Disabled => AccentPolicy {
+                accent_state: ACCENT_DISABLED,
+                accent_flags: 0,
+                gradient_color: 0,
+                animation_id: 0,
+            },
+            BlurEffect::Blur => AccentPolicy {
+                accent_state: ACCENT_ENABLE_BLURBEHIND,
+                accent_flags: 0,
+                gradient_color: 0,
+                animation_id: 0,
+            },
+            BlurEffect::Acrylic { tint, alpha } => AccentPolicy {
+                accent_state: ACCENT_ENABLE_ACRYLICBLURBEHIND,
+                accent_flags: 0,
+                gradient_color: tint.r as u32
+                    | ((tint.g as u32) << 8)
+                    | ((tint.b as u32) << 16)
+                    | ((alpha as u32) << 24),
+                animation_id: 0,
+            },
+        };
+        let mut data = WindowCompositionAttribData {
+            attrib: WCA_ACCENT_POLICY,
+            data: &mut policy as *mut AccentPolicy as _,
+            size: std::mem::size_of::<AccentPolicy>(),
+        };
+        if !f(hwnd, &mut data).as_bool() {
+            last_error!("SetWindowCompositionAttribute");
+        }
+    }
+}
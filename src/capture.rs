@@ -0,0 +1,308 @@
+//! Captures monitors with the Desktop Duplication API.
+//!
+//! To use, specify `"capture"` feature.
+//!
+//! ```no_run
+//! let monitor = wita::get_monitors().into_iter().find(|m| m.is_primary).unwrap();
+//! let (tx, rx) = std::sync::mpsc::channel();
+//! let thread = wita::capture::start(&monitor, move |frame| {
+//!     tx.send(frame).is_ok()
+//! })
+//! .unwrap();
+//! let frame = rx.recv().unwrap();
+//! thread.stop();
+//! ```
+
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Direct3D11::*, Graphics::Dxgi::*, Graphics::Gdi::HMONITOR,
+};
+use crate::bitmap::Bitmap;
+use crate::error::ApiError;
+use crate::geometry::*;
+use crate::monitor::Monitor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use windows::{Interface, HRESULT};
+
+const DXGI_ERROR_NOT_FOUND: HRESULT = HRESULT(0x887a0002);
+const DXGI_ERROR_WAIT_TIMEOUT: HRESULT = HRESULT(0x887a0027);
+const DXGI_ERROR_UNSUPPORTED: HRESULT = HRESULT(0x887a0004);
+
+const DXGI_FORMAT_R8G8B8A8_UNORM: u32 = 28;
+const DXGI_FORMAT_R8G8B8A8_UNORM_SRGB: u32 = 29;
+const DXGI_FORMAT_B8G8R8A8_UNORM: u32 = 87;
+const DXGI_FORMAT_B8G8R8X8_UNORM: u32 = 88;
+const DXGI_FORMAT_B8G8R8A8_UNORM_SRGB: u32 = 91;
+const DXGI_FORMAT_B8G8R8X8_UNORM_SRGB: u32 = 93;
+
+/// The interval to check whether the capture has been stopped.
+const FRAME_TIMEOUT_MS: u32 = 100;
+
+/// A captured frame.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub bitmap: Bitmap,
+    /// The time stamp of the last desktop image update in the performance counter.
+    pub last_present_time: i64,
+}
+
+/// Duplicates the output of a monitor.
+pub struct MonitorCapture {
+    device: ID3D11Device,
+    context: ID3D11DeviceContext,
+    duplication: IDXGIOutputDuplication,
+    staging: Option<ID3D11Texture2D>,
+}
+
+unsafe fn find_output(hmonitor: HMONITOR) -> Result<(IDXGIAdapter1, IDXGIOutput), ApiError> {
+    let factory: IDXGIFactory1 = CreateDXGIFactory1()?;
+    let mut i = 0;
+    loop {
+        let mut adapter = None;
+        let hr = factory.EnumAdapters1(i, &mut adapter);
+        if hr == DXGI_ERROR_NOT_FOUND {
+            break;
+        }
+        hr.ok()?;
+        let adapter = adapter.unwrap();
+        let mut j = 0;
+        loop {
+            let mut output = None;
+            let hr = adapter.EnumOutputs(j, &mut output);
+            if hr == DXGI_ERROR_NOT_FOUND {
+                break;
+            }
+            hr.ok()?;
+            let output = output.unwrap();
+            let mut desc = DXGI_OUTPUT_DESC::default();
+            output.GetDesc(&mut desc).ok()?;
+            if desc.Monitor == hmonitor {
+                return Ok((adapter, output));
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    Err(ApiError::from_hresult(DXGI_ERROR_NOT_FOUND).with_function("IDXGIAdapter1::EnumOutputs"))
+}
+
+/// Returns the bytes per pixel of the format and whether the red and blue channels are swapped against BGRA.
+///
+/// `None` if the format cannot be converted to `Bitmap` such as the HDR formats.
+fn pixel_layout(format: DXGI_FORMAT) -> Option<(usize, bool)> {
+    match format.0 as u32 {
+        DXGI_FORMAT_B8G8R8A8_UNORM
+        | DXGI_FORMAT_B8G8R8X8_UNORM
+        | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB
+        | DXGI_FORMAT_B8G8R8X8_UNORM_SRGB => Some((4, false)),
+        DXGI_FORMAT_R8G8B8A8_UNORM | DXGI_FORMAT_R8G8B8A8_UNORM_SRGB => Some((4, true)),
+        _ => None,
+    }
+}
+
+impl MonitorCapture {
+    pub fn new(monitor: &Monitor) -> Result<Self, ApiError> {
+        unsafe {
+            let (adapter, output) = find_output(monitor.hmonitor())?;
+            let mut device = None;
+            let mut context = None;
+            D3D11CreateDevice(
+                &adapter,
+                D3D_DRIVER_TYPE_UNKNOWN,
+                HINSTANCE::NULL,
+                D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+                std::ptr::null(),
+                0,
+                D3D11_SDK_VERSION,
+                &mut device,
+                std::ptr::null_mut(),
+                &mut context,
+            )
+            .ok()?;
+            let device = device.unwrap();
+            let output = output.cast::<IDXGIOutput1>()?;
+            let mut duplication = None;
+            output.DuplicateOutput(&device, &mut duplication).ok()?;
+            Ok(Self {
+                device,
+                context: context.unwrap(),
+                duplication: duplication.unwrap(),
+                staging: None,
+            })
+        }
+    }
+
+    /// Waits for the next desktop image and returns it.
+    ///
+    /// Returns `Ok(None)` if the timeout elapses.
+    /// If the error is `DXGI_ERROR_ACCESS_LOST` such as by a mode change, create a new `MonitorCapture`.
+    /// The error is `DXGI_ERROR_UNSUPPORTED` if the desktop image is not in a 8-bit RGBA or BGRA format.
+    pub fn next_frame(&mut self, timeout_ms: u32) -> Result<Option<Frame>, ApiError> {
+        unsafe {
+            let mut info = DXGI_OUTDUPL_FRAME_INFO::default();
+            let mut resource = None;
+            let hr = self
+                .duplication
+                .AcquireNextFrame(timeout_ms, &mut info, &mut resource);
+            if hr == DXGI_ERROR_WAIT_TIMEOUT {
+                return Ok(None);
+            }
+            hr.ok()?;
+            let ret = self.read_frame(resource.unwrap(), info.LastPresentTime);
+            self.duplication.ReleaseFrame().ok()?;
+            ret.map(Some)
+        }
+    }
+
+    unsafe fn read_frame(
+        &mut self,
+        resource: IDXGIResource,
+        last_present_time: i64,
+    ) -> Result<Frame, ApiError> {
+        let texture = resource.cast::<ID3D11Texture2D>()?;
+        let mut desc = D3D11_TEXTURE2D_DESC::default();
+        texture.GetDesc(&mut desc);
+        let (bpp, swap_rb) = pixel_layout(desc.Format)
+            .ok_or_else(|| ApiError::from_hresult(DXGI_ERROR_UNSUPPORTED))?;
+        let reusable = self.staging.as_ref().map_or(false, |staging| {
+            let mut current = D3D11_TEXTURE2D_DESC::default();
+            staging.GetDesc(&mut current);
+            current.Width == desc.Width
+                && current.Height == desc.Height
+                && current.Format == desc.Format
+        });
+        if !reusable {
+            let staging_desc = D3D11_TEXTURE2D_DESC {
+                Width: desc.Width,
+                Height: desc.Height,
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: desc.Format,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                Usage: D3D11_USAGE_STAGING,
+                BindFlags: D3D11_BIND_FLAG(0),
+                CPUAccessFlags: D3D11_CPU_ACCESS_READ,
+                MiscFlags: D3D11_RESOURCE_MISC_FLAG(0),
+            };
+            let mut staging = None;
+            self.device
+                .CreateTexture2D(&staging_desc, std::ptr::null(), &mut staging)
+                .ok()?;
+            self.staging = staging;
+        }
+        let staging = self.staging.as_ref().unwrap();
+        self.context.CopyResource(staging, &texture);
+        let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+        self.context
+            .Map(staging, 0, D3D11_MAP_READ, 0, &mut mapped)
+            .ok()?;
+        let mut bitmap = Bitmap::new(PhysicalSize::new(desc.Width, desc.Height));
+        let stride = bitmap.stride();
+        let width = desc.Width as usize;
+        for (y, dst) in bitmap.data.chunks_exact_mut(stride).enumerate() {
+            let src = std::slice::from_raw_parts(
+                (mapped.pData as *const u8).add(y * mapped.RowPitch as usize),
+                width * bpp,
+            );
+            for (dst, src) in dst.chunks_exact_mut(4).zip(src.chunks_exact(bpp)) {
+                dst.copy_from_slice(&src[..4]);
+                if swap_rb {
+                    dst.swap(0, 2);
+                }
+            }
+        }
+        self.context.Unmap(staging, 0);
+        Ok(Frame {
+            bitmap,
+            last_present_time,
+        })
+    }
+}
+
+/// A thread that captures a monitor.
+///
+/// The thread stops when this object is dropped.
+pub struct CaptureThread {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CaptureThread {
+    /// Stops capturing and waits for the thread.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for CaptureThread {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+/// Starts capturing the monitor on a new thread.
+///
+/// `f` is called with each frame on the thread, and the capture stops when `f` returns `false`.
+/// The capture also stops if the duplication fails such as by a mode change.
+pub fn start<F>(monitor: &Monitor, mut f: F) -> Result<CaptureThread, ApiError>
+where
+    F: FnMut(Frame) -> bool + Send + 'static,
+{
+    let monitor = monitor.clone();
+    let stop = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+    let handle = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            let mut capture = match MonitorCapture::new(&monitor) {
+                Ok(capture) => {
+                    tx.send(Ok(())).ok();
+                    capture
+                }
+                Err(e) => {
+                    tx.send(Err(e)).ok();
+                    return;
+                }
+            };
+            while !stop.load(Ordering::Relaxed) {
+                match capture.next_frame(FRAME_TIMEOUT_MS) {
+                    Ok(Some(frame)) => {
+                        if !f(frame) {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("{}", e);
+                        break;
+                    }
+                }
+            }
+        })
+    };
+    match rx.recv() {
+        Ok(Ok(())) => Ok(CaptureThread {
+            stop,
+            handle: Some(handle),
+        }),
+        Ok(Err(e)) => {
+            handle.join().ok();
+            Err(e)
+        }
+        Err(_) => match handle.join() {
+            Err(e) => std::panic::resume_unwind(e),
+            Ok(_) => unreachable!(),
+        },
+    }
+}
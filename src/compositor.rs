@@ -0,0 +1,113 @@
+//! Synchronizes with the desktop compositor.
+//!
+//! This is for presenters without a swap chain such as DirectComposition with
+//! `WindowBuilder::no_redirection_bitmap`, which cannot wait for the vertical blank by `Present`.
+
+use crate::api::load_proc;
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Dwm::*, System::LibraryLoader::*, System::Performance::*,
+};
+use crate::error::ApiError;
+use std::sync::Once;
+use std::time::Duration;
+
+const INFINITE: u32 = 0xffffffff;
+const WAIT_TIMEOUT: u32 = 0x00000102;
+const WAIT_FAILED: u32 = 0xffffffff;
+const ERROR_PROC_NOT_FOUND: u32 = 127;
+
+type DCompositionWaitForCompositorClockFn =
+    unsafe extern "system" fn(u32, *const HANDLE, u32) -> u32;
+
+/// The timing of the composition.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CompositionTiming {
+    /// The refresh rate of the monitor in Hz.
+    pub refresh_rate: f64,
+    /// The interval between vertical blanks.
+    pub refresh_period: Duration,
+    /// The performance counter value at the last vertical blank.
+    pub last_vblank: u64,
+    /// The number of refreshes since the compositor started.
+    pub refresh_count: u64,
+}
+
+/// Returns the timing of the composition for the whole desktop.
+pub fn composition_timing() -> Result<CompositionTiming, ApiError> {
+    unsafe {
+        let mut info = DWM_TIMING_INFO {
+            cbSize: std::mem::size_of::<DWM_TIMING_INFO>() as _,
+            ..Default::default()
+        };
+        DwmGetCompositionTimingInfo(HWND::NULL, &mut info).ok()?;
+        let mut freq = 0;
+        QueryPerformanceFrequency(&mut freq);
+        let rate = info.rateRefresh;
+        let refresh_rate = if rate.uiDenominator == 0 {
+            0.0
+        } else {
+            rate.uiNumerator as f64 / rate.uiDenominator as f64
+        };
+        let period = info.qpcRefreshPeriod;
+        let refresh_period = if freq > 0 {
+            Duration::from_secs_f64(period as f64 / freq as f64)
+        } else {
+            Duration::default()
+        };
+        Ok(CompositionTiming {
+            refresh_rate,
+            refresh_period,
+            last_vblank: info.qpcVBlank,
+            refresh_count: info.cRefresh,
+        })
+    }
+}
+
+fn wait_for_compositor_clock_fn() -> Option<DCompositionWaitForCompositorClockFn> {
+    static LOAD: Once = Once::new();
+    static mut FUNCTION: Option<DCompositionWaitForCompositorClockFn> = None;
+    unsafe {
+        LOAD.call_once(|| {
+            FUNCTION = load_proc(
+                LoadLibraryW("dcomp.dll"),
+                "DCompositionWaitForCompositorClock",
+            );
+        });
+        FUNCTION
+    }
+}
+
+/// Returns `true` if `wait_for_compositor_clock` is available.
+///
+/// The compositor clock is supported from Windows 11.
+pub fn is_compositor_clock_supported() -> bool {
+    wait_for_compositor_clock_fn().is_some()
+}
+
+/// Waits for the next tick of the compositor clock.
+///
+/// Returns `Ok(false)` if the timeout elapses, and `None` waits infinitely.
+pub fn wait_for_compositor_clock(timeout: Option<Duration>) -> Result<bool, ApiError> {
+    let f = wait_for_compositor_clock_fn().ok_or_else(|| {
+        ApiError::from_win32(ERROR_PROC_NOT_FOUND)
+            .with_function("DCompositionWaitForCompositorClock")
+    })?;
+    let timeout = timeout.map_or(INFINITE, |t| t.as_millis().min(INFINITE as u128 - 1) as u32);
+    unsafe {
+        match f(0, std::ptr::null(), timeout) {
+            WAIT_TIMEOUT => Ok(false),
+            WAIT_FAILED => Err(ApiError::new().with_function("DCompositionWaitForCompositorClock")),
+            _ => Ok(true),
+        }
+    }
+}
+
+/// Blocks until the compositor has finished the current frame.
+///
+/// This is the fallback for systems without the compositor clock.
+pub fn flush() -> Result<(), ApiError> {
+    unsafe {
+        DwmFlush().ok()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use crate::bindings::Windows::Win32::{Foundation::*, UI::WindowsAndMessaging::*};
+use crate::geometry::*;
+use crate::monitor::*;
+use crate::window::{Style, WindowStyle, WINDOW_STYLE_MASK};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The settings of a window that can be loaded from files such as TOML and JSON.
+///
+/// With `"serde"` feature, the omitted fields are filled with the default values.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct WindowConfig {
+    pub title: String,
+    pub inner_size: LogicalSize<u32>,
+    /// `None` uses the default position of `WindowBuilder`.
+    pub position: Option<ScreenPosition>,
+    pub borderless: bool,
+    pub resizable: bool,
+    pub has_minimize_box: bool,
+    pub has_maximize_box: bool,
+    pub visible: bool,
+    pub maximized: bool,
+    pub ime: bool,
+    pub accept_drag_files: bool,
+    /// The path of an icon file.
+    pub icon: Option<PathBuf>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            inner_size: LogicalSize::new(640, 480),
+            position: None,
+            borderless: false,
+            resizable: true,
+            has_minimize_box: true,
+            has_maximize_box: true,
+            visible: true,
+            maximized: false,
+            ime: false,
+            accept_drag_files: false,
+            icon: None,
+        }
+    }
+}
+
+/// A snapshot of the layout of a window to restore it such as on the next startup.
+///
+/// Get it by `Window::session` and apply it by `Window::restore_session`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowSession {
+    /// The position when the window is neither maximized nor minimized.
+    ///
+    /// This is in the workspace coordinates which exclude the taskbar.
+    pub position: ScreenPosition,
+    /// The size of the whole window when the window is neither maximized nor minimized.
+    pub size: PhysicalSize<u32>,
+    pub maximized: bool,
+    pub style: WindowStyle,
+    /// The position of the monitor that the window was on.
+    pub monitor: ScreenPosition,
+    /// `true` if the window without the caption covered the whole monitor.
+    pub fullscreen: bool,
+}
+
+fn monitor_of(rc: &RECT) -> Option<Monitor> {
+    monitor_from_point(ScreenPosition::new(
+        (rc.left + rc.right) / 2,
+        (rc.top + rc.bottom) / 2,
+    ))
+}
+
+pub(crate) fn get_session(hwnd: HWND, style: WindowStyle) -> WindowSession {
+    unsafe {
+        let mut wp = WINDOWPLACEMENT {
+            length: std::mem::size_of::<WINDOWPLACEMENT>() as _,
+            ..Default::default()
+        };
+        GetWindowPlacement(hwnd, &mut wp);
+        let mut rc = RECT::default();
+        GetWindowRect(hwnd, &mut rc);
+        let monitor = monitor_of(&rc);
+        let fullscreen = monitor.as_ref().map_or(false, |m| {
+            (style.value() & WS_CAPTION.0) != WS_CAPTION.0
+                && rc.left == m.position.x
+                && rc.top == m.position.y
+                && (rc.right - rc.left) as u32 == m.size.width
+                && (rc.bottom - rc.top) as u32 == m.size.height
+        });
+        let normal = wp.rcNormalPosition;
+        WindowSession {
+            position: ScreenPosition::new(normal.left, normal.top),
+            size: PhysicalSize::new(
+                (normal.right - normal.left) as u32,
+                (normal.bottom - normal.top) as u32,
+            ),
+            maximized: wp.showCmd == SW_SHOWMAXIMIZED,
+            style,
+            monitor: monitor.map_or(ScreenPosition::new(0, 0), |m| m.position),
+            fullscreen,
+        }
+    }
+}
+
+/// Applies `session` in the message loop.
+///
+/// The window is moved onto the primary monitor if the monitor has been disconnected.
+pub(crate) fn apply_session(hwnd: HWND, session: &WindowSession) {
+    let mut rc = RECT {
+        left: session.position.x,
+        top: session.position.y,
+        right: session.position.x + session.size.width as i32,
+        bottom: session.position.y + session.size.height as i32,
+    };
+    if monitor_from_point(session.monitor).is_none() {
+        if let Some(primary) = get_monitors().into_iter().find(|m| m.is_primary) {
+            let dx = primary.position.x - session.monitor.x;
+            let dy = primary.position.y - session.monitor.y;
+            rc.left += dx;
+            rc.top += dy;
+            rc.right += dx;
+            rc.bottom += dy;
+        }
+    }
+    unsafe {
+        // Only the bits of `WindowStyle` are patched to keep the others such as `WS_VISIBLE`.
+        let style = GetWindowLongPtrW(hwnd, GWL_STYLE) as u32;
+        let style = (style & !WINDOW_STYLE_MASK) | (session.style.value() & WINDOW_STYLE_MASK);
+        SetWindowLongPtrW(hwnd, GWL_STYLE, style as _);
+        let wp = WINDOWPLACEMENT {
+            length: std::mem::size_of::<WINDOWPLACEMENT>() as _,
+            showCmd: if session.maximized && !session.fullscreen {
+                SW_SHOWMAXIMIZED
+            } else {
+                SW_SHOWNORMAL
+            },
+            rcNormalPosition: rc,
+            ..Default::default()
+        };
+        SetWindowPlacement(hwnd, &wp);
+        let monitor = if session.fullscreen {
+            monitor_of(&rc)
+        } else {
+            None
+        };
+        if let Some(m) = monitor {
+            SetWindowPos(
+                hwnd,
+                HWND(0),
+                m.position.x,
+                m.position.y,
+                m.size.width as _,
+                m.size.height as _,
+                SWP_NOZORDER | SWP_FRAMECHANGED,
+            );
+        } else {
+            SetWindowPos(
+                hwnd,
+                HWND(0),
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize_partial() {
+        let config: WindowConfig =
+            serde_json::from_str(r#"{ "title": "wita", "resizable": false }"#).unwrap();
+        assert_eq!(config.title, "wita");
+        assert!(!config.resizable);
+        assert_eq!(config.inner_size, LogicalSize::new(640, 480));
+        assert!(config.visible);
+        let dest: WindowConfig =
+            serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(dest, config);
+    }
+}
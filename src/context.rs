@@ -1,19 +1,93 @@
 use crate::bindings::Windows::Win32::{Foundation::*, UI::WindowsAndMessaging::*};
-use crate::{device::*, event::EventHandler, event::OtherParams, window::LocalWindow};
+use crate::{
+    device::*, event::DroppedItem, event::EventHandler, event::OtherParams, geometry::PhysicalSize,
+    window::LocalWindow,
+};
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::panic::resume_unwind;
+use std::rc::Rc;
 
 /// Describes event loop types.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum RunType {
     Idle,
     Wait,
 }
 
+/// Describes DPI awareness of the process.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DpiAwareness {
+    Unaware,
+    System,
+    PerMonitor,
+    PerMonitorV2,
+}
+
+/// Describes how a panic in `EventHandler` is handled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PanicPolicy {
+    /// Drops the event handler and resumes the panic from `run`.
+    Unwind,
+    /// Delivers the panic payload to `EventHandler::handler_panicked` and continues the event loop.
+    Notify,
+}
+
+/// Options of the event loop.
+#[derive(Clone, Debug)]
+pub struct RunOptions {
+    pub(crate) run_type: RunType,
+    pub(crate) dpi_awareness: Option<DpiAwareness>,
+    pub(crate) panic_policy: PanicPolicy,
+    pub(crate) max_fps: Option<f64>,
+}
+
+impl RunOptions {
+    pub fn new(run_type: RunType) -> Self {
+        Self {
+            run_type,
+            dpi_awareness: Some(DpiAwareness::PerMonitorV2),
+            panic_policy: PanicPolicy::Unwind,
+            max_fps: None,
+        }
+    }
+
+    /// Sets DPI awareness of the process.
+    ///
+    /// `None` does not change DPI awareness, for example when the host process has already set it.
+    /// The default is `Some(DpiAwareness::PerMonitorV2)`.
+    pub fn dpi_awareness(mut self, awareness: Option<DpiAwareness>) -> Self {
+        self.dpi_awareness = awareness;
+        self
+    }
+
+    /// Sets how a panic in `EventHandler` is handled.
+    ///
+    /// The default is `PanicPolicy::Unwind`.
+    pub fn panic_policy(mut self, policy: PanicPolicy) -> Self {
+        self.panic_policy = policy;
+        self
+    }
+
+    /// Limits the rate of `EventHandler::idle` with `FramePacer`.
+    ///
+    /// This takes effect only with `RunType::Idle`. Messages are still processed while waiting for the next frame.
+    /// `run_with_options` returns an error if `fps` is not finite or not greater than 0.
+    /// The default is `None`.
+    pub fn max_fps(mut self, fps: Option<f64>) -> Self {
+        self.max_fps = fps;
+        self
+    }
+}
+
 pub(crate) struct ContextState {
     pub mouse_buttons: Vec<MouseButton>,
-    pub entered_window: Option<LocalWindow>,
+    pub entered_window: Option<Rc<LocalWindow>>,
     pub resizing: bool,
+    pub moving: bool,
+    pub size_move_start: Option<PhysicalSize<u32>>,
+    pub consumed_key: bool,
 }
 
 impl ContextState {
@@ -22,24 +96,40 @@ impl ContextState {
             mouse_buttons: Vec::with_capacity(5),
             entered_window: None,
             resizing: false,
+            moving: false,
+            size_move_start: None,
+            consumed_key: false,
         }
     }
 }
 
+/// Buffers that are reused across messages to reduce allocations.
+#[derive(Default)]
+pub(crate) struct ScratchBuffers {
+    pub wide: Vec<u16>,
+    pub dropped_items: Vec<DroppedItem>,
+}
+
 pub(crate) struct Context {
     state: ContextState,
-    window_table: Vec<(HWND, LocalWindow)>,
+    window_table: HashSet<isize>,
     event_handler: Option<Box<dyn Any>>,
     unwind: Option<Box<dyn Any + Send>>,
+    panic_policy: PanicPolicy,
+    run_type: RunType,
+    buffers: ScratchBuffers,
 }
 
 impl Context {
     fn new() -> Self {
         Self {
             state: ContextState::new(),
-            window_table: Vec::new(),
+            window_table: HashSet::new(),
             event_handler: None,
             unwind: None,
+            panic_policy: PanicPolicy::Unwind,
+            run_type: RunType::Wait,
+            buffers: ScratchBuffers::default(),
         }
     }
 }
@@ -61,28 +151,11 @@ pub fn is_context_null() -> bool {
 }
 
 #[inline]
-pub(crate) fn push_window(hwnd: HWND, wnd: LocalWindow) {
+pub(crate) fn push_window(hwnd: HWND) {
     let p = CONTEXT.with(|ctx| *ctx.borrow());
     unsafe {
         let ctx = &mut *p;
-        ctx.window_table.push((hwnd, wnd));
-    }
-}
-
-#[inline]
-pub(crate) fn find_window(hwnd: HWND) -> Option<LocalWindow> {
-    let p = CONTEXT.with(|ctx| *ctx.borrow());
-    unsafe {
-        let ctx = &*p;
-        ctx.window_table.iter().find_map(
-            |(h, wnd)| {
-                if *h == hwnd {
-                    Some(wnd.clone())
-                } else {
-                    None
-                }
-            },
-        )
+        ctx.window_table.insert(hwnd.0);
     }
 }
 
@@ -91,12 +164,7 @@ pub fn remove_window(hwnd: HWND) {
     let p = CONTEXT.with(|ctx| *ctx.borrow());
     unsafe {
         let ctx = &mut *p;
-        ctx.window_table.remove(
-            ctx.window_table
-                .iter()
-                .position(|(h, _)| *h == hwnd)
-                .unwrap(),
-        );
+        ctx.window_table.remove(&hwnd.0);
     }
 }
 
@@ -133,6 +201,7 @@ where
     F: FnOnce(&mut T, &mut ContextState),
     T: EventHandler + 'static,
 {
+    trace_span!("call_handler");
     let p = CONTEXT.with(|ctx| *ctx.borrow());
     unsafe {
         let ctx = &mut *p;
@@ -180,6 +249,63 @@ where
     }
 }
 
+#[inline]
+pub fn set_panic_policy(policy: PanicPolicy) {
+    let p = CONTEXT.with(|ctx| *ctx.borrow());
+    unsafe {
+        let ctx = &mut *p;
+        ctx.panic_policy = policy;
+    }
+}
+
+#[inline]
+pub fn panic_policy() -> PanicPolicy {
+    let p = CONTEXT.with(|ctx| *ctx.borrow());
+    unsafe {
+        let ctx = &*p;
+        ctx.panic_policy
+    }
+}
+
+#[inline]
+pub fn set_run_type(run_type: RunType) {
+    let p = CONTEXT.with(|ctx| *ctx.borrow());
+    unsafe {
+        let ctx = &mut *p;
+        ctx.run_type = run_type;
+    }
+}
+
+#[inline]
+pub fn run_type() -> RunType {
+    let p = CONTEXT.with(|ctx| *ctx.borrow());
+    unsafe {
+        let ctx = &*p;
+        ctx.run_type
+    }
+}
+
+/// Takes the scratch buffers out of the context.
+///
+/// A nested message gets empty buffers while they are taken.
+#[inline]
+pub(crate) fn take_buffers() -> ScratchBuffers {
+    let p = CONTEXT.with(|ctx| *ctx.borrow());
+    unsafe {
+        let ctx = &mut *p;
+        std::mem::take(&mut ctx.buffers)
+    }
+}
+
+#[inline]
+pub(crate) fn restore_buffers(buffers: ScratchBuffers) {
+    let p = CONTEXT.with(|ctx| *ctx.borrow());
+    unsafe {
+        let ctx = &mut *p;
+        ctx.buffers = buffers;
+    }
+}
+
 #[inline]
 pub fn set_unwind(e: Box<dyn Any + Send>) {
     let p = CONTEXT.with(|ctx| *ctx.borrow());
@@ -0,0 +1,117 @@
+use crate::api::get_dpi_for_window;
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Direct2D::*, Graphics::Gdi::InvalidateRect,
+    UI::WindowsAndMessaging::GetClientRect,
+};
+use crate::error::ApiError;
+use crate::geometry::*;
+use std::cell::RefCell;
+use std::ffi::c_void;
+use windows::{Abi, Interface, HRESULT};
+
+const D2DERR_RECREATE_TARGET: HRESULT = HRESULT(0x8899000c_u32 as _);
+
+thread_local! {
+    static FACTORY: RefCell<Option<ID2D1Factory>> = RefCell::new(None);
+}
+
+fn factory() -> Result<ID2D1Factory, ApiError> {
+    FACTORY.with(|factory| {
+        let mut factory = factory.borrow_mut();
+        if let Some(factory) = factory.as_ref() {
+            return Ok(factory.clone());
+        }
+        let mut p: Option<ID2D1Factory> = None;
+        let f = unsafe {
+            D2D1CreateFactory(
+                D2D1_FACTORY_TYPE_SINGLE_THREADED,
+                &ID2D1Factory::IID,
+                std::ptr::null(),
+                p.set_abi(),
+            )
+            .and_some(p)?
+        };
+        *factory = Some(f.clone());
+        Ok(f)
+    })
+}
+
+unsafe fn create_target(hwnd: HWND) -> Result<ID2D1HwndRenderTarget, ApiError> {
+    let dpi = get_dpi_for_window(hwnd) as f32;
+    let mut rc = RECT::default();
+    GetClientRect(hwnd, &mut rc);
+    let mut target = None;
+    factory()?
+        .CreateHwndRenderTarget(
+            &D2D1_RENDER_TARGET_PROPERTIES {
+                dpiX: dpi,
+                dpiY: dpi,
+                ..Default::default()
+            },
+            &D2D1_HWND_RENDER_TARGET_PROPERTIES {
+                hwnd,
+                pixelSize: D2D_SIZE_U {
+                    width: (rc.right - rc.left) as _,
+                    height: (rc.bottom - rc.top) as _,
+                },
+                ..Default::default()
+            },
+            &mut target,
+        )
+        .ok()?;
+    Ok(target.unwrap())
+}
+
+/// An `ID2D1HwndRenderTarget` cached per window.
+///
+/// The target is created on the first draw, resized in `WM_SIZE` and recreated after the device has been lost.
+#[derive(Default)]
+pub(crate) struct RenderTarget(RefCell<Option<ID2D1HwndRenderTarget>>);
+
+impl RenderTarget {
+    /// Calls `f` with the raw `ID2D1HwndRenderTarget` between `BeginDraw` and `EndDraw`.
+    pub fn draw<F>(&self, hwnd: HWND, f: F) -> Result<(), ApiError>
+    where
+        F: FnOnce(*mut c_void),
+    {
+        unsafe {
+            let target = {
+                let mut target = self.0.borrow_mut();
+                if target.is_none() {
+                    *target = Some(create_target(hwnd)?);
+                }
+                target.clone().unwrap()
+            };
+            target.BeginDraw();
+            f(target.abi());
+            let hr = target.EndDraw(std::ptr::null_mut(), std::ptr::null_mut());
+            if hr == D2DERR_RECREATE_TARGET {
+                *self.0.borrow_mut() = None;
+                // The lost frame is redrawn with a new target.
+                InvalidateRect(hwnd, std::ptr::null(), false);
+                return Ok(());
+            }
+            hr.ok()?;
+            Ok(())
+        }
+    }
+
+    pub fn resize(&self, size: PhysicalSize<u32>) {
+        if let Some(target) = self.0.borrow().as_ref() {
+            unsafe {
+                target.Resize(&D2D_SIZE_U {
+                    width: size.width,
+                    height: size.height,
+                });
+            }
+        }
+    }
+
+    pub fn set_dpi(&self, dpi: u32) {
+        if let Some(target) = self.0.borrow().as_ref() {
+            unsafe {
+                target.SetDpi(dpi as f32, dpi as f32);
+            }
+        }
+    }
+}
@@ -23,6 +23,85 @@ pub enum MouseButton {
     Ex(u32),
 }
 
+/// A set of mouse buttons.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct MouseButtons(u8);
+
+impl MouseButtons {
+    const ALL: [MouseButton; 5] = [
+        MouseButton::Left,
+        MouseButton::Right,
+        MouseButton::Middle,
+        MouseButton::Ex(0),
+        MouseButton::Ex(1),
+    ];
+
+    fn bit(button: MouseButton) -> u8 {
+        match button {
+            MouseButton::Left => 0x01,
+            MouseButton::Right => 0x02,
+            MouseButton::Middle => 0x04,
+            MouseButton::Ex(0) => 0x08,
+            MouseButton::Ex(1) => 0x10,
+            MouseButton::Ex(_) => 0,
+        }
+    }
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, button: MouseButton) -> bool {
+        let bit = Self::bit(button);
+        bit != 0 && (self.0 & bit) != 0
+    }
+
+    pub fn insert(&mut self, button: MouseButton) {
+        self.0 |= Self::bit(button);
+    }
+
+    pub fn remove(&mut self, button: MouseButton) {
+        self.0 &= !Self::bit(button);
+    }
+
+    /// Returns an iterator over the buttons in the set.
+    pub fn iter(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        Self::ALL.iter().copied().filter(move |&b| self.contains(b))
+    }
+}
+
+impl std::ops::BitOr for MouseButtons {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitAnd for MouseButtons {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl From<MouseButton> for MouseButtons {
+    fn from(button: MouseButton) -> Self {
+        Self(Self::bit(button))
+    }
+}
+
+impl std::fmt::Debug for MouseButtons {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
 /// A mouse cursor position and pressed mouse buttons.
 #[derive(Clone, Debug)]
 pub struct MouseState<'a> {
@@ -30,6 +109,24 @@ pub struct MouseState<'a> {
     pub buttons: &'a [MouseButton],
 }
 
+impl<'a> MouseState<'a> {
+    /// Returns the pressed buttons as a set to check a button.
+    pub fn pressed(&self) -> MouseButtons {
+        self.buttons
+            .iter()
+            .fold(MouseButtons::empty(), |set, &button| set | button.into())
+    }
+}
+
+/// A point in the history of the mouse movement.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct MouseMovePoint {
+    /// The position in client coordinates.
+    pub position: PhysicalPosition<i32>,
+    /// The time stamp in milliseconds.
+    pub time: u32,
+}
+
 /// Describes keyboard key names.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum VirtualKey {
@@ -320,6 +417,13 @@ pub fn get_key_state(k: VirtualKey) -> bool {
     unsafe { GetKeyState(to_raw_virtual_key(k) as _) & 0x80 != 0 }
 }
 
+/// Returns `true` if the toggle key such as `CapsLock` is on.
+///
+/// The state is of the calling thread as well as `get_key_state`.
+pub fn is_key_toggled(k: VirtualKey) -> bool {
+    unsafe { GetKeyState(to_raw_virtual_key(k) as _) & 0x01 != 0 }
+}
+
 /// Get current key states.
 pub fn keyboard_state(keys: &mut Vec<VirtualKey>) {
     let mut buffer = [0u8; 256];
@@ -334,10 +438,129 @@ pub fn keyboard_state(keys: &mut Vec<VirtualKey>) {
     }
 }
 
+const VK_LBUTTON: u32 = 0x01;
+const VK_RBUTTON: u32 = 0x02;
+const VK_MBUTTON: u32 = 0x04;
+const VK_XBUTTON1: u32 = 0x05;
+const VK_XBUTTON2: u32 = 0x06;
+
+fn is_mouse_button_vk(vk: u32) -> bool {
+    matches!(
+        vk,
+        VK_LBUTTON | VK_RBUTTON | VK_MBUTTON | VK_XBUTTON1 | VK_XBUTTON2
+    )
+}
+
+/// A snapshot of the states of all keys.
+#[derive(Clone)]
+pub struct KeyboardState([u8; 256]);
+
+impl KeyboardState {
+    /// Takes the current key states of the thread.
+    pub fn get() -> Self {
+        let mut buffer = [0u8; 256];
+        unsafe {
+            GetKeyboardState(buffer.as_mut_ptr());
+        }
+        Self(buffer)
+    }
+
+    fn raw(&self, k: VirtualKey) -> u8 {
+        self.0
+            .get(to_raw_virtual_key(k) as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn is_pressed(&self, k: VirtualKey) -> bool {
+        self.raw(k) & 0x80 != 0
+    }
+
+    /// Returns `true` if the toggle key such as `CapsLock` is on.
+    pub fn is_toggled(&self, k: VirtualKey) -> bool {
+        self.raw(k) & 0x01 != 0
+    }
+
+    pub fn caps_lock(&self) -> bool {
+        self.is_toggled(VirtualKey::CapsLock)
+    }
+
+    pub fn num_lock(&self) -> bool {
+        self.is_toggled(VirtualKey::NumLock)
+    }
+
+    pub fn scroll_lock(&self) -> bool {
+        self.is_toggled(VirtualKey::ScrollLock)
+    }
+
+    /// Returns an iterator over the pressed keys.
+    ///
+    /// The mouse buttons are not included.
+    pub fn pressed_keys(&self) -> impl Iterator<Item = VirtualKey> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(i, k)| (*k & 0x80) != 0 && !is_mouse_button_vk(*i as u32))
+            .map(|(i, _)| as_virtual_key(i as u32))
+    }
+
+    /// Returns the raw states indexed by virtual-key codes.
+    pub fn as_raw(&self) -> &[u8; 256] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for KeyboardState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_list().entries(self.pressed_keys()).finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn mouse_buttons() {
+        let mut buttons = MouseButtons::empty();
+        assert!(buttons.is_empty());
+        buttons.insert(MouseButton::Left);
+        buttons.insert(MouseButton::Ex(1));
+        assert!(buttons.contains(MouseButton::Left));
+        assert!(!buttons.contains(MouseButton::Right));
+        assert!(!buttons.contains(MouseButton::Ex(2)));
+        assert_eq!(
+            buttons.iter().collect::<Vec<_>>(),
+            vec![MouseButton::Left, MouseButton::Ex(1)]
+        );
+        buttons.remove(MouseButton::Left);
+        assert_eq!(buttons, MouseButtons::from(MouseButton::Ex(1)));
+    }
+
+    #[test]
+    fn keyboard_state() {
+        let mut buffer = [0u8; 256];
+        buffer[to_raw_virtual_key(VirtualKey::Char('A')) as usize] = 0x80;
+        buffer[to_raw_virtual_key(VirtualKey::CapsLock) as usize] = 0x01;
+        buffer[VK_LBUTTON as usize] = 0x80;
+        let ks = KeyboardState(buffer);
+        assert!(ks.is_pressed(VirtualKey::Char('A')));
+        assert!(!ks.is_pressed(VirtualKey::Char('B')));
+        assert!(ks.caps_lock());
+        assert!(!ks.num_lock());
+        assert_eq!(
+            ks.pressed_keys().collect::<Vec<_>>(),
+            vec![VirtualKey::Char('A')]
+        );
+    }
+
+    #[test]
+    fn cursor_index() {
+        for &cursor in Cursor::ALL.iter() {
+            assert_eq!(Cursor::from_index(cursor.index()), cursor);
+        }
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn serde_test() {
@@ -465,6 +688,31 @@ pub enum Cursor {
 }
 
 impl Cursor {
+    const ALL: [Cursor; 14] = [
+        Self::AppStarting,
+        Self::Arrow,
+        Self::Cross,
+        Self::Hand,
+        Self::Help,
+        Self::IBeam,
+        Self::No,
+        Self::SizeAll,
+        Self::SizeNESW,
+        Self::SizeNS,
+        Self::SizeNWSE,
+        Self::SizeWE,
+        Self::SizeUpArrow,
+        Self::Wait,
+    ];
+
+    pub(crate) fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub(crate) fn from_index(index: u8) -> Self {
+        Self::ALL[index as usize]
+    }
+
     pub(crate) fn name(&self) -> PWSTR {
         match self {
             Self::AppStarting => IDC_APPSTARTING,
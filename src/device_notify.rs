@@ -0,0 +1,138 @@
+use crate::bindings::Windows::Win32::{Foundation::*, UI::WindowsAndMessaging::*};
+use crate::last_error;
+use crate::power::DEVICE_NOTIFY_WINDOW_HANDLE;
+use windows::Guid;
+
+pub(crate) const WM_DEVICECHANGE: u32 = 0x0219;
+
+pub(crate) const DBT_DEVICEARRIVAL: u32 = 0x8000;
+pub(crate) const DBT_DEVICEREMOVECOMPLETE: u32 = 0x8004;
+
+const DBT_DEVTYP_VOLUME: u32 = 0x0002;
+const DBT_DEVTYP_DEVICEINTERFACE: u32 = 0x0005;
+
+const GUID_DEVINTERFACE_HID: Guid = Guid::from_values(
+    0x4d1e55b2,
+    0xf16f,
+    0x11cf,
+    [0x88, 0xcb, 0x00, 0x11, 0x11, 0x00, 0x00, 0x30],
+);
+
+#[repr(C)]
+struct DevBroadcastHdr {
+    size: u32,
+    device_type: u32,
+    reserved: u32,
+}
+
+#[repr(C)]
+struct DevBroadcastVolume {
+    hdr: DevBroadcastHdr,
+    unit_mask: u32,
+    flags: u16,
+}
+
+#[repr(C)]
+struct DevBroadcastDeviceInterface {
+    hdr: DevBroadcastHdr,
+    class_guid: Guid,
+    name: [u16; 1],
+}
+
+/// Describes a device change that `RegisterDeviceNotification` reports.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum DeviceEventKind {
+    /// A volume such as a USB storage has been mounted on the drive letter.
+    VolumeArrived(char),
+    /// A volume has been removed from the drive letter.
+    VolumeRemoved(char),
+    /// A HID interface has been connected. The value is the device interface path.
+    HidArrived(String),
+    /// A HID interface has been disconnected. The value is the device interface path.
+    HidRemoved(String),
+}
+
+/// Decodes `WM_DEVICECHANGE` into the events.
+///
+/// A volume notification can contain multiple drive letters.
+pub(crate) unsafe fn decode(wparam: WPARAM, lparam: LPARAM) -> Vec<DeviceEventKind> {
+    let arrived = match wparam.0 as u32 {
+        DBT_DEVICEARRIVAL => true,
+        DBT_DEVICEREMOVECOMPLETE => false,
+        _ => return vec![],
+    };
+    if lparam.0 == 0 {
+        return vec![];
+    }
+    let hdr = &*(lparam.0 as *const DevBroadcastHdr);
+    match hdr.device_type {
+        DBT_DEVTYP_VOLUME => {
+            let volume = &*(lparam.0 as *const DevBroadcastVolume);
+            (0..26)
+                .filter(|i| volume.unit_mask & (1 << i) != 0)
+                .map(|i| {
+                    let drive = (b'A' + i as u8) as char;
+                    if arrived {
+                        DeviceEventKind::VolumeArrived(drive)
+                    } else {
+                        DeviceEventKind::VolumeRemoved(drive)
+                    }
+                })
+                .collect()
+        }
+        DBT_DEVTYP_DEVICEINTERFACE => {
+            let iface = &*(lparam.0 as *const DevBroadcastDeviceInterface);
+            if iface.class_guid != GUID_DEVINTERFACE_HID {
+                return vec![];
+            }
+            let name = iface.name.as_ptr();
+            let len = (0..).take_while(|&i| *name.add(i) != 0).count();
+            let path = String::from_utf16_lossy(std::slice::from_raw_parts(name, len));
+            if arrived {
+                vec![DeviceEventKind::HidArrived(path)]
+            } else {
+                vec![DeviceEventKind::HidRemoved(path)]
+            }
+        }
+        _ => vec![],
+    }
+}
+
+/// Registers the window for the HID interface notifications.
+///
+/// The volume notifications are broadcast to all top-level windows without the registration.
+pub(crate) struct DeviceNotification(*mut std::ffi::c_void);
+
+impl DeviceNotification {
+    pub fn new(hwnd: HWND) -> Option<Self> {
+        unsafe {
+            let filter = DevBroadcastDeviceInterface {
+                hdr: DevBroadcastHdr {
+                    size: std::mem::size_of::<DevBroadcastDeviceInterface>() as _,
+                    device_type: DBT_DEVTYP_DEVICEINTERFACE,
+                    reserved: 0,
+                },
+                class_guid: GUID_DEVINTERFACE_HID,
+                name: [0],
+            };
+            let h = RegisterDeviceNotificationW(
+                HANDLE(hwnd.0),
+                &filter as *const _ as _,
+                DEVICE_NOTIFY_WINDOW_HANDLE,
+            );
+            if h.is_null() {
+                last_error!("RegisterDeviceNotificationW");
+                return None;
+            }
+            Some(Self(h))
+        }
+    }
+}
+
+impl Drop for DeviceNotification {
+    fn drop(&mut self) {
+        unsafe {
+            UnregisterDeviceNotification(self.0);
+        }
+    }
+}
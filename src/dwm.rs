@@ -0,0 +1,180 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Dwm::*, Graphics::Gdi::*, System::Threading::*, UI::Accessibility::*,
+    UI::WindowsAndMessaging::*,
+};
+use crate::bitmap::*;
+use crate::procedure::UserMessage;
+use crate::window::is_wita_window;
+use std::cell::RefCell;
+use std::ffi::c_void;
+
+const DWMWA_FORCE_ICONIC_REPRESENTATION: u32 = 7;
+const DWMWA_HAS_ICONIC_BITMAP: u32 = 10;
+const DWMWA_CLOAKED: u32 = 14;
+const DWMWA_WINDOW_CORNER_PREFERENCE: u32 = 33;
+
+const DWM_SIT_DISPLAYFRAME: u32 = 0x00000001;
+
+const EVENT_OBJECT_CLOAKED: u32 = 0x8017;
+const EVENT_OBJECT_UNCLOAKED: u32 = 0x8018;
+const WINEVENT_OUTOFCONTEXT: u32 = 0x0000;
+const OBJID_WINDOW: i32 = 0;
+
+pub(crate) const WM_DWMCOMPOSITIONCHANGED: u32 = 0x031e;
+pub(crate) const WM_DWMSENDICONICTHUMBNAIL: u32 = 0x0323;
+pub(crate) const WM_DWMSENDICONICLIVEPREVIEWBITMAP: u32 = 0x0326;
+
+/// Describes the rounded corner preference of the window.
+///
+/// This is only effective on Windows 11 or later.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[repr(u32)]
+pub enum CornerPreference {
+    Default = 0,
+    DoNotRound = 1,
+    Round = 2,
+    RoundSmall = 3,
+}
+
+pub(crate) fn set_window_attribute<T>(hwnd: HWND, attr: u32, value: &T) -> bool {
+    unsafe {
+        DwmSetWindowAttribute(
+            hwnd,
+            attr,
+            value as *const T as *const c_void,
+            std::mem::size_of::<T>() as _,
+        )
+        .is_ok()
+    }
+}
+
+pub(crate) fn is_cloaked(hwnd: HWND) -> bool {
+    let mut value = 0u32;
+    unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_CLOAKED,
+            &mut value as *mut u32 as *mut c_void,
+            std::mem::size_of::<u32>() as _,
+        )
+        .is_ok()
+            && value != 0
+    }
+}
+
+/// Notifies windows on the current thread when they are cloaked or uncloaked.
+///
+/// DWM does not send any window messages for cloaking.
+struct CloakEventHook(HWINEVENTHOOK);
+
+thread_local! {
+    // `Some(None)` means that the hook could not be installed.
+    static CLOAK_EVENT_HOOK: RefCell<Option<Option<CloakEventHook>>> = RefCell::new(None);
+}
+
+/// Installs the hook for cloak events on the current thread when a window is shown first.
+pub(crate) fn watch_cloak_events() {
+    CLOAK_EVENT_HOOK.with(|hook| {
+        let mut hook = hook.borrow_mut();
+        if hook.is_none() {
+            *hook = Some(CloakEventHook::new());
+        }
+    });
+}
+
+/// Removes the hook for cloak events on the current thread.
+pub(crate) fn unwatch_cloak_events() {
+    CLOAK_EVENT_HOOK.with(|hook| hook.borrow_mut().take());
+}
+
+impl CloakEventHook {
+    fn new() -> Option<Self> {
+        unsafe {
+            let hook = SetWinEventHook(
+                EVENT_OBJECT_CLOAKED,
+                EVENT_OBJECT_UNCLOAKED,
+                HINSTANCE::NULL,
+                Some(cloak_event_proc),
+                GetCurrentProcessId(),
+                GetCurrentThreadId(),
+                WINEVENT_OUTOFCONTEXT,
+            );
+            if hook.0 == 0 {
+                log::debug!("SetWinEventHook failed");
+                return None;
+            }
+            Some(Self(hook))
+        }
+    }
+}
+
+impl Drop for CloakEventHook {
+    fn drop(&mut self) {
+        unsafe {
+            UnhookWinEvent(self.0);
+        }
+    }
+}
+
+extern "system" fn cloak_event_proc(
+    _: HWINEVENTHOOK,
+    _: u32,
+    hwnd: HWND,
+    id_object: i32,
+    _: i32,
+    _: u32,
+    _: u32,
+) {
+    if id_object != OBJID_WINDOW || !is_wita_window(hwnd) {
+        return;
+    }
+    unsafe {
+        SendMessageW(
+            hwnd,
+            WM_USER,
+            WPARAM(UserMessage::UpdateVisibility as _),
+            LPARAM(0),
+        );
+    }
+}
+
+pub(crate) fn set_corner_preference(hwnd: HWND, preference: CornerPreference) {
+    let value = preference as u32;
+    if !set_window_attribute(hwnd, DWMWA_WINDOW_CORNER_PREFERENCE, &value) {
+        log::debug!("DWMWA_WINDOW_CORNER_PREFERENCE is not supported");
+    }
+}
+
+pub(crate) fn set_iconic_representation(hwnd: HWND, enable: bool) {
+    let value: BOOL = enable.into();
+    set_window_attribute(hwnd, DWMWA_FORCE_ICONIC_REPRESENTATION, &value);
+    set_window_attribute(hwnd, DWMWA_HAS_ICONIC_BITMAP, &value);
+}
+
+pub(crate) fn invalidate_iconic_bitmaps(hwnd: HWND) {
+    unsafe {
+        DwmInvalidateIconicBitmaps(hwnd).ok();
+    }
+}
+
+pub(crate) fn set_iconic_thumbnail(hwnd: HWND, bitmap: &Bitmap) {
+    if let Some(hbmp) = create_dib_section(bitmap) {
+        unsafe {
+            if DwmSetIconicThumbnail(hwnd, hbmp, DWM_SIT_DISPLAYFRAME).is_err() {
+                log::error!("DwmSetIconicThumbnail failed");
+            }
+            DeleteObject(hbmp);
+        }
+    }
+}
+
+pub(crate) fn set_iconic_live_preview(hwnd: HWND, bitmap: &Bitmap) {
+    if let Some(hbmp) = create_dib_section(bitmap) {
+        unsafe {
+            if DwmSetIconicLivePreviewBitmap(hwnd, hbmp, std::ptr::null_mut(), 0).is_err() {
+                log::error!("DwmSetIconicLivePreviewBitmap failed");
+            }
+            DeleteObject(hbmp);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::bindings::Windows::Win32::{System::Diagnostics::Debug::*, System::Memory::*};
 use std::ptr::{null, null_mut};
+use windows::HRESULT;
 
 #[doc(hidden)]
 #[macro_export]
@@ -39,24 +40,173 @@ fn format_message(code: u32) -> Option<String> {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ErrorCode {
+    Win32(u32),
+    HResult(u32),
+}
+
+impl Default for ErrorCode {
+    fn default() -> Self {
+        Self::Win32(0)
+    }
+}
+
+/// The error code and its message that the system reports.
+#[derive(Default, Debug)]
+struct SystemError {
+    code: ErrorCode,
+    message: Option<String>,
+}
+
+impl std::fmt::Display for SystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.message {
+            Some(message) if !message.is_empty() => write!(f, "{}", message.trim_end()),
+            _ => {
+                let code = match self.code {
+                    ErrorCode::Win32(code) | ErrorCode::HResult(code) => code,
+                };
+                match format_message(code) {
+                    Some(message) => write!(f, "{}", message.trim_end()),
+                    None => write!(f, "0x{:08x}", code),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for SystemError {}
+
 /// Represents an Win32 API error.
+///
+/// When the failed function is recorded, `Display` shows the function
+/// and `source` returns the error that the system reports.
 #[derive(Default, Debug)]
-pub struct ApiError(u32);
+pub struct ApiError {
+    error: SystemError,
+    function: Option<&'static str>,
+}
 
 impl ApiError {
+    /// Creates an error from `GetLastError`.
     pub fn new() -> Self {
-        unsafe { Self(GetLastError().0) }
+        unsafe { Self::from_win32(GetLastError().0) }
+    }
+
+    /// Creates an error from a Win32 error code.
+    pub fn from_win32(code: u32) -> Self {
+        Self {
+            error: SystemError {
+                code: ErrorCode::Win32(code),
+                message: None,
+            },
+            function: None,
+        }
     }
 
+    /// Creates an error from a HRESULT.
+    pub fn from_hresult(hr: HRESULT) -> Self {
+        Self {
+            error: SystemError {
+                code: ErrorCode::HResult(hr.0),
+                message: None,
+            },
+            function: None,
+        }
+    }
+
+    /// Records the name of the function that failed.
+    pub fn with_function(mut self, function: &'static str) -> Self {
+        self.function = Some(function);
+        self
+    }
+
+    /// Returns the Win32 error code or the HRESULT value.
     pub fn code(&self) -> u32 {
-        self.0
+        match self.error.code {
+            ErrorCode::Win32(code) | ErrorCode::HResult(code) => code,
+        }
+    }
+
+    /// Returns the error as a HRESULT.
+    ///
+    /// A Win32 error code is converted in the same way as `HRESULT_FROM_WIN32`.
+    pub fn hresult(&self) -> HRESULT {
+        match self.error.code {
+            ErrorCode::HResult(hr) => HRESULT(hr),
+            ErrorCode::Win32(0) => HRESULT(0),
+            ErrorCode::Win32(code) => HRESULT((code & 0xffff) | 0x8007_0000),
+        }
+    }
+
+    /// Returns `true` if the error is created from a HRESULT.
+    pub fn is_hresult(&self) -> bool {
+        matches!(self.error.code, ErrorCode::HResult(_))
+    }
+
+    /// Returns the name of the function that failed if it is recorded.
+    pub fn function(&self) -> Option<&'static str> {
+        self.function
     }
 }
 
 impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", format_message(self.0).unwrap_or_default())
+        match self.function {
+            Some(function) => write!(f, "{} failed", function),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.function
+            .map(|_| &self.error as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<windows::Error> for ApiError {
+    fn from(e: windows::Error) -> Self {
+        Self {
+            error: SystemError {
+                code: ErrorCode::HResult(e.code().0),
+                message: Some(e.message()),
+            },
+            function: None,
+        }
+    }
+}
+
+impl From<HRESULT> for ApiError {
+    fn from(hr: HRESULT) -> Self {
+        Self::from_hresult(hr)
     }
 }
 
-impl std::error::Error for ApiError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn hresult_from_win32() {
+        assert_eq!(ApiError::from_win32(0).hresult(), HRESULT(0));
+        assert_eq!(ApiError::from_win32(5).hresult(), HRESULT(0x8007_0005));
+        assert_eq!(
+            ApiError::from_hresult(HRESULT(0x8000_4005)).code(),
+            0x8000_4005
+        );
+    }
+
+    #[test]
+    fn source_with_function() {
+        let e = ApiError::from_win32(5);
+        assert!(e.source().is_none());
+        let e = e.with_function("CreateWindowExW");
+        assert_eq!(e.function(), Some("CreateWindowExW"));
+        assert_eq!(e.to_string(), "CreateWindowExW failed");
+        assert!(e.source().is_some());
+    }
+}
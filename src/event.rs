@@ -12,29 +12,90 @@ pub struct OtherParams {
 
 #[cfg(feature = "raw_input")]
 use crate::raw_input;
-use crate::{device::*, geometry::*, ime::*, window::Window};
-use std::path::Path;
+use crate::{
+    bitmap::*, device::*, device_notify::*, geometry::*, ime::*, message::*, power::*, scroll::*,
+    session::*, settings::*, theme::*, window::Window,
+};
+use std::any::Any;
+use std::path::PathBuf;
+
+/// Describes a native modal loop.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModalLoop {
+    Menu,
+    Dialog,
+}
+
+/// Describes a file or a directory that has been dropped on the window.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DroppedItem {
+    pub path: PathBuf,
+}
+
+impl DroppedItem {
+    /// Returns `true` if the item is a directory.
+    ///
+    /// This reads the metadata from the file system on each call.
+    pub fn is_dir(&self) -> bool {
+        self.path.is_dir()
+    }
+
+    /// Returns the size of the file in bytes. `None` if the item is a directory or the metadata cannot be read.
+    ///
+    /// This reads the metadata from the file system on each call.
+    pub fn size(&self) -> Option<u64> {
+        std::fs::metadata(&self.path)
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+    }
+}
+
+/// Describes whether the contents of the window can be seen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VisibilityState {
+    Visible,
+    Hidden,
+    Minimized,
+    /// The window is cloaked by DWM such as on another virtual desktop.
+    Cloaked,
+}
 
 /// Trait that must implements for handling events.
 pub trait EventHandler {
     /// This is called when there are no events.
     ///
     /// only passed `RunType::Idle` to `Context::run`.
+    /// While the window is moved or resized, this is called by a timer
+    /// because the event loop is blocked by the modal loop.
     fn idle(&mut self) {}
 
     /// This is called before a event.
     ///
-    /// only passed `RunType::Idle` to `Context::run`.
+    /// With `RunType::Wait`, this is called after a message has been retrieved.
     fn pre_processing(&mut self) {}
 
     /// This is called after a event.
-    ///
-    /// only passed `RunType::Idle` to `Context::run`.
     fn post_processing(&mut self) {}
 
+    /// This is called once when all queued events have been processed.
+    ///
+    /// With `RunType::Wait`, this is called before waiting for the next event.
+    /// With `RunType::Idle`, this is called before the first `idle` after events.
+    /// This is suitable to request redrawing once per batch of events.
+    fn events_cleared(&mut self) {}
+
     /// This is called when the window needs redrawing.
     fn draw(&mut self, window: &Window) {}
 
+    /// This is called when the window needs redrawing with the area to be redrawn.
+    ///
+    /// `invalid_rect` is `rcPaint` of `PAINTSTRUCT`. Override this instead of `draw` for partial redrawing.
+    /// The default implementation calls `draw`.
+    fn draw_rect(&mut self, window: &Window, invalid_rect: PhysicalRect<i32>) {
+        self.draw(window);
+    }
+
     /// This is called when the window has been activated.
     fn activated(&mut self, window: &Window) {}
 
@@ -44,9 +105,49 @@ pub trait EventHandler {
     /// This is called when the window has been closed.
     fn closed(&mut self, window: &Window) {}
 
+    /// This is called at the end of destroying the window after `closed` and the child windows.
+    ///
+    /// The raw handle of the window is invalid after this returns.
+    fn destroyed(&mut self, window: &Window) {}
+
     /// This is called when the window has been moved.
     fn moved(&mut self, window: &Window, position: ScreenPosition) {}
 
+    /// This is called when the user has started dragging the window to move.
+    fn move_started(&mut self, window: &Window) {}
+
+    /// This is called when the user has finished moving the window.
+    ///
+    /// `resized` is also called before this if the size has been changed by snapping.
+    fn move_ended(&mut self, window: &Window) {}
+
+    /// This is called when the thread has entered the modal loop of a menu.
+    ///
+    /// `popup` is `true` if the menu is a popup menu.
+    fn menu_loop_entered(&mut self, window: &Window, popup: bool) {}
+
+    /// This is called when the thread has exited the modal loop of a menu.
+    fn menu_loop_exited(&mut self, window: &Window, popup: bool) {}
+
+    /// This is called when a modal loop of a menu or a dialog box has no messages to process.
+    ///
+    /// The event loop is blocked while the modal loop is running.
+    fn modal_loop_idle(&mut self, window: &Window, kind: ModalLoop) {}
+
+    /// This is called when the window has been shown, hidden, minimized, cloaked or uncloaked.
+    ///
+    /// Renderers can stop presenting while `state` is not `VisibilityState::Visible`.
+    fn visibility_changed(&mut self, window: &Window, state: VisibilityState) {}
+
+    /// This is called synchronously in `WM_SIZE` before the next `WM_PAINT`.
+    ///
+    /// Resize the swap chain buffers here so that a frame is not presented with a stale size.
+    /// Return `true` to redraw the window immediately with `draw`.
+    /// This is not called while the window is minimized.
+    fn resize_swap_chain(&mut self, window: &Window, size: PhysicalSize<u32>) -> bool {
+        false
+    }
+
     /// This is called when the window is resizing.
     fn resizing(&mut self, window: &Window, size: PhysicalSize<u32>) {}
 
@@ -56,6 +157,11 @@ pub trait EventHandler {
     /// This is called when the window's DPI has been changed.
     fn dpi_changed(&mut self, window: &Window) {}
 
+    /// This is called with the new DPI and the new inner size when the window's DPI has been changed.
+    ///
+    /// This is called after `dpi_changed`, and the window has already been resized to `inner_size`.
+    fn dpi_changed_with_size(&mut self, window: &Window, dpi: u32, inner_size: PhysicalSize<u32>) {}
+
     /// This is called when the mouse button has been pressed and released on the window.
     fn mouse_input(
         &mut self,
@@ -69,6 +175,11 @@ pub trait EventHandler {
     /// This is called when the cursor has been moved on the window.
     fn cursor_moved(&mut self, window: &Window, mouse_state: MouseState) {}
 
+    /// This is called with the amount of mouse movement while the relative mouse mode is enabled.
+    ///
+    /// `cursor_moved` is not called in the relative mouse mode.
+    fn mouse_delta(&mut self, window: &Window, dx: i32, dy: i32) {}
+
     /// This is called when the cursor has been entered the window.
     fn cursor_entered(&mut self, window: &Window, mouse_state: MouseState) {}
 
@@ -85,6 +196,14 @@ pub trait EventHandler {
     ) {
     }
 
+    /// This is called before the key message is translated.
+    ///
+    /// Return `true` to consume the key. The consumed key is still passed to `key_input`,
+    /// but it is not translated into `char_input` and the default behavior such as activating the menu by Alt or F10 is not performed.
+    fn key_filter(&mut self, window: &Window, key_code: KeyCode, state: KeyState) -> bool {
+        false
+    }
+
     /// This is called when the keyboard key has been inputed the character.
     fn char_input(&mut self, window: &Window, c: char) {}
 
@@ -103,8 +222,89 @@ pub trait EventHandler {
     /// This is called when the IME ends composition.
     fn ime_end_composition(&mut self, window: &Window, result_string: Option<&str>) {}
 
+    /// This is called when data has been sent by `WM_COPYDATA` from another window.
+    ///
+    /// Return `true` if the data has been processed.
+    fn copy_data(&mut self, window: &Window, id: usize, data: &[u8]) -> bool {
+        false
+    }
+
+    /// This is called when the session is ending by logoff or shutdown.
+    ///
+    /// Return `SessionEndResponse::Block` to block the shutdown while saving data.
+    fn session_ending(&mut self, window: &Window, reason: SessionEndReason) -> SessionEndResponse {
+        SessionEndResponse::Allow
+    }
+
+    /// This is called when the session has been ended or the ending has been canceled.
+    ///
+    /// If `ending` is `true`, the process may be terminated after this returns.
+    fn session_ended(&mut self, window: &Window, ending: bool) {}
+
+    /// This is called when the system is suspending.
+    ///
+    /// Release resources such as GPU devices here.
+    fn suspending(&mut self, window: &Window) {}
+
+    /// This is called when the system has been resumed from suspend.
+    fn resumed(&mut self, window: &Window) {}
+
+    /// This is called when the power status has been changed.
+    fn power_status_changed(&mut self, window: &Window, status: PowerStatus) {}
+
+    /// This is called when a volume or a HID interface has been connected or disconnected.
+    ///
+    /// This is not called for child windows.
+    fn device_changed(&mut self, window: &Window, kind: DeviceEventKind) {}
+
+    /// This is called when system settings have been changed.
+    ///
+    /// Cached system metrics should be queried again.
+    fn system_settings_changed(&mut self, window: &Window, kind: SettingKind) {}
+
+    /// This is called when the regional settings of the user have been changed.
+    ///
+    /// `locale` is the same as `user_locale()`. `number_format()` and `date_format()` return
+    /// the new settings.
+    fn locale_changed(&mut self, window: &Window, locale: &str) {}
+
+    /// This is called when the taskbar requests the thumbnail of the window.
+    ///
+    /// Enabled by `Window::set_iconic_thumbnail`. The bitmap must fit in `max_size`.
+    fn iconic_thumbnail(&mut self, window: &Window, max_size: PhysicalSize<u32>) -> Option<Bitmap> {
+        None
+    }
+
+    /// This is called when the taskbar requests the live preview of the window.
+    ///
+    /// Enabled by `Window::set_iconic_thumbnail`. The bitmap should have the size of the window.
+    fn iconic_live_preview(&mut self, window: &Window) -> Option<Bitmap> {
+        None
+    }
+
+    /// This is called when the system theme has been changed.
+    fn theme_changed(&mut self, window: &Window, theme: Theme) {}
+
+    /// This is called when the high contrast mode has been changed.
+    ///
+    /// `scheme` is `None` when the high contrast mode has been disabled.
+    fn high_contrast_changed(&mut self, window: &Window, scheme: Option<HighContrastScheme>) {}
+
+    /// This is called when the standard scroll bar has been operated.
+    ///
+    /// The scroll bar has already been moved to `position` in the standard way.
+    fn scrolled(&mut self, window: &Window, bar: ScrollBar, action: ScrollAction, position: i32) {}
+
     /// This is called when files have been dropped on the window.
-    fn drop_files(&mut self, window: &Window, paths: &[&Path], position: PhysicalPosition<f32>) {}
+    ///
+    /// `position` is the drop point in the client area.
+    fn drop_files(
+        &mut self,
+        window: &Window,
+        items: &[DroppedItem],
+        position: PhysicalPosition<i32>,
+    ) {
+    }
 
     /// This is called when raw data has been inputed.
     #[cfg(feature = "raw_input")]
@@ -120,6 +320,25 @@ pub trait EventHandler {
     ) {
     }
 
+    /// This is called when a message registered by `register_message` has been received.
+    ///
+    /// Return `None` to pass the message to `EventHandler::other`.
+    fn custom_message(
+        &mut self,
+        window: &Window,
+        id: MessageId,
+        wparam: usize,
+        lparam: isize,
+    ) -> Option<isize> {
+        None
+    }
+
+    /// This is called with the panic payload when a callback has panicked
+    /// and `PanicPolicy::Notify` is set by `RunOptions::panic_policy`.
+    ///
+    /// The event loop continues after this returns.
+    fn handler_panicked(&mut self, payload: Box<dyn Any + Send>) {}
+
     fn other(&mut self, params: &OtherParams) -> Option<i32> {
         None
     }
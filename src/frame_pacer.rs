@@ -0,0 +1,135 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Media::Multimedia::*, System::Threading::*, UI::WindowsAndMessaging::*,
+};
+use crate::error::ApiError;
+use std::time::{Duration, Instant};
+
+const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: u32 = 0x00000002;
+const TIMER_ALL_ACCESS: u32 = 0x001f0003;
+const INFINITE: u32 = 0xffffffff;
+const WAIT_OBJECT_0: u32 = 0;
+const ERROR_INVALID_PARAMETER: u32 = 87;
+
+/// `fps` must be finite and greater than 0.
+fn interval(fps: f64) -> Result<Duration, ApiError> {
+    if !fps.is_finite() || fps <= 0.0 {
+        return Err(ApiError::from_win32(ERROR_INVALID_PARAMETER));
+    }
+    Ok(Duration::from_secs_f64(1.0 / fps))
+}
+
+/// Limits the frame rate with a high-resolution waitable timer.
+///
+/// `RunOptions::max_fps` uses this to pace `EventHandler::idle`. This can also be used directly such as in a render thread.
+pub struct FramePacer {
+    timer: HANDLE,
+    interval: Duration,
+    next: Instant,
+}
+
+impl FramePacer {
+    /// Returns an error if `fps` is not finite or not greater than 0.
+    pub fn new(fps: f64) -> Result<Self, ApiError> {
+        let interval = interval(fps)?;
+        unsafe {
+            let mut timer = CreateWaitableTimerExW(
+                std::ptr::null(),
+                PWSTR::NULL,
+                CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+                TIMER_ALL_ACCESS,
+            );
+            if timer.0 == 0 {
+                // The high resolution timer is supported from Windows 10 1803.
+                timer = CreateWaitableTimerExW(std::ptr::null(), PWSTR::NULL, 0, TIMER_ALL_ACCESS);
+                if timer.0 == 0 {
+                    return Err(ApiError::new().with_function("CreateWaitableTimerExW"));
+                }
+            }
+            timeBeginPeriod(1);
+            Ok(Self {
+                timer,
+                interval,
+                next: Instant::now(),
+            })
+        }
+    }
+
+    /// Returns an error and keeps the current rate if `fps` is not finite or not greater than 0.
+    pub fn set_fps(&mut self, fps: f64) -> Result<(), ApiError> {
+        self.interval = interval(fps)?;
+        Ok(())
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn set_timer(&self, now: Instant) -> bool {
+        let remaining = self.next.saturating_duration_since(now);
+        if remaining.is_zero() {
+            return false;
+        }
+        // A negative value means the relative time in 100 nanoseconds.
+        let due = -((remaining.as_nanos() / 100) as i64).max(1);
+        unsafe { SetWaitableTimer(self.timer, &due, 0, None, std::ptr::null(), false).as_bool() }
+    }
+
+    /// Schedules the next frame. Frames that are late more than one interval are not caught up.
+    fn advance(&mut self, now: Instant) {
+        self.next += self.interval;
+        if self.next < now {
+            self.next = now;
+        }
+    }
+
+    /// Sleeps until the next frame.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        if self.set_timer(now) {
+            unsafe {
+                WaitForSingleObject(self.timer, INFINITE);
+            }
+        }
+        self.advance(Instant::now());
+    }
+
+    /// Waits until the next frame or a message.
+    ///
+    /// Returns `true` if the next frame has come.
+    pub(crate) fn wait_frame_or_message(&mut self) -> bool {
+        let now = Instant::now();
+        if self.set_timer(now) {
+            let ret =
+                unsafe { MsgWaitForMultipleObjects(1, &self.timer, false, INFINITE, QS_ALLINPUT) };
+            if ret != WAIT_OBJECT_0 {
+                return false;
+            }
+        }
+        self.advance(Instant::now());
+        true
+    }
+}
+
+impl Drop for FramePacer {
+    fn drop(&mut self) {
+        unsafe {
+            timeEndPeriod(1);
+            CloseHandle(self.timer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_fps() {
+        assert_eq!(interval(60.0).unwrap(), Duration::from_secs_f64(1.0 / 60.0));
+        assert!(interval(0.0).is_err());
+        assert!(interval(-30.0).is_err());
+        assert!(interval(f64::NAN).is_err());
+        assert!(interval(f64::INFINITY).is_err());
+        assert!(FramePacer::new(0.0).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::api::get_dpi_for_window;
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Gdi::*, UI::WindowsAndMessaging::GetClientRect,
+};
+use crate::bitmap::{bitmap_info, data_len};
+use crate::error::ApiError;
+use crate::geometry::*;
+use crate::DEFAULT_DPI;
+
+const ERROR_ARITHMETIC_OVERFLOW: u32 = 534;
+
+/// A double-buffered GDI drawing context for the client area.
+///
+/// Draw to `hdc` in `EventHandler::draw`, and the content is copied to the window when this is dropped.
+/// The buffer has the physical size of the client area.
+pub struct GdiFrame {
+    hwnd: HWND,
+    hdc: HDC,
+    bitmap: HBITMAP,
+    prev: HGDIOBJ,
+    bits: *mut u8,
+    len: usize,
+    size: PhysicalSize<u32>,
+    dpi: u32,
+}
+
+impl GdiFrame {
+    pub(crate) fn new(hwnd: HWND) -> Result<Self, ApiError> {
+        unsafe {
+            let mut rc = RECT::default();
+            GetClientRect(hwnd, &mut rc);
+            // A DIB section cannot be created with zero size such as in a minimized window.
+            let size = PhysicalSize::new(
+                ((rc.right - rc.left) as u32).max(1),
+                ((rc.bottom - rc.top) as u32).max(1),
+            );
+            let len =
+                data_len(size).ok_or_else(|| ApiError::from_win32(ERROR_ARITHMETIC_OVERFLOW))?;
+            let hdc = CreateCompatibleDC(HDC::NULL);
+            if hdc.0 == 0 {
+                return Err(ApiError::new().with_function("CreateCompatibleDC"));
+            }
+            let info = bitmap_info(size);
+            let mut bits = std::ptr::null_mut();
+            let bitmap = CreateDIBSection(hdc, &info, DIB_RGB_COLORS, &mut bits, HANDLE::NULL, 0);
+            if bitmap == HBITMAP::NULL || bits.is_null() {
+                let e = ApiError::new().with_function("CreateDIBSection");
+                DeleteDC(hdc);
+                return Err(e);
+            }
+            let prev = SelectObject(hdc, bitmap);
+            Ok(Self {
+                hwnd,
+                hdc,
+                bitmap,
+                prev,
+                bits: bits as _,
+                len,
+                size,
+                dpi: get_dpi_for_window(hwnd),
+            })
+        }
+    }
+
+    /// Returns the memory device context to draw.
+    pub fn hdc(&self) -> *mut std::ffi::c_void {
+        self.hdc.0 as _
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn dpi(&self) -> u32 {
+        self.dpi
+    }
+
+    pub fn scale_factor(&self) -> f32 {
+        self.dpi as f32 / DEFAULT_DPI as f32
+    }
+
+    /// Returns the pixels of the buffer in BGRA order from top to bottom.
+    ///
+    /// GDI does not write the alpha channel.
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.bits, self.len) }
+    }
+
+    /// Fills the whole buffer with a RGB color.
+    pub fn clear(&mut self, r: u8, g: u8, b: u8) {
+        for pixel in self.pixels_mut().chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[b, g, r, 0xff]);
+        }
+    }
+}
+
+impl Drop for GdiFrame {
+    fn drop(&mut self) {
+        unsafe {
+            GdiFlush();
+            let hdc = GetDC(self.hwnd);
+            if hdc.0 != 0 {
+                BitBlt(
+                    hdc,
+                    0,
+                    0,
+                    self.size.width as _,
+                    self.size.height as _,
+                    self.hdc,
+                    0,
+                    0,
+                    SRCCOPY,
+                );
+                ReleaseDC(self.hwnd, hdc);
+            }
+            SelectObject(self.hdc, self.prev);
+            DeleteObject(self.bitmap);
+            DeleteDC(self.hdc);
+        }
+    }
+}
@@ -26,12 +26,24 @@ impl<T, U> Position<T, U>
 where
     T: num::NumCast,
 {
+    /// # Panics
+    ///
+    /// Panics if a value cannot be represented by `R`. Use `try_cast` for values from outside.
     #[inline]
     pub fn cast<R>(self) -> Position<R, U>
     where
         R: num::NumCast,
     {
-        Position::new(num::cast(self.x).unwrap(), num::cast(self.y).unwrap())
+        self.try_cast().unwrap()
+    }
+
+    /// Returns `None` if a value cannot be represented by `R` such as a negative value to an unsigned type.
+    #[inline]
+    pub fn try_cast<R>(self) -> Option<Position<R, U>>
+    where
+        R: num::NumCast,
+    {
+        Some(Position::new(num::cast(self.x)?, num::cast(self.y)?))
     }
 }
 
@@ -78,15 +90,24 @@ impl<T, U> Size<T, U>
 where
     T: num::NumCast,
 {
+    /// # Panics
+    ///
+    /// Panics if a value cannot be represented by `R`. Use `try_cast` for values from outside.
     #[inline]
     pub fn cast<R>(self) -> Size<R, U>
     where
         R: num::NumCast,
     {
-        Size::new(
-            num::cast(self.width).unwrap(),
-            num::cast(self.height).unwrap(),
-        )
+        self.try_cast().unwrap()
+    }
+
+    /// Returns `None` if a value cannot be represented by `R` such as a negative value to an unsigned type.
+    #[inline]
+    pub fn try_cast<R>(self) -> Option<Size<R, U>>
+    where
+        R: num::NumCast,
+    {
+        Some(Size::new(num::cast(self.width)?, num::cast(self.height)?))
     }
 }
 
@@ -107,6 +128,21 @@ impl<T, U> From<(T, T)> for Size<T, U> {
     }
 }
 
+/// A generic rectangle
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect<T, U> {
+    pub position: Position<T, U>,
+    pub size: Size<T, U>,
+}
+
+impl<T, U> Rect<T, U> {
+    #[inline]
+    pub fn new(position: Position<T, U>, size: Size<T, U>) -> Self {
+        Self { position, size }
+    }
+}
+
 /// Logical coordinate.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Logical;
@@ -127,13 +163,68 @@ pub type PhysicalPosition<T> = Position<T, Physical>;
 pub type PhysicalSize<T> = Size<T, Physical>;
 /// A position in screen coordinate.
 pub type ScreenPosition = Position<i32, Screen>;
+/// A rectangle in physical coordinate.
+pub type PhysicalRect<T> = Rect<T, Physical>;
+
+/// Scales the value by `num / den` in `f64`.
+///
+/// Integer values are rounded to the nearest instead of truncated,
+/// so that fractional scale factors such as 1.25 and 1.5 are preserved as much as possible.
+#[inline]
+fn scale_value<T>(a: T, num: f64, den: f64) -> T
+where
+    T: Copy + num::NumCast,
+{
+    let value = num::cast::<T, f64>(a).unwrap_or(0.0) * num / den;
+    let is_float = num::cast::<f64, T>(0.5).and_then(num::cast::<T, f64>) == Some(0.5);
+    saturating_cast(if is_float { value } else { value.round() }).unwrap_or(a)
+}
+
+/// Casts the value to `T`, saturating at the bounds of `T` instead of failing.
+///
+/// `num::NumCast` does not provide the bounds, so the bounds of primitive types are tried from the widest.
+fn saturating_cast<T>(value: f64) -> Option<T>
+where
+    T: num::NumCast,
+{
+    if let Some(v) = num::cast(value) {
+        return Some(v);
+    }
+    let bounds: &[f64] = if value.is_nan() {
+        &[0.0]
+    } else if value < 0.0 {
+        &[
+            i64::MIN as f64,
+            i32::MIN as f64,
+            i16::MIN as f64,
+            i8::MIN as f64,
+            0.0,
+        ]
+    } else {
+        &[
+            u64::MAX as f64,
+            i64::MAX as f64,
+            u32::MAX as f64,
+            i32::MAX as f64,
+            u16::MAX as f64,
+            i16::MAX as f64,
+            u8::MAX as f64,
+            i8::MAX as f64,
+        ]
+    };
+    bounds.iter().find_map(|&b| num::cast(b))
+}
 
 #[inline]
 fn to_logical_value<T>(a: T, dpi: T) -> T
 where
     T: std::ops::Mul<Output = T> + std::ops::Div<Output = T> + Copy + num::NumCast,
 {
-    a * num::cast(DEFAULT_DPI).unwrap() / dpi
+    scale_value(
+        a,
+        DEFAULT_DPI as f64,
+        num::cast(dpi).unwrap_or(DEFAULT_DPI as f64),
+    )
 }
 
 #[inline]
@@ -141,7 +232,11 @@ fn to_physical_value<T>(a: T, dpi: T) -> T
 where
     T: std::ops::Mul<Output = T> + std::ops::Div<Output = T> + Copy + num::NumCast,
 {
-    a * dpi / num::cast(DEFAULT_DPI).unwrap()
+    scale_value(
+        a,
+        num::cast(dpi).unwrap_or(DEFAULT_DPI as f64),
+        DEFAULT_DPI as f64,
+    )
 }
 
 impl<T> Position<T, Logical>
@@ -293,6 +388,52 @@ where
     }
 }
 
+macro_rules! impl_float_to_physical {
+    ($($t:ty),*) => {
+        $(
+            impl ToPhysicalPosition<i32> for Position<$t, Logical> {
+                #[inline]
+                fn to_physical(&self, dpi: i32) -> Position<i32, Physical> {
+                    Position::new(
+                        (self.x as f64 * dpi as f64 / DEFAULT_DPI as f64).round() as i32,
+                        (self.y as f64 * dpi as f64 / DEFAULT_DPI as f64).round() as i32,
+                    )
+                }
+            }
+
+            impl ToPhysicalPosition<i32> for Position<$t, Physical> {
+                #[inline]
+                fn to_physical(&self, _: i32) -> Position<i32, Physical> {
+                    Position::new(self.x.round() as i32, self.y.round() as i32)
+                }
+            }
+
+            impl ToPhysicalSize<u32> for Size<$t, Logical> {
+                #[inline]
+                fn to_physical(&self, dpi: u32) -> Size<u32, Physical> {
+                    Size::new(
+                        (self.width as f64 * dpi as f64 / DEFAULT_DPI as f64).round().max(0.0) as u32,
+                        (self.height as f64 * dpi as f64 / DEFAULT_DPI as f64).round().max(0.0) as u32,
+                    )
+                }
+            }
+
+            impl ToPhysicalSize<u32> for Size<$t, Physical> {
+                #[inline]
+                fn to_physical(&self, _: u32) -> Size<u32, Physical> {
+                    Size::new(
+                        self.width.round().max(0.0) as u32,
+                        self.height.round().max(0.0) as u32,
+                    )
+                }
+            }
+        )*
+    };
+}
+
+// Floating-point positions and sizes can be passed to the setters that take integer coordinates.
+impl_float_to_physical!(f32, f64);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +454,23 @@ mod tests {
         assert!(dst.height == 256);
     }
 
+    #[test]
+    fn try_cast() {
+        assert!(PhysicalPosition::new(-1, 2).try_cast::<u32>().is_none());
+        let dst = PhysicalSize::new(128i32, 256).try_cast::<u16>().unwrap();
+        assert!(dst.width == 128);
+        assert!(dst.height == 256);
+        assert!(PhysicalSize::new(70000u32, 1).try_cast::<u16>().is_none());
+    }
+
+    #[test]
+    fn saturating_conversion() {
+        let dest = LogicalSize::new(u32::MAX, 0).to_physical(DEFAULT_DPI as u32 * 2);
+        assert!(dest.width == u32::MAX);
+        let dest = LogicalPosition::new(i32::MIN, 0).to_physical(DEFAULT_DPI * 2);
+        assert!(dest.x == i32::MIN);
+    }
+
     #[test]
     fn logical_to_logical_position() {
         let src = LogicalPosition::new(128, 256);
@@ -392,6 +550,31 @@ mod tests {
         assert!((dest.height - src.height / 2.0).abs() <= std::f32::EPSILON);
     }
 
+    #[test]
+    fn fractional_scale_factor() {
+        let dpi = (DEFAULT_DPI * 5 / 4) as u32;
+        let dest = LogicalSize::new(101u32, 3).to_physical(dpi);
+        assert!(dest.width == 126);
+        assert!(dest.height == 4);
+        let dest = PhysicalPosition::new(151, 3).to_logical(DEFAULT_DPI * 3 / 2);
+        assert!(dest.x == 101);
+        assert!(dest.y == 2);
+    }
+
+    #[test]
+    fn float_to_integer_physical() {
+        let dest: PhysicalSize<u32> = ToPhysicalSize::<u32>::to_physical(
+            &LogicalSize::new(100.5f64, 200.0),
+            (DEFAULT_DPI * 3 / 2) as u32,
+        );
+        assert!(dest.width == 151);
+        assert!(dest.height == 300);
+        let dest: PhysicalPosition<i32> =
+            ToPhysicalPosition::<i32>::to_physical(&PhysicalPosition::new(-1.6f32, 2.4), 0);
+        assert!(dest.x == -2);
+        assert!(dest.y == 2);
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn serde_check() {
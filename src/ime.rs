@@ -72,21 +72,51 @@ impl std::ops::Index<usize> for Composition {
 pub struct CandidateList {
     list: Vec<String>,
     selection: usize,
+    page_start: usize,
+    page_size: usize,
 }
 
 impl CandidateList {
-    pub(crate) fn new(list: Vec<String>, selection: usize) -> Self {
-        Self { list, selection }
+    pub(crate) fn new(
+        list: Vec<String>,
+        selection: usize,
+        page_start: usize,
+        page_size: usize,
+    ) -> Self {
+        Self {
+            list,
+            selection,
+            page_start,
+            page_size,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
         self.list.is_empty()
     }
 
+    /// Returns the total count of the candidates in all pages.
     pub fn len(&self) -> usize {
         self.list.len()
     }
 
+    /// Returns the index of the first candidate in the current page.
+    pub fn page_start(&self) -> usize {
+        self.page_start
+    }
+
+    /// Returns the number of candidates in a page that the system UI shows.
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Returns the range of the indices in the current page.
+    pub fn page_range(&self) -> std::ops::Range<usize> {
+        let start = self.page_start.min(self.list.len());
+        let end = (start + self.page_size).min(self.list.len());
+        start..end
+    }
+
     pub fn iter(&self) -> impl Iterator + '_ {
         self.list.iter()
     }
@@ -103,29 +133,36 @@ impl std::ops::Index<usize> for CandidateList {
     }
 }
 
+/// The input context of a window.
+///
+/// The context is associated with only the window, so that child windows enable IME and
+/// place the composition window independently of the parent.
 pub struct ImmContext {
     hwnd: HWND,
     himc: HIMC,
 }
 
 impl ImmContext {
-    pub fn new(hwnd: HWND) -> Self {
-        unsafe {
-            let himc = ImmCreateContext();
-            ImmAssociateContextEx(hwnd, himc, IACE_CHILDREN);
-            Self { hwnd, himc }
+    pub fn new(hwnd: HWND, enabled: bool) -> Self {
+        let himc = unsafe { ImmCreateContext() };
+        let imc = Self { hwnd, himc };
+        if enabled {
+            imc.enable();
+        } else {
+            imc.disable();
         }
+        imc
     }
 
     pub fn enable(&self) {
         unsafe {
-            ImmAssociateContextEx(self.hwnd, self.himc, IACE_CHILDREN);
+            ImmAssociateContextEx(self.hwnd, self.himc, 0);
         }
     }
 
     pub fn disable(&self) {
         unsafe {
-            ImmAssociateContextEx(self.hwnd, HIMC::NULL, IACE_IGNORENOCONTEXT);
+            ImmAssociateContextEx(self.hwnd, HIMC::NULL, 0);
         }
     }
 }
@@ -264,6 +301,22 @@ impl Imc {
         }
     }
 
+    /// Returns the composition string with the attributes, or `None` if IME is not composing.
+    pub fn get_composition(&self) -> Option<Composition> {
+        if self.himc == HIMC::NULL {
+            return None;
+        }
+        match (
+            self.get_composition_string(GCS_COMPSTR),
+            self.get_composition_string(GCS_COMPATTR),
+        ) {
+            (Some(CompositionString::CompStr(s)), Some(CompositionString::CompAttr(attrs))) => {
+                Some(Composition::new(s, attrs))
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_candidate_list(&self) -> Option<CandidateList> {
         unsafe {
             let size = ImmGetCandidateListW(self.himc, 0, std::ptr::null_mut(), 0) as usize;
@@ -286,7 +339,12 @@ impl Imc {
                 let slice = std::slice::from_raw_parts(p, len);
                 list.push(String::from_utf16_lossy(slice));
             }
-            Some(CandidateList::new(list, obj.dwSelection as usize))
+            Some(CandidateList::new(
+                list,
+                obj.dwSelection as usize,
+                obj.dwPageStart as usize,
+                obj.dwPageSize as usize,
+            ))
         }
     }
 }
@@ -298,3 +356,17 @@ impl Drop for Imc {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidate_list_page_range() {
+        let list = (0..12).map(|i| i.to_string()).collect::<Vec<_>>();
+        let candidates = CandidateList::new(list.clone(), 0, 9, 9);
+        assert_eq!(candidates.page_range(), 9..12);
+        let candidates = CandidateList::new(list, 0, 20, 9);
+        assert!(candidates.page_range().is_empty());
+    }
+}
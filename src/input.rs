@@ -0,0 +1,217 @@
+use crate::bindings::Windows::Win32::{UI::KeyboardAndMouseInput::*, UI::WindowsAndMessaging::*};
+use crate::device::*;
+use crate::error::ApiError;
+use crate::geometry::*;
+
+const WHEEL_DELTA: i32 = 120;
+const XBUTTON1: u32 = 0x0001;
+const XBUTTON2: u32 = 0x0002;
+const VK_LWIN: u32 = 0x5b;
+const VK_RWIN: u32 = 0x5c;
+const VK_APPS: u32 = 0x5d;
+const ERROR_INVALID_PARAMETER: u32 = 87;
+
+/// Describes a synthetic input for `send_input`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputEvent {
+    /// A key identified by the virtual key.
+    Key { key: VirtualKey, state: KeyState },
+    /// A key identified by the scan code. The extended keys are given as `0xe0xx`.
+    ScanCode { code: ScanCode, state: KeyState },
+    /// A character that is sent without the keyboard layout.
+    Char(char),
+    MouseButton {
+        button: MouseButton,
+        state: KeyState,
+    },
+    /// Moves the cursor relatively. The system applies the mouse acceleration.
+    MouseMove { dx: i32, dy: i32 },
+    /// Moves the cursor to the position on the virtual screen.
+    MouseMoveTo(ScreenPosition),
+    /// The amount of the wheel rotation in notches.
+    Wheel(f32),
+    /// The amount of the horizontal wheel rotation in notches.
+    HWheel(f32),
+}
+
+fn is_extended_key(key: VirtualKey) -> bool {
+    matches!(
+        key,
+        VirtualKey::Insert
+            | VirtualKey::Delete
+            | VirtualKey::Home
+            | VirtualKey::End
+            | VirtualKey::PageUp
+            | VirtualKey::PageDown
+            | VirtualKey::Up
+            | VirtualKey::Down
+            | VirtualKey::Left
+            | VirtualKey::Right
+            | VirtualKey::RCtrl
+            | VirtualKey::RAlt
+            | VirtualKey::NumDiv
+            | VirtualKey::NumLock
+            | VirtualKey::PrintScreen
+            | VirtualKey::Other(VK_LWIN)
+            | VirtualKey::Other(VK_RWIN)
+            | VirtualKey::Other(VK_APPS)
+    )
+}
+
+fn keyboard(vk: u16, scan: u16, flags: u32) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: KEYBD_EVENT_FLAGS(flags),
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+fn mouse(dx: i32, dy: i32, data: u32, flags: u32) -> INPUT {
+    INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dx,
+                dy,
+                mouseData: data,
+                dwFlags: MOUSE_EVENT_FLAGS(flags),
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+fn key_up_flag(state: KeyState) -> u32 {
+    match state {
+        KeyState::Pressed => 0,
+        KeyState::Released => KEYEVENTF_KEYUP.0,
+    }
+}
+
+fn push_inputs(event: &InputEvent, inputs: &mut Vec<INPUT>) -> Result<(), ApiError> {
+    match *event {
+        InputEvent::Key { key, state } => {
+            let mut flags = key_up_flag(state);
+            if is_extended_key(key) {
+                flags |= KEYEVENTF_EXTENDEDKEY.0;
+            }
+            inputs.push(keyboard(to_raw_virtual_key(key) as _, 0, flags));
+        }
+        InputEvent::ScanCode { code, state } => {
+            let mut flags = KEYEVENTF_SCANCODE.0 | key_up_flag(state);
+            if code.0 & 0xe000 == 0xe000 {
+                flags |= KEYEVENTF_EXTENDEDKEY.0;
+            }
+            inputs.push(keyboard(0, (code.0 & 0xff) as _, flags));
+        }
+        InputEvent::Char(c) => {
+            let mut buffer = [0u16; 2];
+            for &unit in c.encode_utf16(&mut buffer).iter() {
+                inputs.push(keyboard(0, unit, KEYEVENTF_UNICODE.0));
+                inputs.push(keyboard(0, unit, KEYEVENTF_UNICODE.0 | KEYEVENTF_KEYUP.0));
+            }
+        }
+        InputEvent::MouseButton { button, state } => {
+            let pressed = state == KeyState::Pressed;
+            let (flags, data) = match button {
+                MouseButton::Left if pressed => (MOUSEEVENTF_LEFTDOWN.0, 0),
+                MouseButton::Left => (MOUSEEVENTF_LEFTUP.0, 0),
+                MouseButton::Right if pressed => (MOUSEEVENTF_RIGHTDOWN.0, 0),
+                MouseButton::Right => (MOUSEEVENTF_RIGHTUP.0, 0),
+                MouseButton::Middle if pressed => (MOUSEEVENTF_MIDDLEDOWN.0, 0),
+                MouseButton::Middle => (MOUSEEVENTF_MIDDLEUP.0, 0),
+                MouseButton::Ex(n) => {
+                    let data = match n {
+                        0 => XBUTTON1,
+                        1 => XBUTTON2,
+                        _ => return Err(ApiError::from_win32(ERROR_INVALID_PARAMETER)),
+                    };
+                    if pressed {
+                        (MOUSEEVENTF_XDOWN.0, data)
+                    } else {
+                        (MOUSEEVENTF_XUP.0, data)
+                    }
+                }
+            };
+            inputs.push(mouse(0, 0, data, flags));
+        }
+        InputEvent::MouseMove { dx, dy } => {
+            inputs.push(mouse(dx, dy, 0, MOUSEEVENTF_MOVE.0));
+        }
+        InputEvent::MouseMoveTo(position) => unsafe {
+            // The absolute coordinates are normalized to 0..=65535 on the virtual screen.
+            let x = GetSystemMetrics(SM_XVIRTUALSCREEN);
+            let y = GetSystemMetrics(SM_YVIRTUALSCREEN);
+            let width = (GetSystemMetrics(SM_CXVIRTUALSCREEN) - 1).max(1);
+            let height = (GetSystemMetrics(SM_CYVIRTUALSCREEN) - 1).max(1);
+            inputs.push(mouse(
+                ((position.x - x) as i64 * 65535 / width as i64) as i32,
+                ((position.y - y) as i64 * 65535 / height as i64) as i32,
+                0,
+                MOUSEEVENTF_MOVE.0 | MOUSEEVENTF_ABSOLUTE.0 | MOUSEEVENTF_VIRTUALDESK.0,
+            ));
+        },
+        InputEvent::Wheel(notches) => {
+            let data = (notches * WHEEL_DELTA as f32) as i32;
+            inputs.push(mouse(0, 0, data as u32, MOUSEEVENTF_WHEEL.0));
+        }
+        InputEvent::HWheel(notches) => {
+            let data = (notches * WHEEL_DELTA as f32) as i32;
+            inputs.push(mouse(0, 0, data as u32, MOUSEEVENTF_HWHEEL.0));
+        }
+    }
+    Ok(())
+}
+
+/// Synthesizes the system input with `SendInput`.
+///
+/// The inputs are inserted into the input stream serially, and are subject to UIPI.
+/// Nothing is sent if an event has a mouse button that cannot be synthesized such as `MouseButton::Ex(2)`.
+pub fn send_input(events: &[InputEvent]) -> Result<(), ApiError> {
+    let mut inputs = Vec::with_capacity(events.len());
+    for event in events {
+        push_inputs(event, &mut inputs)?;
+    }
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    unsafe {
+        let len = SendInput(
+            inputs.len() as _,
+            inputs.as_mut_ptr(),
+            std::mem::size_of::<INPUT>() as _,
+        );
+        if (len as usize) < inputs.len() {
+            return Err(ApiError::new().with_function("SendInput"));
+        }
+    }
+    Ok(())
+}
+
+/// Turns the toggle key such as `CapsLock` on or off by synthesizing the key strokes.
+///
+/// The keyboard indicator LEDs follow the toggle state of the system.
+/// Nothing is sent if the key is already in the state that `is_key_toggled` returns.
+pub fn set_key_toggled(key: VirtualKey, on: bool) -> Result<(), ApiError> {
+    if is_key_toggled(key) == on {
+        return Ok(());
+    }
+    send_input(&[
+        InputEvent::Key {
+            key,
+            state: KeyState::Pressed,
+        },
+        InputEvent::Key {
+            key,
+            state: KeyState::Released,
+        },
+    ])
+}
@@ -0,0 +1,234 @@
+//! Publishes recent files and tasks on the taskbar button.
+//!
+//! A task launches the executable with the arguments. To activate the running instance instead,
+//! call [`forward_to_instance`] in the launched process and
+//! handle the arguments in `EventHandler::copy_data` with [`parse_arguments`].
+
+use crate::api::initialize_com;
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Storage::StructuredStorage::*, System::Com::*, System::PropertiesSystem::*,
+    UI::Shell::*, UI::WindowsAndMessaging::*,
+};
+use crate::{app_user_model_id, ApiError, Window};
+use std::path::{Path, PathBuf};
+use windows::Interface;
+
+/// The identifier of `WM_COPYDATA` that [`forward_to_instance`] sends.
+pub const COPY_DATA_ID: usize = 0x7769_7461;
+
+const VT_LPWSTR: u16 = 31;
+
+/// Describes a known category of the jump list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum KnownCategory {
+    Recent,
+    Frequent,
+}
+
+/// A task item of the jump list.
+#[derive(Clone, Debug)]
+pub struct Task {
+    pub title: String,
+    pub arguments: String,
+    pub description: Option<String>,
+    pub icon: Option<(PathBuf, i32)>,
+}
+
+impl Task {
+    pub fn new(title: impl AsRef<str>, arguments: impl AsRef<str>) -> Self {
+        Self {
+            title: title.as_ref().to_string(),
+            arguments: arguments.as_ref().to_string(),
+            description: None,
+            icon: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl AsRef<str>) -> Self {
+        self.description = Some(description.as_ref().to_string());
+        self
+    }
+
+    pub fn icon(mut self, path: impl AsRef<Path>, index: i32) -> Self {
+        self.icon = Some((path.as_ref().to_path_buf(), index));
+        self
+    }
+}
+
+/// The object to build a jump list.
+#[derive(Clone, Default, Debug)]
+pub struct JumpList {
+    tasks: Vec<Task>,
+    categories: Vec<KnownCategory>,
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(Some(0)).collect()
+}
+
+unsafe fn create_link(exe: &str, task: &Task) -> windows::Result<IShellLinkW> {
+    let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+    link.SetPath(exe).ok()?;
+    link.SetArguments(task.arguments.as_str()).ok()?;
+    if let Some(description) = task.description.as_ref() {
+        link.SetDescription(description.as_str()).ok()?;
+    }
+    if let Some((path, index)) = task.icon.as_ref() {
+        link.SetIconLocation(path.to_string_lossy().as_ref(), *index)
+            .ok()?;
+    }
+    let store = link.cast::<IPropertyStore>()?;
+    let mut title = to_wide(&task.title);
+    let mut value = PROPVARIANT::default();
+    value.Anonymous.Anonymous.vt = VT_LPWSTR;
+    value.Anonymous.Anonymous.Anonymous.pwszVal = PWSTR(title.as_mut_ptr());
+    store.SetValue(&PKEY_Title, &value).ok()?;
+    store.Commit().ok()?;
+    Ok(link)
+}
+
+impl JumpList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn task(mut self, task: Task) -> Self {
+        self.tasks.push(task);
+        self
+    }
+
+    pub fn known_category(mut self, category: KnownCategory) -> Self {
+        self.categories.push(category);
+        self
+    }
+
+    /// Publishes the jump list to the taskbar button.
+    pub fn commit(&self) -> Result<(), ApiError> {
+        initialize_com()?;
+        unsafe {
+            let exe = std::env::current_exe().map_err(|e| {
+                e.raw_os_error()
+                    .map_or_else(ApiError::new, |code| ApiError::from_win32(code as u32))
+                    .with_function("GetModuleFileNameW")
+            })?;
+            let exe = exe.to_string_lossy();
+            let list: ICustomDestinationList =
+                CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+            if let Some(id) = app_user_model_id() {
+                list.SetAppID(id.as_str()).ok()?;
+            }
+            let mut min_slots = 0;
+            let _removed: IObjectArray = list.BeginList(&mut min_slots)?;
+            for category in &self.categories {
+                let category = match category {
+                    KnownCategory::Recent => KDC_RECENT,
+                    KnownCategory::Frequent => KDC_FREQUENT,
+                };
+                list.AppendKnownCategory(category).ok()?;
+            }
+            if !self.tasks.is_empty() {
+                let collection: IObjectCollection =
+                    CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+                for task in &self.tasks {
+                    collection.AddObject(create_link(&exe, task)?).ok()?;
+                }
+                list.AddUserTasks(collection.cast::<IObjectArray>()?).ok()?;
+            }
+            list.CommitList().ok()?;
+            Ok(())
+        }
+    }
+}
+
+/// Removes all items of the jump list.
+pub fn clear() -> Result<(), ApiError> {
+    initialize_com()?;
+    unsafe {
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        match app_user_model_id() {
+            Some(id) => list.DeleteList(id.as_str()).ok()?,
+            None => list.DeleteList(PWSTR::NULL).ok()?,
+        }
+        Ok(())
+    }
+}
+
+/// Adds the file to the recent category.
+///
+/// The file type must be registered for the application.
+pub fn add_recent_file(path: impl AsRef<Path>) {
+    let path = to_wide(&path.as_ref().to_string_lossy());
+    unsafe {
+        SHAddToRecentDocs(SHARD_PATHW.0 as _, path.as_ptr() as _);
+    }
+}
+
+/// Sends the command line arguments of the current process to the running instance
+/// that has the window titled `title`.
+///
+/// Returns `true` if the running instance has processed the arguments.
+pub fn forward_to_instance(title: &str) -> bool {
+    unsafe {
+        let hwnd = FindWindowW(PWSTR::NULL, title);
+        if hwnd == HWND::NULL {
+            return false;
+        }
+        let data = std::env::args()
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join("\0")
+            .into_bytes();
+        let cds = crate::bindings::Windows::Win32::System::DataExchange::COPYDATASTRUCT {
+            dwData: COPY_DATA_ID,
+            cbData: data.len() as _,
+            lpData: data.as_ptr() as _,
+        };
+        SetForegroundWindow(hwnd);
+        SendMessageW(hwnd, WM_COPYDATA, WPARAM(0), LPARAM(&cds as *const _ as _)).0 != 0
+    }
+}
+
+/// Returns the arguments sent by [`forward_to_instance`].
+///
+/// Returns `None` if `id` is not [`COPY_DATA_ID`].
+pub fn parse_arguments(id: usize, data: &[u8]) -> Option<Vec<String>> {
+    if id != COPY_DATA_ID {
+        return None;
+    }
+    if data.is_empty() {
+        return Some(vec![]);
+    }
+    Some(
+        String::from_utf8_lossy(data)
+            .split('\0')
+            .map(|s| s.to_string())
+            .collect(),
+    )
+}
+
+/// Activates the window that has received the forwarded arguments.
+pub fn activate(window: &Window) {
+    unsafe {
+        let hwnd = HWND(window.raw_handle() as _);
+        if IsIconic(hwnd).as_bool() {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        SetForegroundWindow(hwnd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_forwarded_arguments() {
+        assert!(parse_arguments(0, b"a").is_none());
+        assert!(parse_arguments(COPY_DATA_ID, b"").unwrap().is_empty());
+        assert_eq!(
+            parse_arguments(COPY_DATA_ID, b"--open\0file.txt").unwrap(),
+            ["--open", "file.txt"]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Globalization::*, UI::KeyboardAndMouseInput::*,
+};
+use crate::error::ApiError;
+use crate::locale::LOCALE_NAME_MAX_LENGTH;
+
+/// An input locale identifier of a keyboard layout (`HKL`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct KeyboardLayout(isize);
+
+impl KeyboardLayout {
+    /// Loads the keyboard layout that is not installed for the user.
+    ///
+    /// `klid` is the keyboard layout identifier such as `"00000409"` for US and `"00000411"` for Japanese.
+    pub fn load(klid: &str) -> Result<Self, ApiError> {
+        unsafe {
+            let hkl = LoadKeyboardLayoutW(klid, ACTIVATE_KEYBOARD_LAYOUT_FLAGS(0));
+            if hkl.0 == 0 {
+                return Err(ApiError::new().with_function("LoadKeyboardLayoutW"));
+            }
+            Ok(Self(hkl.0))
+        }
+    }
+
+    /// Returns the language identifier such as `0x0409`.
+    pub fn language_id(&self) -> u16 {
+        (self.0 & 0xffff) as u16
+    }
+
+    /// Returns the locale name of the language such as `"en-US"`.
+    pub fn locale_name(&self) -> String {
+        unsafe {
+            let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH];
+            let len = LCIDToLocaleName(
+                self.language_id() as u32,
+                PWSTR(buffer.as_mut_ptr()),
+                buffer.len() as _,
+                0,
+            );
+            if len <= 0 {
+                return String::new();
+            }
+            String::from_utf16_lossy(&buffer[..len as usize - 1])
+        }
+    }
+
+    pub fn raw_handle(&self) -> isize {
+        self.0
+    }
+}
+
+/// Returns the keyboard layouts that are installed for the user.
+pub fn keyboard_layouts() -> Vec<KeyboardLayout> {
+    unsafe {
+        let len = GetKeyboardLayoutList(0, std::ptr::null_mut());
+        if len <= 0 {
+            return Vec::new();
+        }
+        let mut list = vec![HKL::default(); len as usize];
+        let len = GetKeyboardLayoutList(len, list.as_mut_ptr());
+        list.truncate(len.max(0) as usize);
+        list.into_iter().map(|hkl| KeyboardLayout(hkl.0)).collect()
+    }
+}
+
+/// Returns the active keyboard layout of the current thread.
+pub fn current_keyboard_layout() -> KeyboardLayout {
+    unsafe { KeyboardLayout(GetKeyboardLayout(0).0) }
+}
+
+/// Activates the keyboard layout for the whole process and returns the previous one.
+pub fn activate_keyboard_layout(layout: KeyboardLayout) -> Result<KeyboardLayout, ApiError> {
+    unsafe {
+        let prev = ActivateKeyboardLayout(HKL(layout.0), KLF_SETFORPROCESS);
+        if prev.0 == 0 {
+            return Err(ApiError::new().with_function("ActivateKeyboardLayout"));
+        }
+        Ok(KeyboardLayout(prev.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installed_layouts() {
+        let layouts = keyboard_layouts();
+        assert!(!layouts.is_empty());
+        assert!(layouts.contains(&current_keyboard_layout()));
+    }
+}
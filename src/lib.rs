@@ -70,29 +70,104 @@ mod bindings {
     ::windows::include_bindings!();
 }
 
+/// Enters a `tracing` span until the end of the current scope if `"tracing"` feature is enabled.
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::trace_span!($($args)*).entered();
+    };
+}
+
+#[cfg(feature = "accessibility")]
+mod accessibility;
 mod api;
+mod app_id;
+mod bitmap;
+#[cfg(feature = "blur_behind")]
+mod blur;
+#[cfg(any(feature = "capture", doc))]
+pub mod capture;
+pub mod compositor;
+mod config;
 mod context;
+#[cfg(feature = "d2d")]
+mod d2d;
 mod device;
+mod device_notify;
+mod dwm;
 mod event;
+mod frame_pacer;
+mod gdi_frame;
 mod geometry;
 pub mod ime;
+mod input;
+pub mod jumplist;
+mod keyboard_layout;
+mod locale;
+mod message;
 mod monitor;
+#[cfg(any(feature = "opengl", doc))]
+pub mod opengl;
+mod power;
 mod procedure;
 #[cfg(any(feature = "raw_input", doc))]
 pub mod raw_input;
 mod resource;
+mod scroll;
+mod session;
+mod settings;
+#[cfg(feature = "block_shortcuts")]
+mod shortcut_blocker;
+#[cfg(feature = "test_util")]
+mod test_util;
+mod theme;
+mod tooltip;
+#[cfg(feature = "trace_messages")]
+mod trace;
+mod tray;
+#[cfg(feature = "virtual_desktop")]
+mod virtual_desktop;
+#[cfg(feature = "ash")]
+mod vulkan;
 mod window;
 #[macro_use]
 pub mod error;
 
-pub use context::RunType;
+#[cfg(feature = "accessibility")]
+pub use accessibility::{AccessibleElement, AccessibleRole, AnnouncementPriority, ElementRole};
+pub use app_id::*;
+pub use bitmap::Bitmap;
+#[cfg(feature = "blur_behind")]
+pub use blur::BlurEffect;
+pub use config::{WindowConfig, WindowSession};
+pub use context::{DpiAwareness, PanicPolicy, RunOptions, RunType};
 pub use device::*;
+pub use device_notify::DeviceEventKind;
+pub use dwm::CornerPreference;
 #[doc(inline)]
 pub use error::ApiError;
 pub use event::*;
+pub use frame_pacer::FramePacer;
+pub use gdi_frame::GdiFrame;
 pub use geometry::*;
+pub use input::{send_input, set_key_toggled, InputEvent};
+pub use keyboard_layout::{
+    activate_keyboard_layout, current_keyboard_layout, keyboard_layouts, KeyboardLayout,
+};
+pub use locale::{date_format, number_format, user_locale, DateFormat, NumberFormat};
+pub use message::*;
 pub use monitor::*;
+pub use power::{power_status, PowerSource, PowerStatus};
 pub use resource::*;
+pub use scroll::{ScrollAction, ScrollBar, ScrollInfo};
+pub use session::{SessionEndReason, SessionEndResponse};
+pub use settings::SettingKind;
+pub use theme::*;
+pub use tooltip::{ToolId, Tooltip};
+#[cfg(feature = "virtual_desktop")]
+pub use virtual_desktop::VirtualDesktopId;
+#[cfg(feature = "ash")]
+pub use vulkan::vulkan_required_extensions;
 pub use window::*;
 
 use bindings::Windows::Win32::{Foundation::*, UI::WindowsAndMessaging::*};
@@ -102,30 +177,73 @@ use context::*;
 pub const DEFAULT_DPI: i32 = 96;
 
 /// Run the event loop.
+///
+/// Panics if the window classes cannot be registered.
 pub fn run<F, T, E>(run_type: RunType, f: F) -> Result<(), E>
 where
     F: FnOnce() -> Result<T, E>,
     T: EventHandler + 'static,
 {
-    api::enable_dpi_awareness();
+    run_impl(RunOptions::new(run_type), f, |e| panic!("{}", e))
+}
+
+/// Run the event loop with options.
+///
+/// Returns the `ApiError` converted into `E` if the window classes cannot be registered.
+pub fn run_with_options<F, T, E>(options: RunOptions, f: F) -> Result<(), E>
+where
+    F: FnOnce() -> Result<T, E>,
+    T: EventHandler + 'static,
+    E: From<ApiError>,
+{
+    run_impl(options, f, E::from)
+}
+
+fn run_impl<F, T, E>(options: RunOptions, f: F, api_error: fn(ApiError) -> E) -> Result<(), E>
+where
+    F: FnOnce() -> Result<T, E>,
+    T: EventHandler + 'static,
+{
+    if let Some(awareness) = options.dpi_awareness {
+        api::set_dpi_awareness(awareness);
+    }
     api::enable_gui_thread();
-    window::register_class::<T>();
+    window::register_class::<T>().map_err(api_error)?;
+    let mut pacer = match options.max_fps {
+        Some(fps) if options.run_type == RunType::Idle => {
+            Some(FramePacer::new(fps).map_err(api_error)?)
+        }
+        _ => None,
+    };
     context::create_context();
+    context::set_panic_policy(options.panic_policy);
+    context::set_run_type(options.run_type);
     let handler = f();
     match handler {
         Ok(handler) => set_event_handler(handler),
         Err(e) => return Err(e),
     }
     let mut msg = MSG::default();
-    match run_type {
+    match options.run_type {
         RunType::Idle => unsafe {
+            let mut cleared = false;
             while msg.message != WM_QUIT {
                 call_handler(|eh: &mut T, _| eh.pre_processing());
                 if PeekMessageW(&mut msg, HWND::NULL, 0, 0, PM_REMOVE) != BOOL(0) {
-                    TranslateMessage(&msg);
-                    DispatchMessageW(&msg);
+                    if !procedure::navigate_focus(&msg) {
+                        procedure::translate_message::<T>(&msg);
+                        DispatchMessageW(&msg);
+                    }
+                    cleared = false;
                 } else {
-                    call_handler(|eh: &mut T, _| eh.idle());
+                    if !cleared {
+                        call_handler(|eh: &mut T, _| eh.events_cleared());
+                        cleared = true;
+                    }
+                    match pacer.as_mut() {
+                        Some(pacer) if !pacer.wait_frame_or_message() => {}
+                        _ => call_handler(|eh: &mut T, _| eh.idle()),
+                    }
                 }
                 maybe_resume_unwind();
                 call_handler(|eh: &mut T, _| eh.post_processing());
@@ -133,16 +251,24 @@ where
         },
         RunType::Wait => unsafe {
             loop {
+                if PeekMessageW(&mut msg, HWND::NULL, 0, 0, PM_NOREMOVE) == BOOL(0) {
+                    call_handler(|eh: &mut T, _| eh.events_cleared());
+                }
                 let ret = GetMessageW(&mut msg, HWND::NULL, 0, 0);
                 if ret == BOOL(0) || ret == BOOL(-1) {
                     break;
                 }
-                TranslateMessage(&msg);
-                DispatchMessageW(&msg);
+                call_handler(|eh: &mut T, _| eh.pre_processing());
+                if !procedure::navigate_focus(&msg) {
+                    procedure::translate_message::<T>(&msg);
+                    DispatchMessageW(&msg);
+                }
                 maybe_resume_unwind();
+                call_handler(|eh: &mut T, _| eh.post_processing());
             }
         },
     }
+    dwm::unwatch_cloak_events();
     destroy_context();
     Ok(())
 }
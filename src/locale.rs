@@ -0,0 +1,94 @@
+use crate::bindings::Windows::Win32::{Foundation::*, Globalization::*};
+
+pub(crate) const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+const LOCALE_SDECIMAL: u32 = 0x0000000e;
+const LOCALE_STHOUSAND: u32 = 0x0000000f;
+const LOCALE_SCURRENCY: u32 = 0x00000014;
+const LOCALE_SSHORTDATE: u32 = 0x0000001f;
+const LOCALE_SLONGDATE: u32 = 0x00000020;
+const LOCALE_SNEGATIVESIGN: u32 = 0x00000051;
+const LOCALE_SSHORTTIME: u32 = 0x00000079;
+const LOCALE_STIMEFORMAT: u32 = 0x00001003;
+const LOCALE_IFIRSTDAYOFWEEK: u32 = 0x0000100c;
+
+/// The settings to format numbers.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct NumberFormat {
+    pub decimal_separator: String,
+    pub group_separator: String,
+    pub negative_sign: String,
+    pub currency_symbol: String,
+}
+
+/// The settings to format dates and times.
+///
+/// The patterns are the format pictures of Windows such as `"yyyy/MM/dd"` and `"HH:mm:ss"`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct DateFormat {
+    pub short_date: String,
+    pub long_date: String,
+    pub short_time: String,
+    pub long_time: String,
+    /// `0` is Monday and `6` is Sunday.
+    pub first_day_of_week: u32,
+}
+
+fn locale_info(lctype: u32) -> String {
+    unsafe {
+        let len = GetLocaleInfoEx(PWSTR::NULL, lctype, PWSTR::NULL, 0);
+        if len <= 0 {
+            return String::new();
+        }
+        let mut buffer = vec![0u16; len as usize];
+        let len = GetLocaleInfoEx(PWSTR::NULL, lctype, PWSTR(buffer.as_mut_ptr()), len);
+        if len <= 0 {
+            return String::new();
+        }
+        String::from_utf16_lossy(&buffer[..len as usize - 1])
+    }
+}
+
+/// Returns the locale name of the user such as `"en-US"`.
+pub fn user_locale() -> String {
+    unsafe {
+        let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH];
+        let len = GetUserDefaultLocaleName(PWSTR(buffer.as_mut_ptr()), buffer.len() as _);
+        if len <= 0 {
+            return String::new();
+        }
+        String::from_utf16_lossy(&buffer[..len as usize - 1])
+    }
+}
+
+/// Returns the settings to format numbers of the user.
+pub fn number_format() -> NumberFormat {
+    NumberFormat {
+        decimal_separator: locale_info(LOCALE_SDECIMAL),
+        group_separator: locale_info(LOCALE_STHOUSAND),
+        negative_sign: locale_info(LOCALE_SNEGATIVESIGN),
+        currency_symbol: locale_info(LOCALE_SCURRENCY),
+    }
+}
+
+/// Returns the settings to format dates and times of the user.
+pub fn date_format() -> DateFormat {
+    DateFormat {
+        short_date: locale_info(LOCALE_SSHORTDATE),
+        long_date: locale_info(LOCALE_SLONGDATE),
+        short_time: locale_info(LOCALE_SSHORTTIME),
+        long_time: locale_info(LOCALE_STIMEFORMAT),
+        first_day_of_week: locale_info(LOCALE_IFIRSTDAYOFWEEK).parse().unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_locale_name() {
+        assert!(!user_locale().is_empty());
+        assert!(!number_format().decimal_separator.is_empty());
+    }
+}
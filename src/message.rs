@@ -0,0 +1,57 @@
+use crate::bindings::Windows::Win32::UI::WindowsAndMessaging::*;
+use crate::error::ApiError;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const FIRST_ID: u32 = 0xc000;
+const ID_COUNT: usize = 0x4000;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: AtomicU64 = AtomicU64::new(0);
+
+/// The set of identifiers that `register_message` has returned in this process.
+///
+/// Other messages in the same range such as `TaskbarCreated` are not routed to `EventHandler::custom_message`.
+static REGISTERED: [AtomicU64; ID_COUNT / 64] = [EMPTY; ID_COUNT / 64];
+
+#[inline]
+fn bit(msg: u32) -> Option<(&'static AtomicU64, u64)> {
+    let index = msg.checked_sub(FIRST_ID)? as usize;
+    if index >= ID_COUNT {
+        return None;
+    }
+    Some((&REGISTERED[index / 64], 1 << (index % 64)))
+}
+
+/// An identifier of a registered window message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct MessageId(pub(crate) u32);
+
+impl MessageId {
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    #[inline]
+    pub(crate) fn is_registered(msg: u32) -> bool {
+        bit(msg).map_or(false, |(bits, mask)| {
+            bits.load(Ordering::Acquire) & mask != 0
+        })
+    }
+}
+
+/// Register the window message that is unique in the system.
+///
+/// The same name returns the same identifier in all processes.
+pub fn register_message(name: impl AsRef<str>) -> Result<MessageId, ApiError> {
+    unsafe {
+        let msg = RegisterWindowMessageW(name.as_ref());
+        if msg == 0 {
+            return Err(ApiError::new().with_function("RegisterWindowMessageW"));
+        }
+        if let Some((bits, mask)) = bit(msg) {
+            bits.fetch_or(mask, Ordering::Release);
+        }
+        Ok(MessageId(msg))
+    }
+}
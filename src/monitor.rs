@@ -1,7 +1,28 @@
 use crate::bindings::Windows::Win32::{
-    Foundation::*, Graphics::Gdi::*, UI::WindowsAndMessaging::*,
+    Devices::Display::*, Foundation::*, Graphics::Gdi::*, UI::WindowsAndMessaging::*,
 };
 use crate::geometry::*;
+use crate::window::Window;
+
+const ERROR_SUCCESS: i32 = 0;
+
+#[repr(C)]
+struct MonitorInfoEx {
+    info: MONITORINFO,
+    device: [u16; 32],
+}
+
+/// Describes the color capability of a monitor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorInfo {
+    /// The monitor supports advanced color such as HDR and wide color gamut.
+    pub advanced_color_supported: bool,
+    /// Advanced color is enabled by the user.
+    pub advanced_color_enabled: bool,
+    /// HDR is enabled, not only wide color gamut.
+    pub hdr_enabled: bool,
+    pub bits_per_color_channel: u32,
+}
 
 /// Describes monitor info.
 #[derive(Clone, Debug)]
@@ -12,6 +33,122 @@ pub struct Monitor {
     pub is_primary: bool,
 }
 
+impl Monitor {
+    #[inline]
+    pub(crate) fn hmonitor(&self) -> HMONITOR {
+        self.hmonitor
+    }
+
+    /// Returns the identifier of the physical monitor that is stable across reboots.
+    ///
+    /// This is the device path of the monitor, which includes the EDID-derived hardware ID
+    /// and the connector. `HMONITOR` changes when the display configuration is changed.
+    pub fn id(&self) -> Option<String> {
+        unsafe {
+            let path = display_path(&device_name(self.hmonitor)?)?;
+            let mut name: DISPLAYCONFIG_TARGET_DEVICE_NAME = std::mem::zeroed();
+            name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
+            name.header.size = std::mem::size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as _;
+            name.header.adapterId = path.targetInfo.adapterId;
+            name.header.id = path.targetInfo.id;
+            if DisplayConfigGetDeviceInfo(&mut name.header) != ERROR_SUCCESS {
+                return None;
+            }
+            let device_path = &name.monitorDevicePath;
+            let len = device_path
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(device_path.len());
+            if len == 0 {
+                return None;
+            }
+            Some(String::from_utf16_lossy(&device_path[..len]))
+        }
+    }
+
+    /// Finds the connected monitor by the identifier returned by `Monitor::id`.
+    pub fn from_id(id: &str) -> Option<Monitor> {
+        get_monitors()
+            .into_iter()
+            .find(|monitor| monitor.id().as_deref() == Some(id))
+    }
+
+    /// Returns the advanced color state and the bit depth of the monitor.
+    pub fn color_info(&self) -> Option<ColorInfo> {
+        unsafe {
+            let path = display_path(&device_name(self.hmonitor)?)?;
+            let mut info: DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO = std::mem::zeroed();
+            info.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO;
+            info.header.size = std::mem::size_of::<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO>() as _;
+            info.header.adapterId = path.targetInfo.adapterId;
+            info.header.id = path.targetInfo.id;
+            if DisplayConfigGetDeviceInfo(&mut info.header) != ERROR_SUCCESS {
+                return None;
+            }
+            let value = info.Anonymous.value;
+            let enabled = value & 0x2 != 0;
+            let wide_color_enforced = value & 0x4 != 0;
+            Some(ColorInfo {
+                advanced_color_supported: value & 0x1 != 0,
+                advanced_color_enabled: enabled,
+                hdr_enabled: enabled && !wide_color_enforced,
+                bits_per_color_channel: info.bitsPerColorChannel,
+            })
+        }
+    }
+}
+
+/// Returns the GDI device name such as `\\.\DISPLAY1`.
+unsafe fn device_name(hmonitor: HMONITOR) -> Option<[u16; 32]> {
+    let mut info = MonitorInfoEx {
+        info: MONITORINFO {
+            cbSize: std::mem::size_of::<MonitorInfoEx>() as u32,
+            ..Default::default()
+        },
+        device: [0; 32],
+    };
+    if !GetMonitorInfoW(hmonitor, &mut info.info).as_bool() {
+        return None;
+    }
+    Some(info.device)
+}
+
+/// Finds the active display path whose source is the GDI device.
+unsafe fn display_path(device: &[u16; 32]) -> Option<DISPLAYCONFIG_PATH_INFO> {
+    let mut path_len = 0;
+    let mut mode_len = 0;
+    if GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_len, &mut mode_len)
+        != ERROR_SUCCESS
+    {
+        return None;
+    }
+    let mut paths: Vec<DISPLAYCONFIG_PATH_INFO> = Vec::new();
+    paths.resize_with(path_len as usize, || std::mem::zeroed());
+    let mut modes: Vec<DISPLAYCONFIG_MODE_INFO> = Vec::new();
+    modes.resize_with(mode_len as usize, || std::mem::zeroed());
+    let ret = QueryDisplayConfig(
+        QDC_ONLY_ACTIVE_PATHS,
+        &mut path_len,
+        paths.as_mut_ptr(),
+        &mut mode_len,
+        modes.as_mut_ptr(),
+        std::ptr::null_mut(),
+    );
+    if ret != ERROR_SUCCESS {
+        return None;
+    }
+    paths.truncate(path_len as usize);
+    paths.into_iter().find(|path| {
+        let mut name: DISPLAYCONFIG_SOURCE_DEVICE_NAME = std::mem::zeroed();
+        name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
+        name.header.size = std::mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as _;
+        name.header.adapterId = path.sourceInfo.adapterId;
+        name.header.id = path.sourceInfo.id;
+        DisplayConfigGetDeviceInfo(&mut name.header) == ERROR_SUCCESS
+            && name.viewGdiDeviceName == *device
+    })
+}
+
 impl PartialEq for Monitor {
     fn eq(&self, other: &Monitor) -> bool {
         self.hmonitor == other.hmonitor
@@ -57,19 +194,22 @@ pub fn get_monitors() -> Vec<Monitor> {
     }
 }
 
-/// A screen position to a monitor.
-pub fn monitor_from_point(point: ScreenPosition) -> Option<Monitor> {
+/// Describes which monitor is returned when the point is not on any monitor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MonitorFallback {
+    /// Returns `None`.
+    None,
+    /// Returns the nearest monitor.
+    Nearest,
+    /// Returns the primary monitor.
+    Primary,
+}
+
+fn monitor_from_hmonitor(hmonitor: HMONITOR) -> Option<Monitor> {
+    if hmonitor == HMONITOR::NULL {
+        return None;
+    }
     unsafe {
-        let hmonitor = MonitorFromPoint(
-            POINT {
-                x: point.x,
-                y: point.y,
-            },
-            MONITOR_DEFAULTTONULL,
-        );
-        if hmonitor == HMONITOR::NULL {
-            return None;
-        }
         let mut info = MONITORINFO {
             cbSize: std::mem::size_of::<MONITORINFO>() as u32,
             ..Default::default()
@@ -87,6 +227,47 @@ pub fn monitor_from_point(point: ScreenPosition) -> Option<Monitor> {
     }
 }
 
+/// A screen position to a monitor.
+pub fn monitor_from_point(point: ScreenPosition) -> Option<Monitor> {
+    monitor_from_point_with(point, MonitorFallback::None)
+}
+
+/// A screen position to a monitor with the fallback for points outside any monitor.
+///
+/// This returns `Some` except for `MonitorFallback::None`.
+pub fn monitor_from_point_with(
+    point: ScreenPosition,
+    fallback: MonitorFallback,
+) -> Option<Monitor> {
+    let flags = match fallback {
+        MonitorFallback::None => MONITOR_DEFAULTTONULL,
+        MonitorFallback::Nearest => MONITOR_DEFAULTTONEAREST,
+        MonitorFallback::Primary => MONITOR_DEFAULTTOPRIMARY,
+    };
+    let hmonitor = unsafe {
+        MonitorFromPoint(
+            POINT {
+                x: point.x,
+                y: point.y,
+            },
+            flags,
+        )
+    };
+    monitor_from_hmonitor(hmonitor)
+}
+
+/// Returns the monitor that has the largest area of the window, or the nearest monitor.
+///
+/// Returns `None` if the window has been closed.
+pub fn monitor_from_window(window: &Window) -> Option<Monitor> {
+    if window.is_closed() {
+        return None;
+    }
+    let hmonitor =
+        unsafe { MonitorFromWindow(HWND(window.raw_handle() as _), MONITOR_DEFAULTTONEAREST) };
+    monitor_from_hmonitor(hmonitor)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,4 +278,20 @@ mod tests {
             assert_eq!(monitors.len(), GetSystemMetrics(SM_CMONITORS) as usize);
         }
     }
+
+    #[test]
+    fn nearest_monitor() {
+        let far = ScreenPosition::new(i32::MAX / 2, i32::MAX / 2);
+        assert!(monitor_from_point(far).is_none());
+        assert!(monitor_from_point_with(far, MonitorFallback::Nearest).is_some());
+    }
+
+    #[test]
+    fn from_id() {
+        for monitor in get_monitors() {
+            if let Some(id) = monitor.id() {
+                assert_eq!(Monitor::from_id(&id), Some(monitor));
+            }
+        }
+    }
 }
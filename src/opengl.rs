@@ -0,0 +1,292 @@
+//! Provides OpenGL contexts with WGL.
+//!
+//! To use, specify `"opengl"` feature.
+//!
+//! The window should be built with `WindowBuilder::own_dc(true)` because the device context must not change
+//! while the OpenGL context is used.
+
+use crate::bindings::Windows::Win32::{
+    Foundation::*, Graphics::Gdi::*, Graphics::OpenGL::*, System::LibraryLoader::*,
+};
+use crate::error::ApiError;
+use crate::window::Window;
+use std::ffi::c_void;
+
+const WGL_CONTEXT_MAJOR_VERSION_ARB: i32 = 0x2091;
+const WGL_CONTEXT_MINOR_VERSION_ARB: i32 = 0x2092;
+const WGL_CONTEXT_FLAGS_ARB: i32 = 0x2094;
+const WGL_CONTEXT_PROFILE_MASK_ARB: i32 = 0x9126;
+const WGL_CONTEXT_DEBUG_BIT_ARB: i32 = 0x0001;
+const WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB: i32 = 0x0002;
+const WGL_CONTEXT_CORE_PROFILE_BIT_ARB: i32 = 0x0001;
+const WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB: i32 = 0x0002;
+
+type WglCreateContextAttribsArb = unsafe extern "system" fn(HDC, HGLRC, *const i32) -> HGLRC;
+type WglSwapIntervalExt = unsafe extern "system" fn(i32) -> BOOL;
+
+/// Describes an OpenGL profile.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Profile {
+    Core,
+    Compatibility,
+}
+
+/// The object to build a `GlContext`.
+#[derive(Clone, Debug)]
+pub struct GlContextBuilder {
+    version: (u8, u8),
+    profile: Profile,
+    debug: bool,
+    forward_compatible: bool,
+    color_bits: u8,
+    alpha_bits: u8,
+    depth_bits: u8,
+    stencil_bits: u8,
+    double_buffer: bool,
+}
+
+impl GlContextBuilder {
+    pub fn new() -> Self {
+        Self {
+            version: (3, 3),
+            profile: Profile::Core,
+            debug: false,
+            forward_compatible: false,
+            color_bits: 24,
+            alpha_bits: 8,
+            depth_bits: 24,
+            stencil_bits: 8,
+            double_buffer: true,
+        }
+    }
+
+    pub fn version(mut self, major: u8, minor: u8) -> Self {
+        self.version = (major, minor);
+        self
+    }
+
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn debug(mut self, enable: bool) -> Self {
+        self.debug = enable;
+        self
+    }
+
+    pub fn forward_compatible(mut self, enable: bool) -> Self {
+        self.forward_compatible = enable;
+        self
+    }
+
+    pub fn color_bits(mut self, bits: u8) -> Self {
+        self.color_bits = bits;
+        self
+    }
+
+    pub fn alpha_bits(mut self, bits: u8) -> Self {
+        self.alpha_bits = bits;
+        self
+    }
+
+    pub fn depth_bits(mut self, bits: u8) -> Self {
+        self.depth_bits = bits;
+        self
+    }
+
+    pub fn stencil_bits(mut self, bits: u8) -> Self {
+        self.stencil_bits = bits;
+        self
+    }
+
+    pub fn double_buffer(mut self, enable: bool) -> Self {
+        self.double_buffer = enable;
+        self
+    }
+
+    /// Sets the pixel format of the window and creates the context.
+    ///
+    /// The pixel format of a window can be set only once.
+    pub fn build(self, window: &Window) -> Result<GlContext, ApiError> {
+        unsafe {
+            let hwnd = HWND(window.raw_handle() as _);
+            let hdc = GetDC(hwnd);
+            if hdc.0 == 0 {
+                return Err(ApiError::new().with_function("GetDC"));
+            }
+            let mut ctx = GlContext {
+                hwnd,
+                hdc,
+                hglrc: HGLRC::NULL,
+            };
+            let mut flags = PFD_DRAW_TO_WINDOW.0 | PFD_SUPPORT_OPENGL.0;
+            if self.double_buffer {
+                flags |= PFD_DOUBLEBUFFER.0;
+            }
+            let pfd = PIXELFORMATDESCRIPTOR {
+                nSize: std::mem::size_of::<PIXELFORMATDESCRIPTOR>() as _,
+                nVersion: 1,
+                dwFlags: PFD_FLAGS(flags),
+                iPixelType: PFD_TYPE_RGBA,
+                cColorBits: self.color_bits,
+                cAlphaBits: self.alpha_bits,
+                cDepthBits: self.depth_bits,
+                cStencilBits: self.stencil_bits,
+                iLayerType: PFD_MAIN_PLANE,
+                ..Default::default()
+            };
+            let format = ChoosePixelFormat(hdc, &pfd);
+            if format == 0 {
+                return Err(ApiError::new().with_function("ChoosePixelFormat"));
+            }
+            if !SetPixelFormat(hdc, format, &pfd).as_bool() {
+                return Err(ApiError::new().with_function("SetPixelFormat"));
+            }
+            // wglCreateContextAttribsARB can be loaded only while a context is current.
+            let legacy = wglCreateContext(hdc);
+            if legacy.0 == 0 {
+                return Err(ApiError::new().with_function("wglCreateContext"));
+            }
+            let create_context_attribs = if wglMakeCurrent(hdc, legacy).as_bool() {
+                wglGetProcAddress("wglCreateContextAttribsARB")
+                    .map(|f| std::mem::transmute::<_, WglCreateContextAttribsArb>(f))
+            } else {
+                None
+            };
+            let create_context_attribs = match create_context_attribs {
+                Some(f) => f,
+                None => {
+                    let e = ApiError::new().with_function("wglGetProcAddress");
+                    wglMakeCurrent(HDC::NULL, HGLRC::NULL);
+                    wglDeleteContext(legacy);
+                    return Err(e);
+                }
+            };
+            let mut context_flags = 0;
+            if self.debug {
+                context_flags |= WGL_CONTEXT_DEBUG_BIT_ARB;
+            }
+            if self.forward_compatible {
+                context_flags |= WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB;
+            }
+            let attribs = [
+                WGL_CONTEXT_MAJOR_VERSION_ARB,
+                self.version.0 as i32,
+                WGL_CONTEXT_MINOR_VERSION_ARB,
+                self.version.1 as i32,
+                WGL_CONTEXT_FLAGS_ARB,
+                context_flags,
+                WGL_CONTEXT_PROFILE_MASK_ARB,
+                match self.profile {
+                    Profile::Core => WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
+                    Profile::Compatibility => WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB,
+                },
+                0,
+            ];
+            let hglrc = create_context_attribs(hdc, HGLRC::NULL, attribs.as_ptr());
+            let e = ApiError::new().with_function("wglCreateContextAttribsARB");
+            wglMakeCurrent(HDC::NULL, HGLRC::NULL);
+            wglDeleteContext(legacy);
+            if hglrc.0 == 0 {
+                return Err(e);
+            }
+            ctx.hglrc = hglrc;
+            Ok(ctx)
+        }
+    }
+}
+
+impl Default for GlContextBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An OpenGL context for a window.
+pub struct GlContext {
+    hwnd: HWND,
+    hdc: HDC,
+    hglrc: HGLRC,
+}
+
+impl GlContext {
+    /// Makes the context current on the calling thread.
+    pub fn make_current(&self) -> Result<(), ApiError> {
+        unsafe {
+            if !wglMakeCurrent(self.hdc, self.hglrc).as_bool() {
+                return Err(ApiError::new().with_function("wglMakeCurrent"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Releases the current context of the calling thread.
+    pub fn make_not_current(&self) -> Result<(), ApiError> {
+        unsafe {
+            if !wglMakeCurrent(HDC::NULL, HGLRC::NULL).as_bool() {
+                return Err(ApiError::new().with_function("wglMakeCurrent"));
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        unsafe { wglGetCurrentContext() == self.hglrc }
+    }
+
+    pub fn swap_buffers(&self) -> Result<(), ApiError> {
+        unsafe {
+            if !SwapBuffers(self.hdc).as_bool() {
+                return Err(ApiError::new().with_function("SwapBuffers"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Sets the number of vertical blanks to wait for in `swap_buffers`.
+    ///
+    /// The context must be current. `0` disables vsync, and a negative value enables adaptive vsync
+    /// if `WGL_EXT_swap_control_tear` is supported.
+    pub fn set_swap_interval(&self, interval: i32) -> Result<(), ApiError> {
+        unsafe {
+            let f = match wglGetProcAddress("wglSwapIntervalEXT") {
+                Some(f) => std::mem::transmute::<_, WglSwapIntervalExt>(f),
+                None => return Err(ApiError::new().with_function("wglGetProcAddress")),
+            };
+            if !f(interval).as_bool() {
+                return Err(ApiError::new().with_function("wglSwapIntervalEXT"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Returns the address of an OpenGL function.
+    ///
+    /// The context must be current. OpenGL 1.1 functions are loaded from `opengl32.dll`.
+    pub fn get_proc_address(&self, name: &str) -> *const c_void {
+        unsafe {
+            // wglGetProcAddress can return 1, 2, 3 or -1 on failure.
+            match wglGetProcAddress(name) {
+                Some(f) if !matches!(f as isize, 1 | 2 | 3 | -1) => f as _,
+                _ => GetProcAddress(GetModuleHandleW("opengl32.dll"), name)
+                    .map_or(std::ptr::null(), |f| f as _),
+            }
+        }
+    }
+}
+
+impl Drop for GlContext {
+    fn drop(&mut self) {
+        unsafe {
+            if self.hglrc.0 != 0 {
+                if self.is_current() {
+                    wglMakeCurrent(HDC::NULL, HGLRC::NULL);
+                }
+                wglDeleteContext(self.hglrc);
+            }
+            ReleaseDC(self.hwnd, self.hdc);
+        }
+    }
+}
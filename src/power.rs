@@ -0,0 +1,120 @@
+use crate::api::load_proc;
+use crate::bindings::Windows::Win32::{Foundation::*, System::LibraryLoader::*, System::Power::*};
+use crate::last_error;
+use std::sync::Once;
+use std::time::Duration;
+
+pub(crate) const DEVICE_NOTIFY_WINDOW_HANDLE: u32 = 0;
+
+pub(crate) const PBT_APMSUSPEND: u32 = 0x0004;
+pub(crate) const PBT_APMRESUMEAUTOMATIC: u32 = 0x0012;
+pub(crate) const PBT_APMPOWERSTATUSCHANGE: u32 = 0x000a;
+
+const BATTERY_FLAG_NO_SYSTEM_BATTERY: u8 = 128;
+const BATTERY_PERCENTAGE_UNKNOWN: u8 = 255;
+const BATTERY_LIFE_UNKNOWN: u32 = 0xffffffff;
+
+/// Describes the power source of the system.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    Unknown,
+}
+
+/// The power status of the system.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct PowerStatus {
+    pub source: PowerSource,
+    /// `None` if the system has no battery or the percentage is unknown.
+    pub battery_percent: Option<u8>,
+    /// The remaining battery life time if it is known.
+    pub battery_life_time: Option<Duration>,
+    /// `true` if the battery saver is on.
+    pub power_saver: bool,
+}
+
+/// Returns the power status of the system.
+pub fn power_status() -> Option<PowerStatus> {
+    unsafe {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        if !GetSystemPowerStatus(&mut status).as_bool() {
+            last_error!("GetSystemPowerStatus");
+            return None;
+        }
+        let source = match status.ACLineStatus {
+            0 => PowerSource::Battery,
+            1 => PowerSource::Ac,
+            _ => PowerSource::Unknown,
+        };
+        let battery_percent = if status.BatteryFlag & BATTERY_FLAG_NO_SYSTEM_BATTERY != 0
+            || status.BatteryLifePercent == BATTERY_PERCENTAGE_UNKNOWN
+        {
+            None
+        } else {
+            Some(status.BatteryLifePercent)
+        };
+        let battery_life_time = if status.BatteryLifeTime == BATTERY_LIFE_UNKNOWN {
+            None
+        } else {
+            Some(Duration::from_secs(status.BatteryLifeTime as u64))
+        };
+        Some(PowerStatus {
+            source,
+            battery_percent,
+            battery_life_time,
+            power_saver: status.SystemStatusFlag & 0x01 != 0,
+        })
+    }
+}
+
+type RegisterSuspendResumeNotificationFn = unsafe extern "system" fn(HANDLE, u32) -> HPOWERNOTIFY;
+type UnregisterSuspendResumeNotificationFn = unsafe extern "system" fn(HPOWERNOTIFY) -> BOOL;
+
+// These functions don't exist before Windows 8, so they are loaded at runtime instead of being linked.
+struct NotificationFunctions {
+    register: Option<RegisterSuspendResumeNotificationFn>,
+    unregister: Option<UnregisterSuspendResumeNotificationFn>,
+}
+
+fn notification_functions() -> &'static NotificationFunctions {
+    static LOAD: Once = Once::new();
+    static mut FUNCTIONS: Option<NotificationFunctions> = None;
+    unsafe {
+        LOAD.call_once(|| {
+            let user32 = GetModuleHandleW("user32.dll");
+            FUNCTIONS = Some(NotificationFunctions {
+                register: load_proc(user32, "RegisterSuspendResumeNotification"),
+                unregister: load_proc(user32, "UnregisterSuspendResumeNotification"),
+            });
+        });
+        FUNCTIONS.as_ref().unwrap()
+    }
+}
+
+pub(crate) struct SuspendResumeNotification(HPOWERNOTIFY);
+
+impl SuspendResumeNotification {
+    /// Returns `None` without registering if the system does not support the notification.
+    pub fn new(hwnd: HWND) -> Option<Self> {
+        let register = notification_functions().register?;
+        unsafe {
+            let h = register(HANDLE(hwnd.0), DEVICE_NOTIFY_WINDOW_HANDLE);
+            if h.0 == 0 {
+                last_error!("RegisterSuspendResumeNotification");
+                return None;
+            }
+            Some(Self(h))
+        }
+    }
+}
+
+impl Drop for SuspendResumeNotification {
+    fn drop(&mut self) {
+        if let Some(unregister) = notification_functions().unregister {
+            unsafe {
+                unregister(self.0);
+            }
+        }
+    }
+}
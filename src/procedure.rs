@@ -1,12 +1,42 @@
+#[cfg(feature = "accessibility")]
+use crate::accessibility;
 use crate::bindings::Windows::Win32::{
-    Foundation::*, Globalization::*, Graphics::Gdi::*, UI::Controls::WM_MOUSELEAVE, UI::HiDpi::*,
-    UI::KeyboardAndMouseInput::*, UI::Shell::*, UI::WindowsAndMessaging::*,
+    Foundation::*, Globalization::*, Graphics::Gdi::*, System::DataExchange::COPYDATASTRUCT,
+    UI::Controls::WM_MOUSELEAVE, UI::HiDpi::*, UI::KeyboardAndMouseInput::*, UI::Shell::*,
+    UI::WindowsAndMessaging::*,
 };
 #[cfg(feature = "raw_input")]
 use crate::raw_input;
-use crate::{api::*, context::*, device::*, event::EventHandler, geometry::*, ime, window::Window};
-use std::panic::catch_unwind;
+#[cfg(feature = "block_shortcuts")]
+use crate::shortcut_blocker;
+use crate::{
+    api::*,
+    config,
+    context::*,
+    device::*,
+    device_notify, dwm,
+    event::{DroppedItem, EventHandler, ModalLoop, VisibilityState},
+    geometry::*,
+    ime,
+    locale::user_locale,
+    message::MessageId,
+    power,
+    scroll::{self, ScrollBar},
+    session::{self, SessionEndReason, SessionEndResponse},
+    settings::{self, SettingKind},
+    theme::*,
+    tray::{self, TrayIcon, WM_TRAY_NOTIFY},
+    window::{is_wita_window, Background, LocalWindow, Window},
+};
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+const SIZE_MOVE_TIMER_ID: usize = 0x7769_7461;
+const MSGF_MENU: usize = 2;
+const SIZE_MINIMIZED: usize = 1;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(usize)]
@@ -17,7 +47,16 @@ pub(crate) enum UserMessage {
     EnableIme,
     DisableIme,
     SetStyle,
+    SetStyleBits,
     AcceptDragFiles,
+    FirstDraw,
+    UpdateVisibility,
+    SetRelativeMouseMode,
+    RestoreSession,
+    Destroy,
+    FocusChild,
+    #[cfg(feature = "block_shortcuts")]
+    BlockShortcuts,
 }
 
 #[inline]
@@ -50,6 +89,15 @@ fn get_keystate_wparam(wp: WPARAM) -> u32 {
     (wp.0 & 0xffff) as _
 }
 
+unsafe fn lparam_to_string(lparam: LPARAM) -> Option<String> {
+    if lparam.0 == 0 {
+        return None;
+    }
+    let p = lparam.0 as *const u16;
+    let len = (0..isize::MAX).position(|i| *p.offset(i) == 0).unwrap();
+    Some(String::from_utf16_lossy(std::slice::from_raw_parts(p, len)))
+}
+
 #[inline]
 fn lparam_to_point(lparam: LPARAM) -> PhysicalPosition<i32> {
     PhysicalPosition::new(get_x_lparam(lparam) as i32, get_y_lparam(lparam) as i32)
@@ -65,23 +113,104 @@ fn wparam_to_button(wparam: WPARAM) -> MouseButton {
 }
 
 fn update_buttons(buttons: &mut Vec<MouseButton>, wparam: WPARAM) {
-    buttons.clear();
     let values = get_keystate_wparam(wparam);
+    let mut pressed = MouseButtons::empty();
     if values & MK_LBUTTON != 0 {
-        buttons.push(MouseButton::Left);
+        pressed.insert(MouseButton::Left);
     }
     if values & MK_RBUTTON != 0 {
-        buttons.push(MouseButton::Right);
+        pressed.insert(MouseButton::Right);
     }
     if values & MK_MBUTTON != 0 {
-        buttons.push(MouseButton::Middle);
+        pressed.insert(MouseButton::Middle);
     }
     if values & MK_XBUTTON1 != 0 {
-        buttons.push(MouseButton::Ex(0));
+        pressed.insert(MouseButton::Ex(0));
     }
     if values & MK_XBUTTON2 != 0 {
-        buttons.push(MouseButton::Ex(1));
+        pressed.insert(MouseButton::Ex(1));
+    }
+    buttons.clear();
+    buttons.extend(pressed.iter());
+}
+
+fn is_aero_snap_borderless(window: &Window) -> bool {
+    window.flags.aero_snap_borderless.load(Ordering::Relaxed)
+}
+
+/// The frame of the Aero Snap borderless style is removed by WM_NCCALCSIZE.
+fn frame_style(window: &Window, style: u32) -> u32 {
+    if is_aero_snap_borderless(window) {
+        WS_POPUP.0
+    } else {
+        style
+    }
+}
+
+fn is_custom_caption_button(window: &Window, wparam: WPARAM) -> bool {
+    window.flags.hit_test().is_some()
+        && matches!(wparam.0 as u32, HTMINBUTTON | HTMAXBUTTON | HTCLOSE)
+}
+
+unsafe fn update_visibility<T: EventHandler + 'static>(
+    window: &LocalWindow,
+    hwnd: HWND,
+    shown: bool,
+) {
+    let state = if !shown {
+        VisibilityState::Hidden
+    } else if IsIconic(hwnd).as_bool() {
+        VisibilityState::Minimized
+    } else {
+        dwm::watch_cloak_events();
+        if dwm::is_cloaked(hwnd) {
+            VisibilityState::Cloaked
+        } else {
+            VisibilityState::Visible
+        }
+    };
+    if window.visibility.replace(state) != state {
+        call_handler(|eh: &mut T, _| eh.visibility_changed(&window.handle, state));
+    }
+}
+
+unsafe fn client_center(hwnd: HWND) -> POINT {
+    let mut rc = RECT::default();
+    GetClientRect(hwnd, &mut rc);
+    let mut center = POINT {
+        x: (rc.left + rc.right) / 2,
+        y: (rc.top + rc.bottom) / 2,
+    };
+    ClientToScreen(hwnd, &mut center);
+    center
+}
+
+/// Confines the cursor to the client area and moves it to the center, or releases it.
+unsafe fn confine_cursor(hwnd: HWND, enable: bool) {
+    if !enable {
+        ClipCursor(std::ptr::null());
+        return;
     }
+    let mut rc = RECT::default();
+    GetClientRect(hwnd, &mut rc);
+    let mut lt = POINT {
+        x: rc.left,
+        y: rc.top,
+    };
+    let mut rb = POINT {
+        x: rc.right,
+        y: rc.bottom,
+    };
+    ClientToScreen(hwnd, &mut lt);
+    ClientToScreen(hwnd, &mut rb);
+    ClipCursor(&RECT {
+        left: lt.x,
+        top: lt.y,
+        right: rb.x,
+        bottom: rb.y,
+    });
+    let center = client_center(hwnd);
+    SetCursorPos(center.x, center.y);
 }
 
 unsafe fn mouse_input<T: EventHandler + 'static>(
@@ -107,17 +236,22 @@ unsafe fn mouse_input<T: EventHandler + 'static>(
     LRESULT(0)
 }
 
+fn key_code(wparam: WPARAM, lparam: LPARAM) -> KeyCode {
+    let scan_code = ScanCode(((lparam.0 >> 16) & 0x7f) as u32);
+    KeyCode::new(as_virtual_key(wparam.0 as u32), scan_code)
+}
+
 fn key_input<T: EventHandler + 'static>(
     window: &Window,
     state: KeyState,
     wparam: WPARAM,
     lparam: LPARAM,
 ) -> LRESULT {
-    let scan_code = ScanCode(((lparam.0 >> 16) & 0x7f) as u32);
-    call_handler(|eh: &mut T, _| {
+    call_handler(|eh: &mut T, cs| {
+        cs.consumed_key = false;
         eh.key_input(
             window,
-            KeyCode::new(as_virtual_key(wparam.0 as u32), scan_code),
+            key_code(wparam, lparam),
             state,
             (lparam.0 >> 30) & 0x01 != 0,
         );
@@ -125,29 +259,188 @@ fn key_input<T: EventHandler + 'static>(
     LRESULT(0)
 }
 
+/// Translates the message in the message loop unless `EventHandler::key_filter` consumes the key.
+pub(crate) unsafe fn translate_message<T: EventHandler + 'static>(msg: &MSG) {
+    let state = match msg.message {
+        WM_KEYDOWN | WM_SYSKEYDOWN => KeyState::Pressed,
+        WM_KEYUP | WM_SYSKEYUP => KeyState::Released,
+        _ => {
+            TranslateMessage(msg);
+            return;
+        }
+    };
+    // Key messages for windows that wita does not own such as dialogs are translated as usual.
+    let window = match Some(msg.hwnd)
+        .filter(|hwnd| is_wita_window(*hwnd))
+        .and_then(LocalWindow::get)
+    {
+        Some(window) => window,
+        None => {
+            call_handler(|_: &mut T, cs| cs.consumed_key = false);
+            TranslateMessage(msg);
+            return;
+        }
+    };
+    let mut consumed = false;
+    call_handler(|eh: &mut T, cs| {
+        consumed = eh.key_filter(&window.handle, key_code(msg.wParam, msg.lParam), state);
+        cs.consumed_key = consumed;
+    });
+    if !consumed {
+        TranslateMessage(msg);
+    }
+}
+
+/// Moves the focus to the next or the previous inner window of `parent`.
+///
+/// Returns `false` if `parent` has no inner windows that can take the focus.
+unsafe fn focus_child(parent: &Window, forward: bool) -> bool {
+    let children = parent
+        .state
+        .read()
+        .unwrap()
+        .children
+        .iter()
+        .filter_map(|child| child.upgrade())
+        .filter(|child| {
+            let hwnd = HWND(child.raw_handle() as _);
+            !child.is_closed()
+                && child.flags.style.load(Ordering::Relaxed) & WS_CHILD.0 != 0
+                && IsWindowVisible(hwnd).as_bool()
+                && IsWindowEnabled(hwnd).as_bool()
+        })
+        .collect::<Vec<_>>();
+    if children.is_empty() {
+        return false;
+    }
+    let focus = GetFocus();
+    let len = children.len();
+    let next = match children.iter().position(|child| {
+        let hwnd = HWND(child.raw_handle() as _);
+        hwnd == focus || IsChild(hwnd, focus).as_bool()
+    }) {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None if forward => 0,
+        None => len - 1,
+    };
+    SetFocus(HWND(children[next].raw_handle() as _));
+    true
+}
+
+/// Handles Tab and Shift+Tab in the message loop for `Window::set_tab_navigation`.
+///
+/// Returns `true` if the focus has been moved and the message must not be dispatched.
+pub(crate) unsafe fn navigate_focus(msg: &MSG) -> bool {
+    const VK_TAB: usize = 0x09;
+    const VK_SHIFT: i32 = 0x10;
+    if msg.message != WM_KEYDOWN || msg.wParam.0 != VK_TAB {
+        return false;
+    }
+    // The root can be a message box or a dialog on this thread.
+    let root = GetAncestor(msg.hwnd, GA_ROOT);
+    if !is_wita_window(root) {
+        return false;
+    }
+    let root = match LocalWindow::get(root) {
+        Some(root) => root,
+        None => return false,
+    };
+    if !root.handle.flags.tab_navigation.load(Ordering::Relaxed) {
+        return false;
+    }
+    focus_child(&root.handle, GetKeyState(VK_SHIFT) >= 0)
+}
+
+/// Handles `WM_SYSKEYDOWN` and `WM_SYSKEYUP`.
+///
+/// Only the keys consumed by `EventHandler::key_filter` are passed to `key_input`.
+fn sys_key_input<T: EventHandler + 'static>(
+    window: &Window,
+    hwnd: HWND,
+    msg: u32,
+    state: KeyState,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let mut consumed = false;
+    call_handler(|_: &mut T, cs| consumed = std::mem::take(&mut cs.consumed_key));
+    if consumed {
+        key_input::<T>(window, state, wparam, lparam)
+    } else {
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+    }
+}
+
 pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
     hwnd: HWND,
     msg: u32,
     wparam: WPARAM,
     lparam: LPARAM,
 ) -> LRESULT {
+    #[cfg(feature = "trace_messages")]
+    crate::trace::trace_message(hwnd, msg, wparam, lparam);
+    trace_span!("window_proc", msg);
     let ret = catch_unwind(|| unsafe {
-        let window = find_window(hwnd);
-        if window.is_none() {
+        if msg == WM_NCCREATE {
+            let cs = &*(lparam.0 as *const CREATESTRUCTW);
+            LocalWindow::attach(hwnd, cs.lpCreateParams);
+            enable_non_client_dpi_scaling(hwnd);
             return DefWindowProcW(hwnd, msg, wparam, lparam);
         }
-        let window = window.unwrap();
+        let window = match LocalWindow::get(hwnd) {
+            Some(window) => window,
+            None => return DefWindowProcW(hwnd, msg, wparam, lparam),
+        };
         let handle = &window.handle;
         match msg {
+            WM_ERASEBKGND => {
+                let background = handle.state.read().unwrap().background;
+                if let Background::SolidColor(r, g, b) = background {
+                    let mut rc = RECT::default();
+                    GetClientRect(hwnd, &mut rc);
+                    let brush = CreateSolidBrush(r as u32 | (g as u32) << 8 | (b as u32) << 16);
+                    FillRect(HDC(wparam.0 as _), &rc, brush);
+                    DeleteObject(brush);
+                }
+                LRESULT(1)
+            }
             WM_PAINT => {
                 let mut ps = PAINTSTRUCT::default();
                 BeginPaint(hwnd, &mut ps);
-                call_handler(|eh: &mut T, _| eh.draw(handle));
+                let rc = ps.rcPaint;
+                let invalid_rect = PhysicalRect::new(
+                    PhysicalPosition::new(rc.left, rc.top),
+                    PhysicalSize::new((rc.right - rc.left) as u32, (rc.bottom - rc.top) as u32),
+                );
+                call_handler(|eh: &mut T, _| eh.draw_rect(handle, invalid_rect));
                 EndPaint(hwnd, &ps);
                 LRESULT(0)
             }
             #[cfg(feature = "raw_input")]
             WM_INPUT => raw_input::wm_input::<T>(handle, hwnd, wparam, lparam),
+            WM_MOUSEMOVE if handle.is_relative_mouse_mode() => {
+                let center = client_center(hwnd);
+                let mut pos = POINT {
+                    x: get_x_lparam(lparam) as i32,
+                    y: get_y_lparam(lparam) as i32,
+                };
+                ClientToScreen(hwnd, &mut pos);
+                if pos.x != center.x || pos.y != center.y {
+                    // Raw input delivers the deltas without the acceleration if it reaches this window.
+                    #[cfg(feature = "raw_input")]
+                    let raw = raw_input::is_mouse_delivered_to(hwnd);
+                    #[cfg(not(feature = "raw_input"))]
+                    let raw = false;
+                    if !raw {
+                        call_handler(|eh: &mut T, _| {
+                            eh.mouse_delta(handle, pos.x - center.x, pos.y - center.y)
+                        });
+                    }
+                    SetCursorPos(center.x, center.y);
+                }
+                LRESULT(0)
+            }
             WM_MOUSEMOVE => {
                 call_handler(|eh: &mut T, state| {
                     let position = lparam_to_point(lparam);
@@ -177,10 +470,20 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                         );
                     }
                 });
-                let wnd = handle.state.read().unwrap();
-                wnd.cursor.set();
                 LRESULT(0)
             }
+            WM_SETCURSOR => {
+                // The cursor in the inner windows is set by themselves.
+                if wparam.0 as isize != hwnd.0 {
+                    return LRESULT(0);
+                }
+                if loword(lparam.0 as _) as u32 == HTCLIENT {
+                    handle.flags.cursor().set();
+                    LRESULT(1)
+                } else {
+                    DefWindowProcW(hwnd, msg, wparam, lparam)
+                }
+            }
             WM_MOUSELEAVE => {
                 call_handler(|eh: &mut T, state| {
                     state.entered_window = None;
@@ -191,7 +494,7 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                         handle,
                         MouseState {
                             position: PhysicalPosition::new(pos.x, pos.y),
-                            buttons: &mut state.mouse_buttons,
+                            buttons: &state.mouse_buttons,
                         },
                     );
                 });
@@ -251,6 +554,12 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
             ),
             WM_KEYDOWN => key_input::<T>(handle, KeyState::Pressed, wparam, lparam),
             WM_KEYUP => key_input::<T>(handle, KeyState::Released, wparam, lparam),
+            WM_SYSKEYDOWN => {
+                sys_key_input::<T>(handle, hwnd, msg, KeyState::Pressed, wparam, lparam)
+            }
+            WM_SYSKEYUP => {
+                sys_key_input::<T>(handle, hwnd, msg, KeyState::Released, wparam, lparam)
+            }
             WM_CHAR => {
                 call_handler(|eh: &mut T, _| {
                     if let Some(c) = std::char::from_u32(wparam.0 as u32) {
@@ -261,12 +570,11 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
             }
             WM_IME_SETCONTEXT => {
                 let lparam = {
-                    let state = handle.state.read().unwrap();
                     let mut lparam = lparam.0 as u32;
-                    if !state.visible_ime_composition_window {
+                    if !handle.flags.visible_ime_composition_window {
                         lparam &= !ISC_SHOWUICOMPOSITIONWINDOW;
                     }
-                    if !state.visible_ime_candidate_window {
+                    if !handle.flags.visible_ime_candidate_window {
                         lparam &= !ISC_SHOWUICANDIDATEWINDOW;
                         lparam &= !(ISC_SHOWUICANDIDATEWINDOW << 1);
                         lparam &= !(ISC_SHOWUICANDIDATEWINDOW << 2);
@@ -280,13 +588,13 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                 {
                     let imc = ime::Imc::get(hwnd);
                     let state = handle.state.read().unwrap();
-                    if state.visible_ime_composition_window {
+                    if handle.flags.visible_ime_composition_window {
                         imc.set_composition_window_position(state.ime_position);
                     }
-                    if state.visible_ime_candidate_window {
+                    if handle.flags.visible_ime_candidate_window {
                         imc.set_candidate_window_position(
                             state.ime_position,
-                            state.visible_ime_composition_window,
+                            handle.flags.visible_ime_composition_window,
                         );
                     }
                 }
@@ -325,11 +633,7 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                         }
                     }
                 });
-                let show_composition_window = {
-                    let state = handle.state.read().unwrap();
-                    state.visible_ime_composition_window
-                };
-                if show_composition_window {
+                if handle.flags.visible_ime_composition_window {
                     DefWindowProcW(hwnd, msg, wparam, lparam)
                 } else {
                     LRESULT(0)
@@ -351,15 +655,85 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
             WM_ACTIVATE => {
                 if ((wparam.0 as u32) & WA_ACTIVE) != 0 || ((wparam.0 as u32) & WA_CLICKACTIVE) != 0
                 {
+                    if handle.is_relative_mouse_mode() {
+                        confine_cursor(hwnd, true);
+                    }
+                    let focused_child = handle
+                        .state
+                        .read()
+                        .unwrap()
+                        .focused_child
+                        .as_ref()
+                        .and_then(|child| child.upgrade());
+                    if let Some(child) = focused_child {
+                        let child_hwnd = HWND(child.raw_handle() as _);
+                        if !child.is_closed()
+                            && IsWindowVisible(child_hwnd).as_bool()
+                            && IsWindowEnabled(child_hwnd).as_bool()
+                        {
+                            SetFocus(child_hwnd);
+                        }
+                    }
                     call_handler(|eh: &mut T, _| eh.activated(handle));
                 } else {
+                    if handle.is_relative_mouse_mode() {
+                        confine_cursor(hwnd, false);
+                    }
                     call_handler(|eh: &mut T, _| eh.inactivated(handle));
                 }
                 LRESULT(0)
             }
+            WM_SETFOCUS => {
+                if handle.flags.style.load(Ordering::Relaxed) & WS_CHILD.0 != 0 {
+                    if let Some(root) = LocalWindow::get(GetAncestor(hwnd, GA_ROOT)) {
+                        root.handle.state.write().unwrap().focused_child = Some(handle.downgrade());
+                    }
+                } else {
+                    handle.state.write().unwrap().focused_child = None;
+                }
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            WM_VSCROLL | WM_HSCROLL => {
+                let bar = if msg == WM_VSCROLL {
+                    ScrollBar::Vertical
+                } else {
+                    ScrollBar::Horizontal
+                };
+                if let Some((action, position)) = scroll::apply(hwnd, bar, wparam) {
+                    call_handler(|eh: &mut T, _| eh.scrolled(handle, bar, action, position));
+                }
+                LRESULT(0)
+            }
             WM_SIZE => {
                 let value = lparam.0 as u32;
                 let size = PhysicalSize::new(loword(value as _) as u32, hiword(value as _) as u32);
+                if wparam.0 == SIZE_MINIMIZED
+                    && handle.flags.minimize_to_tray.load(Ordering::Relaxed)
+                {
+                    let mut tray_icon = window.tray_icon.borrow_mut();
+                    if tray_icon.is_none() {
+                        *tray_icon = TrayIcon::new(hwnd, &handle.title());
+                    }
+                    ShowWindow(hwnd, SW_HIDE);
+                }
+                update_visibility::<T>(&window, hwnd, IsWindowVisible(hwnd).as_bool());
+                if handle.is_relative_mouse_mode() && GetActiveWindow() == hwnd {
+                    confine_cursor(hwnd, true);
+                }
+                #[cfg(feature = "d2d")]
+                window.d2d_target.resize(size);
+                if wparam.0 != SIZE_MINIMIZED {
+                    let mut redraw = false;
+                    call_handler(|eh: &mut T, _| redraw = eh.resize_swap_chain(handle, size));
+                    if redraw {
+                        RedrawWindow(
+                            hwnd,
+                            std::ptr::null(),
+                            HRGN::NULL,
+                            REDRAW_WINDOW_FLAGS(RDW_INVALIDATE.0 | RDW_UPDATENOW.0),
+                        );
+                    }
+                }
                 call_handler(|eh: &mut T, state| {
                     if state.resizing {
                         eh.resizing(handle, size);
@@ -369,6 +743,15 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                 });
                 LRESULT(0)
             }
+            WM_SHOWWINDOW => {
+                // The window is not visible yet when it is being shown.
+                update_visibility::<T>(&window, hwnd, wparam.0 != 0);
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            dwm::WM_DWMCOMPOSITIONCHANGED => {
+                update_visibility::<T>(&window, hwnd, IsWindowVisible(hwnd).as_bool());
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
             WM_WINDOWPOSCHANGED => {
                 let pos = &*(lparam.0 as *const WINDOWPOS);
                 if pos.flags.0 & SWP_NOMOVE.0 == 0 {
@@ -380,12 +763,74 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
             }
             WM_ENTERSIZEMOVE => {
                 set_resizing(true);
+                if run_type() == RunType::Idle {
+                    // The event loop stalls in the modal loop of DefWindowProcW,
+                    // so the timer keeps calling the Idle callbacks.
+                    SetTimer(hwnd, SIZE_MOVE_TIMER_ID, USER_TIMER_MINIMUM, None);
+                }
+                let size = handle.inner_size();
+                call_handler(|_: &mut T, state| {
+                    state.moving = false;
+                    state.size_move_start = Some(size);
+                });
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            WM_MOVING => {
+                call_handler(|eh: &mut T, state| {
+                    if state.resizing && !state.moving {
+                        state.moving = true;
+                        eh.move_started(handle);
+                    }
+                });
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            WM_TIMER if wparam.0 == SIZE_MOVE_TIMER_ID => {
+                call_handler(|eh: &mut T, _| {
+                    eh.pre_processing();
+                    eh.idle();
+                    eh.post_processing();
+                });
+                LRESULT(0)
+            }
+            WM_TRAY_NOTIFY => {
+                if matches!(lparam.0 as u32, WM_LBUTTONUP | WM_LBUTTONDBLCLK) {
+                    window.tray_icon.borrow_mut().take();
+                    ShowWindow(hwnd, SW_RESTORE);
+                    SetForegroundWindow(hwnd);
+                }
+                LRESULT(0)
+            }
+            WM_ENTERMENULOOP => {
+                let popup = wparam.0 != 0;
+                call_handler(|eh: &mut T, _| eh.menu_loop_entered(handle, popup));
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            WM_EXITMENULOOP => {
+                let popup = wparam.0 != 0;
+                call_handler(|eh: &mut T, _| eh.menu_loop_exited(handle, popup));
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            WM_ENTERIDLE => {
+                let kind = if wparam.0 == MSGF_MENU {
+                    ModalLoop::Menu
+                } else {
+                    ModalLoop::Dialog
+                };
+                call_handler(|eh: &mut T, _| eh.modal_loop_idle(handle, kind));
                 DefWindowProcW(hwnd, msg, wparam, lparam)
             }
             WM_EXITSIZEMOVE => {
                 set_resizing(false);
+                KillTimer(hwnd, SIZE_MOVE_TIMER_ID);
                 let size = handle.inner_size();
-                call_handler(|eh: &mut T, _| eh.resized(handle, size));
+                call_handler(|eh: &mut T, state| {
+                    if state.size_move_start.take() != Some(size) {
+                        eh.resized(handle, size);
+                    }
+                    if std::mem::replace(&mut state.moving, false) {
+                        eh.move_ended(handle);
+                    }
+                });
                 DefWindowProcW(hwnd, msg, wparam, lparam)
             }
             WM_DPICHANGED => {
@@ -399,11 +844,18 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                     rc.bottom - rc.top,
                     SWP_NOZORDER | SWP_NOACTIVATE,
                 );
-                call_handler(|eh: &mut T, _| eh.dpi_changed(handle));
+                let dpi = loword(wparam.0 as _) as u32;
+                let size = handle.inner_size();
+                #[cfg(feature = "d2d")]
+                window.d2d_target.set_dpi(dpi);
+                call_handler(|eh: &mut T, _| {
+                    eh.dpi_changed(handle);
+                    eh.dpi_changed_with_size(handle, dpi, size);
+                });
                 LRESULT(0)
             }
             WM_GETDPISCALEDSIZE => {
-                let prev_dpi = GetDpiForWindow(hwnd) as i32;
+                let prev_dpi = get_dpi_for_window(hwnd) as i32;
                 let next_dpi = wparam.0 as i32;
                 let mut rc = RECT::default();
                 GetClientRect(hwnd, &mut rc);
@@ -413,7 +865,7 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                 );
                 let rc = adjust_window_rect(
                     size,
-                    GetWindowLongPtrW(hwnd, GWL_STYLE) as _,
+                    frame_style(handle, GetWindowLongPtrW(hwnd, GWL_STYLE) as _),
                     GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as _,
                     next_dpi as u32,
                 );
@@ -425,62 +877,225 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
             WM_DROPFILES => {
                 let hdrop = HDROP(wparam.0 as _);
                 let file_count = DragQueryFileW(hdrop, std::u32::MAX, PWSTR::NULL, 0);
-                let mut buffer = Vec::new();
-                let files = (0..file_count)
-                    .map(|i| {
-                        let len = DragQueryFileW(hdrop, i, PWSTR::NULL, 0) as usize + 1;
-                        buffer.resize(len, 0);
-                        DragQueryFileW(hdrop, i, PWSTR(buffer.as_mut_ptr()), len as u32);
-                        buffer.pop();
-                        PathBuf::from(String::from_utf16_lossy(&buffer))
-                    })
-                    .collect::<Vec<_>>();
-                let files_ref = files.iter().map(|pb| pb.as_path()).collect::<Vec<_>>();
+                let mut buffers = take_buffers();
+                buffers.dropped_items.clear();
+                for i in 0..file_count as usize {
+                    let len = DragQueryFileW(hdrop, i as u32, PWSTR::NULL, 0) as usize + 1;
+                    buffers.wide.resize(len, 0);
+                    DragQueryFileW(
+                        hdrop,
+                        i as u32,
+                        PWSTR(buffers.wide.as_mut_ptr()),
+                        len as u32,
+                    );
+                    buffers.dropped_items.push(DroppedItem {
+                        path: PathBuf::from(OsString::from_wide(&buffers.wide[..len - 1])),
+                    });
+                }
                 let mut pt = POINT::default();
                 DragQueryPoint(hdrop, &mut pt);
                 call_handler(|eh: &mut T, _| {
                     eh.drop_files(
                         handle,
-                        &files_ref,
-                        PhysicalPosition::new(pt.x as f32, pt.y as f32),
+                        &buffers.dropped_items,
+                        PhysicalPosition::new(pt.x, pt.y),
                     );
                 });
+                restore_buffers(buffers);
                 DragFinish(hdrop);
                 LRESULT(0)
             }
+            WM_NCHITTEST => match handle.flags.hit_test() {
+                Some(f) => {
+                    let mut pt = POINT {
+                        x: get_x_lparam(lparam) as i32,
+                        y: get_y_lparam(lparam) as i32,
+                    };
+                    ScreenToClient(hwnd, &mut pt);
+                    LRESULT(f(PhysicalPosition::new(pt.x, pt.y)).value() as _)
+                }
+                None => DefWindowProcW(hwnd, msg, wparam, lparam),
+            },
+            WM_NCCALCSIZE if wparam.0 != 0 && is_aero_snap_borderless(handle) => {
+                if IsZoomed(hwnd).as_bool() {
+                    // A maximized window is extended over the monitor by the frame size.
+                    let params = &mut *(lparam.0 as *mut NCCALCSIZE_PARAMS);
+                    let dpi = get_dpi_for_window(hwnd);
+                    let padding = get_system_metrics_for_dpi(SM_CXPADDEDBORDER, dpi);
+                    let frame_x = get_system_metrics_for_dpi(SM_CXFRAME, dpi) + padding;
+                    let frame_y = get_system_metrics_for_dpi(SM_CYFRAME, dpi) + padding;
+                    let rc = &mut params.rgrc[0];
+                    rc.left += frame_x;
+                    rc.top += frame_y;
+                    rc.right -= frame_x;
+                    rc.bottom -= frame_y;
+                }
+                LRESULT(0)
+            }
+            WM_NCLBUTTONDOWN | WM_NCLBUTTONUP if is_custom_caption_button(handle, wparam) => {
+                if msg == WM_NCLBUTTONUP {
+                    let command = match wparam.0 as u32 {
+                        HTMINBUTTON => SC_MINIMIZE,
+                        HTMAXBUTTON if IsZoomed(hwnd).as_bool() => SC_RESTORE,
+                        HTMAXBUTTON => SC_MAXIMIZE,
+                        _ => SC_CLOSE,
+                    };
+                    PostMessageW(hwnd, WM_SYSCOMMAND, WPARAM(command as _), LPARAM(0));
+                }
+                LRESULT(0)
+            }
+            WM_COPYDATA => {
+                let cds = &*(lparam.0 as *const COPYDATASTRUCT);
+                let data: &[u8] = if cds.lpData.is_null() {
+                    &[]
+                } else {
+                    std::slice::from_raw_parts(cds.lpData as *const u8, cds.cbData as usize)
+                };
+                let mut ret = false;
+                call_handler(|eh: &mut T, _| ret = eh.copy_data(handle, cds.dwData, data));
+                LRESULT(ret as _)
+            }
+            WM_QUERYENDSESSION => {
+                let reason = SessionEndReason::from_lparam(lparam.0 as u32);
+                let mut response = SessionEndResponse::Allow;
+                call_handler(|eh: &mut T, _| response = eh.session_ending(handle, reason));
+                match response {
+                    SessionEndResponse::Allow => LRESULT(1),
+                    SessionEndResponse::Block(text) => {
+                        session::block_shutdown(hwnd, &text);
+                        LRESULT(0)
+                    }
+                }
+            }
+            WM_ENDSESSION => {
+                let ending = wparam.0 != 0;
+                if !ending {
+                    session::unblock_shutdown(hwnd);
+                }
+                call_handler(|eh: &mut T, _| eh.session_ended(handle, ending));
+                LRESULT(0)
+            }
+            device_notify::WM_DEVICECHANGE => {
+                for kind in device_notify::decode(wparam, lparam) {
+                    call_handler(|eh: &mut T, _| eh.device_changed(handle, kind));
+                }
+                LRESULT(1)
+            }
+            WM_POWERBROADCAST => {
+                match wparam.0 as u32 {
+                    power::PBT_APMSUSPEND => call_handler(|eh: &mut T, _| eh.suspending(handle)),
+                    power::PBT_APMRESUMEAUTOMATIC => {
+                        call_handler(|eh: &mut T, _| eh.resumed(handle))
+                    }
+                    power::PBT_APMPOWERSTATUSCHANGE => {
+                        if let Some(status) = power::power_status() {
+                            call_handler(|eh: &mut T, _| eh.power_status_changed(handle, status));
+                        }
+                    }
+                    _ => (),
+                }
+                LRESULT(1)
+            }
+            dwm::WM_DWMSENDICONICTHUMBNAIL => {
+                let max_size = PhysicalSize::new(
+                    hiword(lparam.0 as _) as u16 as u32,
+                    loword(lparam.0 as _) as u16 as u32,
+                );
+                let mut bitmap = None;
+                call_handler(|eh: &mut T, _| bitmap = eh.iconic_thumbnail(handle, max_size));
+                if let Some(bitmap) = bitmap {
+                    dwm::set_iconic_thumbnail(hwnd, &bitmap);
+                }
+                LRESULT(0)
+            }
+            dwm::WM_DWMSENDICONICLIVEPREVIEWBITMAP => {
+                let mut bitmap = None;
+                call_handler(|eh: &mut T, _| bitmap = eh.iconic_live_preview(handle));
+                if let Some(bitmap) = bitmap {
+                    dwm::set_iconic_live_preview(hwnd, &bitmap);
+                }
+                LRESULT(0)
+            }
+            WM_SYSCOLORCHANGE => {
+                call_handler(|eh: &mut T, _| {
+                    eh.system_settings_changed(handle, SettingKind::Colors)
+                });
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            WM_SETTINGCHANGE => {
+                let area = lparam_to_string(lparam);
+                let kind = settings::setting_kind(wparam.0 as u32, area.as_deref());
+                call_handler(|eh: &mut T, _| eh.system_settings_changed(handle, kind));
+                if kind == SettingKind::Locale {
+                    let locale = user_locale();
+                    call_handler(|eh: &mut T, _| eh.locale_changed(handle, &locale));
+                }
+                if area.as_deref() == Some("ImmersiveColorSet") {
+                    let theme = system_theme();
+                    call_handler(|eh: &mut T, _| eh.theme_changed(handle, theme));
+                }
+                if wparam.0 as u32 == SPI_SETHIGHCONTRAST.0 {
+                    let scheme = high_contrast();
+                    call_handler(|eh: &mut T, _| eh.high_contrast_changed(handle, scheme));
+                }
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
             #[cfg(feature = "raw_input")]
             WM_INPUT_DEVICE_CHANGE => {
                 raw_input::wm_input_device_change::<T>(handle, hwnd, wparam, lparam)
             }
+            #[cfg(feature = "accessibility")]
+            WM_GETOBJECT => {
+                let tree = handle.state.read().unwrap().accessible_tree.clone();
+                accessibility::get_object(hwnd, wparam, lparam, &tree)
+                    .unwrap_or_else(|| DefWindowProcW(hwnd, msg, wparam, lparam))
+            }
             WM_DESTROY => {
+                #[cfg(feature = "raw_input")]
+                raw_input::unregister_window(hwnd);
+                #[cfg(feature = "block_shortcuts")]
+                shortcut_blocker::set_blocked(hwnd, false);
+                // The children are destroyed before the parent so that their `closed` are called first.
+                let children = std::mem::take(&mut handle.state.write().unwrap().children);
+                for child in children.iter().filter_map(|child| child.upgrade()) {
+                    if !child.is_closed() {
+                        DestroyWindow(HWND(child.raw_handle() as _));
+                    }
+                }
+                handle.flags.closed.store(true, Ordering::Release);
+                if handle
+                    .flags
+                    .relative_mouse_mode
+                    .swap(false, Ordering::Relaxed)
                 {
-                    let mut state = handle.state.write().unwrap();
-                    state.closed = true;
+                    confine_cursor(hwnd, false);
+                    ShowCursor(true);
                 }
-                call_handler(|eh: &mut T, _| {
-                    eh.closed(handle);
-                    {
-                        let state = handle.state.read().unwrap();
-                        for child in state.children.iter() {
-                            child.close();
-                        }
-                    }
-                });
+                call_handler(|eh: &mut T, _| eh.closed(handle));
                 remove_window(hwnd);
                 if window_table_is_empty() {
                     PostQuitMessage(0);
                 }
                 LRESULT(0)
             }
-            WM_NCCREATE => {
-                EnableNonClientDpiScaling(hwnd);
+            WM_NCDESTROY => {
+                call_handler(|eh: &mut T, _| eh.destroyed(handle));
+                LocalWindow::detach(hwnd);
                 DefWindowProcW(hwnd, msg, wparam, lparam)
             }
             WM_USER => {
                 match wparam.0 {
                     w if w == UserMessage::SetTitle as usize => {
-                        let state = handle.state.read().unwrap();
-                        SetWindowTextW(hwnd, state.title.as_str());
+                        let mut buffers = take_buffers();
+                        buffers.wide.clear();
+                        {
+                            let state = handle.state.read().unwrap();
+                            buffers
+                                .wide
+                                .extend(state.title.encode_utf16().chain(Some(0)));
+                        }
+                        SetWindowTextW(hwnd, PWSTR(buffers.wide.as_mut_ptr()));
+                        restore_buffers(buffers);
                     }
                     w if w == UserMessage::SetPosition as usize => {
                         let state = handle.state.read().unwrap();
@@ -498,9 +1113,9 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                         let state = handle.state.read().unwrap();
                         let rc = adjust_window_rect(
                             state.set_inner_size,
-                            GetWindowLongPtrW(hwnd, GWL_STYLE) as _,
+                            frame_style(handle, GetWindowLongPtrW(hwnd, GWL_STYLE) as _),
                             GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as _,
-                            GetDpiForWindow(hwnd),
+                            get_dpi_for_window(hwnd),
                         );
                         SetWindowPos(
                             hwnd,
@@ -519,15 +1134,12 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                         window.ime_context.borrow().disable();
                     }
                     w if w == UserMessage::SetStyle as usize => {
-                        let style = {
-                            let state = handle.state.read().unwrap();
-                            state.style
-                        };
+                        let style = handle.flags.style.load(Ordering::Relaxed);
                         let rc = adjust_window_rect(
                             handle.inner_size().to_physical(handle.dpi()),
-                            style,
+                            frame_style(handle, style),
                             0,
-                            GetDpiForWindow(hwnd),
+                            get_dpi_for_window(hwnd),
                         );
                         SetWindowLongPtrW(hwnd, GWL_STYLE, style as _);
                         SetWindowPos(
@@ -541,20 +1153,97 @@ pub(crate) extern "system" fn window_proc<T: EventHandler + 'static>(
                         );
                         ShowWindow(hwnd, SW_SHOW);
                     }
+                    w if w == UserMessage::SetStyleBits as usize => {
+                        // Only the requested bits are patched to keep the others such as `WS_MAXIMIZE` and `WS_VISIBLE`.
+                        let bits = lparam.0 as u32;
+                        let style = GetWindowLongPtrW(hwnd, GWL_STYLE) as u32;
+                        let style =
+                            (style & !bits) | (handle.flags.style.load(Ordering::Relaxed) & bits);
+                        SetWindowLongPtrW(hwnd, GWL_STYLE, style as _);
+                        SetWindowPos(
+                            hwnd,
+                            HWND(0),
+                            0,
+                            0,
+                            0,
+                            0,
+                            SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER,
+                        );
+                    }
                     w if w == UserMessage::AcceptDragFiles as usize => {
                         DragAcceptFiles(hwnd, BOOL(lparam.0 as _));
                     }
+                    w if w == UserMessage::UpdateVisibility as usize => {
+                        update_visibility::<T>(&window, hwnd, IsWindowVisible(hwnd).as_bool());
+                    }
+                    w if w == UserMessage::SetRelativeMouseMode as usize => {
+                        // This is posted only when the flag has been changed.
+                        let enable = lparam.0 != 0;
+                        ShowCursor(!enable);
+                        confine_cursor(hwnd, enable);
+                    }
+                    w if w == UserMessage::RestoreSession as usize => {
+                        let session = handle.state.write().unwrap().session.take();
+                        if let Some(session) = session {
+                            config::apply_session(hwnd, &session);
+                        }
+                    }
+                    w if w == UserMessage::Destroy as usize => {
+                        DestroyWindow(hwnd);
+                    }
+                    w if w == UserMessage::FocusChild as usize => {
+                        focus_child(handle, lparam.0 != 0);
+                    }
+                    #[cfg(feature = "block_shortcuts")]
+                    w if w == UserMessage::BlockShortcuts as usize => {
+                        shortcut_blocker::set_blocked(hwnd, lparam.0 != 0);
+                    }
+                    w if w == UserMessage::FirstDraw as usize => {
+                        call_handler(|eh: &mut T, _| eh.draw(handle));
+                        if handle
+                            .flags
+                            .show_after_first_draw
+                            .swap(false, Ordering::Relaxed)
+                        {
+                            ShowWindow(hwnd, SW_SHOW);
+                        }
+                    }
                     _ => {
                         return call_other::<T>(hwnd, msg, wparam, lparam);
                     }
                 }
                 LRESULT(0)
             }
+            _ if msg == tray::taskbar_created() => {
+                if let Some(tray_icon) = window.tray_icon.borrow().as_ref() {
+                    tray_icon.restore(&handle.title());
+                }
+                call_other::<T>(hwnd, msg, wparam, lparam)
+            }
+            _ if MessageId::is_registered(msg) => {
+                let mut ret = None;
+                call_handler(|eh: &mut T, _| {
+                    ret = eh.custom_message(handle, MessageId(msg), wparam.0, lparam.0);
+                });
+                match ret {
+                    Some(ret) => LRESULT(ret),
+                    None => call_other::<T>(hwnd, msg, wparam, lparam),
+                }
+            }
             _ => call_other::<T>(hwnd, msg, wparam, lparam),
         }
     });
     ret.unwrap_or_else(|e| {
-        set_unwind(e);
+        if panic_policy() == PanicPolicy::Notify {
+            let notified = catch_unwind(AssertUnwindSafe(|| {
+                call_handler(|eh: &mut T, _| eh.handler_panicked(e))
+            }));
+            if let Err(e) = notified {
+                set_unwind(e);
+            }
+        } else {
+            set_unwind(e);
+        }
         LRESULT(0)
     })
 }
@@ -2,20 +2,24 @@
 //!
 //! To use, specify `"raw_input"` feature.
 
+use crate::api::load_proc;
 use crate::bindings::Windows::Win32::{
     Devices::HumanInterfaceDevice::*, Foundation::*, Storage::FileSystem::*,
-    UI::KeyboardAndMouseInput::*, UI::WindowsAndMessaging::*,
+    System::LibraryLoader::LoadLibraryW, UI::KeyboardAndMouseInput::*, UI::WindowsAndMessaging::*,
 };
 use crate::context::call_handler;
 use crate::device::*;
+use crate::error::ApiError;
 use crate::last_error;
 use crate::EventHandler;
 use crate::Window;
 use log::debug;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::mem::size_of;
 use std::ptr::null_mut;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex, Once};
 
 const FACILITY_HID_ERROR_CODE: u32 = 0x11;
 
@@ -25,6 +29,9 @@ const fn hidp_error_codes(sev: u32, code: u32) -> NTSTATUS {
 
 const HIDP_STATUS_SUCCESS: NTSTATUS = hidp_error_codes(0x0, 0);
 
+const GENERIC_READ: u32 = 0x80000000;
+const GENERIC_WRITE: u32 = 0x40000000;
+
 const HID_USAGE_PAGE_GENERIC: u16 = 0x01;
 
 const HID_USAGE_GENERIC_MOUSE: u16 = 0x02;
@@ -32,6 +39,16 @@ const HID_USAGE_GENERIC_JOYSTICK: u16 = 0x04;
 const HID_USAGE_GENERIC_GAMEPAD: u16 = 0x05;
 const HID_USAGE_GENERIC_KEYBOARD: u16 = 0x06;
 
+const HID_USAGE_PAGE_GENERIC_DEVICE: u16 = 0x06;
+const HID_USAGE_BATTERY_STRENGTH: u16 = 0x20;
+
+const BATTERY_DEVTYPE_GAMEPAD: u8 = 0x00;
+const BATTERY_TYPE_DISCONNECTED: u8 = 0x00;
+const BATTERY_TYPE_WIRED: u8 = 0x01;
+const BATTERY_LEVEL_EMPTY: u8 = 0x00;
+const BATTERY_LEVEL_LOW: u8 = 0x01;
+const BATTERY_LEVEL_MEDIUM: u8 = 0x02;
+
 /// An input data value.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum Value {
@@ -155,12 +172,33 @@ pub enum DeviceType {
     GamePad,
 }
 
+/// Describes a coarse battery level.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BatteryLevel {
+    Empty,
+    Low,
+    Medium,
+    Full,
+}
+
+/// Describes the battery state of a device.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Battery {
+    /// The device is powered by the cable.
+    Wired,
+    /// The remaining capacity in percent.
+    Percent(u8),
+    /// The device reports only a coarse level.
+    Level(BatteryLevel),
+}
+
 /// A handle that represents a device.
 #[derive(Clone, Debug)]
 pub struct Device {
     handle: HANDLE,
     ty: DeviceType,
-    name: Option<String>,
+    // Shared to make a clone per input cheap.
+    name: Option<Arc<str>>,
 }
 
 impl Device {
@@ -175,6 +213,139 @@ impl Device {
     pub fn raw_handle(&self) -> HANDLE {
         self.handle
     }
+
+    /// Opens the HID interface of the device for reading and writing reports.
+    ///
+    /// Keyboards and mice cannot be opened because the system opens them exclusively.
+    pub fn open(&self) -> Result<HidDevice, ApiError> {
+        unsafe {
+            let interface = get_device_interface(self.handle)
+                .ok_or_else(|| ApiError::new().with_function("GetRawInputDeviceInfoW"))?;
+            let mut preparsed = vec![];
+            get_preparsed_data(self.handle, &mut preparsed)
+                .ok_or_else(|| ApiError::new().with_function("GetRawInputDeviceInfoW"))?;
+            let mut caps = HIDP_CAPS::default();
+            let ret = HidP_GetCaps(preparsed.as_mut_ptr() as _, &mut caps);
+            if ret != HIDP_STATUS_SUCCESS {
+                return Err(ApiError::from_hresult(windows::HRESULT(ret.0 as _))
+                    .with_function("HidP_GetCaps"));
+            }
+            let handle = CreateFileW(
+                PWSTR(interface.as_ptr() as _),
+                FILE_ACCESS_FLAGS(GENERIC_READ | GENERIC_WRITE),
+                FILE_SHARE_MODE(FILE_SHARE_READ.0 | FILE_SHARE_WRITE.0),
+                null_mut(),
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                HANDLE::NULL,
+            );
+            if handle.0 == -1 {
+                return Err(ApiError::new().with_function("CreateFileW"));
+            }
+            Ok(HidDevice {
+                handle,
+                input_report_len: caps.InputReportByteLength as _,
+                output_report_len: caps.OutputReportByteLength as _,
+                feature_report_len: caps.FeatureReportByteLength as _,
+            })
+        }
+    }
+
+    /// Returns the battery state from the battery strength usage of the HID input report.
+    ///
+    /// `None` if the device does not report it. XInput controllers do not report it via HID,
+    /// so use `xinput_battery` for them.
+    pub fn battery(&self) -> Option<Battery> {
+        unsafe {
+            let mut preparsed = vec![];
+            get_preparsed_data(self.handle, &mut preparsed)?;
+            let p = preparsed.as_mut_ptr() as _;
+            let mut caps = HIDP_VALUE_CAPS::default();
+            let mut len = 1;
+            let ret = HidP_GetSpecificValueCaps(
+                HidP_Input,
+                HID_USAGE_PAGE_GENERIC_DEVICE,
+                0,
+                HID_USAGE_BATTERY_STRENGTH,
+                &mut caps,
+                &mut len,
+                p,
+            );
+            if ret != HIDP_STATUS_SUCCESS || len == 0 {
+                return None;
+            }
+            let device = self.open().ok()?;
+            let mut report = vec![0u8; device.input_report_len()];
+            report[0] = caps.ReportID;
+            device.get_input_report(&mut report).ok()?;
+            let mut value = 0;
+            let ret = HidP_GetUsageValue(
+                HidP_Input,
+                HID_USAGE_PAGE_GENERIC_DEVICE,
+                0,
+                HID_USAGE_BATTERY_STRENGTH,
+                &mut value,
+                p,
+                PSTR(report.as_mut_ptr()),
+                report.len() as _,
+            );
+            if ret != HIDP_STATUS_SUCCESS {
+                return None;
+            }
+            let range = caps.LogicalMax as i64 - caps.LogicalMin as i64;
+            if range <= 0 {
+                return None;
+            }
+            let percent = (value as i64 - caps.LogicalMin as i64) * 100 / range;
+            Some(Battery::Percent(percent.clamp(0, 100) as u8))
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct XInputBatteryInformation {
+    battery_type: u8,
+    battery_level: u8,
+}
+
+type XInputGetBatteryInformationFn =
+    unsafe extern "system" fn(u32, u8, *mut XInputBatteryInformation) -> u32;
+
+fn xinput_get_battery_information_fn() -> Option<XInputGetBatteryInformationFn> {
+    static LOAD: Once = Once::new();
+    static mut FUNCTION: Option<XInputGetBatteryInformationFn> = None;
+    unsafe {
+        LOAD.call_once(|| {
+            FUNCTION = load_proc(LoadLibraryW("xinput1_4.dll"), "XInputGetBatteryInformation");
+        });
+        FUNCTION
+    }
+}
+
+/// Returns the battery state of the XInput controller.
+///
+/// `user_index` is from `0` to `3`. `None` if the controller is not connected.
+/// XInput 1.4 is available from Windows 8, so `None` is also returned if it cannot be loaded.
+pub fn xinput_battery(user_index: u32) -> Option<Battery> {
+    let f = xinput_get_battery_information_fn()?;
+    unsafe {
+        let mut info = XInputBatteryInformation::default();
+        let ret = f(user_index, BATTERY_DEVTYPE_GAMEPAD, &mut info);
+        if ret != 0 {
+            return None;
+        }
+        match info.battery_type {
+            BATTERY_TYPE_DISCONNECTED => None,
+            BATTERY_TYPE_WIRED => Some(Battery::Wired),
+            _ => Some(Battery::Level(match info.battery_level {
+                BATTERY_LEVEL_EMPTY => BatteryLevel::Empty,
+                BATTERY_LEVEL_LOW => BatteryLevel::Low,
+                BATTERY_LEVEL_MEDIUM => BatteryLevel::Medium,
+                _ => BatteryLevel::Full,
+            })),
+        }
+    }
 }
 
 impl std::fmt::Display for Device {
@@ -191,9 +362,134 @@ impl PartialEq for Device {
 
 impl Eq for Device {}
 
+/// An opened HID interface.
+///
+/// The first byte of each report is the report ID, which is `0` if the device does not use report IDs.
+#[derive(Debug)]
+pub struct HidDevice {
+    handle: HANDLE,
+    input_report_len: usize,
+    output_report_len: usize,
+    feature_report_len: usize,
+}
+
+impl HidDevice {
+    /// Returns the length of input reports including the report ID.
+    pub fn input_report_len(&self) -> usize {
+        self.input_report_len
+    }
+
+    /// Returns the length of output reports including the report ID.
+    pub fn output_report_len(&self) -> usize {
+        self.output_report_len
+    }
+
+    /// Returns the length of feature reports including the report ID.
+    pub fn feature_report_len(&self) -> usize {
+        self.feature_report_len
+    }
+
+    /// Waits for the next input report and returns the length that has been read.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize, ApiError> {
+        unsafe {
+            let mut len = 0;
+            if !ReadFile(
+                self.handle,
+                buffer.as_mut_ptr() as _,
+                buffer.len() as _,
+                &mut len,
+                null_mut(),
+            )
+            .as_bool()
+            {
+                return Err(ApiError::new().with_function("ReadFile"));
+            }
+            Ok(len as _)
+        }
+    }
+
+    /// Writes an output report to the interrupt pipe and returns the length that has been written.
+    pub fn write(&self, report: &[u8]) -> Result<usize, ApiError> {
+        unsafe {
+            let mut len = 0;
+            if !WriteFile(
+                self.handle,
+                report.as_ptr() as _,
+                report.len() as _,
+                &mut len,
+                null_mut(),
+            )
+            .as_bool()
+            {
+                return Err(ApiError::new().with_function("WriteFile"));
+            }
+            Ok(len as _)
+        }
+    }
+
+    /// Gets an input report with the control pipe.
+    ///
+    /// The report ID must be set to `buffer[0]`.
+    pub fn get_input_report(&self, buffer: &mut [u8]) -> Result<(), ApiError> {
+        unsafe {
+            if HidD_GetInputReport(self.handle, buffer.as_mut_ptr() as _, buffer.len() as _) == 0 {
+                return Err(ApiError::new().with_function("HidD_GetInputReport"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Sends an output report with the control pipe.
+    pub fn set_output_report(&self, report: &[u8]) -> Result<(), ApiError> {
+        unsafe {
+            if HidD_SetOutputReport(self.handle, report.as_ptr() as _, report.len() as _) == 0 {
+                return Err(ApiError::new().with_function("HidD_SetOutputReport"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Gets a feature report.
+    ///
+    /// The report ID must be set to `buffer[0]`.
+    pub fn get_feature_report(&self, buffer: &mut [u8]) -> Result<(), ApiError> {
+        unsafe {
+            if HidD_GetFeature(self.handle, buffer.as_mut_ptr() as _, buffer.len() as _) == 0 {
+                return Err(ApiError::new().with_function("HidD_GetFeature"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Sends a feature report.
+    pub fn set_feature_report(&self, report: &[u8]) -> Result<(), ApiError> {
+        unsafe {
+            if HidD_SetFeature(self.handle, report.as_ptr() as _, report.len() as _) == 0 {
+                return Err(ApiError::new().with_function("HidD_SetFeature"));
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Drop for HidDevice {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.handle);
+        }
+    }
+}
+
 /// Keyboard information
 #[derive(Debug)]
 pub struct KeyboardInfo {
+    /// The type of the keyboard such as `4` for the enhanced 101/102-key keyboard,
+    /// `7` for the Japanese keyboard and `0x51` for HID keyboards that are not identified.
+    pub keyboard_type: u32,
+    /// The vendor specific subtype.
+    pub keyboard_subtype: u32,
+    /// The scan code set that the keyboard sends.
+    pub scan_code_mode: u32,
     pub function_num: u32,
     pub indicator_num: u32,
     pub keys_total: u32,
@@ -203,8 +499,22 @@ pub struct KeyboardInfo {
 #[derive(Debug)]
 pub struct MouseInfo {
     pub button_num: u32,
+    /// The number of data points per second that the driver reports.
+    ///
+    /// This is `0` for most USB mice. Use `sample_rate_hz` to distinguish unknown rates.
     pub sample_rate: u32,
     pub has_hwheel: bool,
+    /// The hardware resolution in counts per inch.
+    ///
+    /// `None` if the HID report descriptor of the mouse has no physical extents of the X axis.
+    pub cpi: Option<u32>,
+}
+
+impl MouseInfo {
+    /// Returns the sample rate if the driver reports it.
+    pub fn sample_rate_hz(&self) -> Option<u32> {
+        (self.sample_rate != 0).then(|| self.sample_rate)
+    }
 }
 
 /// Game pad information
@@ -228,6 +538,62 @@ pub enum DeviceInfo {
     GamePad(GamePadInfo),
 }
 
+/// Computes the resolution from the physical extents of the X axis in the HID report descriptor.
+///
+/// Mice have no preparsed data via raw input, so the HID interface is opened without access rights.
+unsafe fn get_mouse_cpi(handle: HANDLE) -> Option<u32> {
+    const HID_USAGE_GENERIC_X: u16 = 0x30;
+    const UNIT_CENTIMETER: u32 = 0x11;
+    const UNIT_INCH: u32 = 0x13;
+    let interface = get_device_interface(handle)?;
+    let file = CreateFileW(
+        PWSTR(interface.as_ptr() as _),
+        FILE_ACCESS_FLAGS(0),
+        FILE_SHARE_MODE(FILE_SHARE_READ.0 | FILE_SHARE_WRITE.0),
+        null_mut(),
+        OPEN_EXISTING,
+        FILE_FLAGS_AND_ATTRIBUTES(0),
+        HANDLE::NULL,
+    );
+    if file.0 == -1 {
+        return None;
+    }
+    let mut preparsed = 0;
+    let ret = HidD_GetPreparsedData(file, &mut preparsed);
+    CloseHandle(file);
+    if ret == 0 {
+        return None;
+    }
+    let mut caps = HIDP_VALUE_CAPS::default();
+    let mut len = 1;
+    let ret = HidP_GetSpecificValueCaps(
+        HidP_Input,
+        HID_USAGE_PAGE_GENERIC,
+        0,
+        HID_USAGE_GENERIC_X,
+        &mut caps,
+        &mut len,
+        preparsed,
+    );
+    HidD_FreePreparsedData(preparsed);
+    if ret != HIDP_STATUS_SUCCESS || len == 0 {
+        return None;
+    }
+    let logical = (caps.LogicalMax as i64 - caps.LogicalMin as i64) as f64;
+    let physical = (caps.PhysicalMax as i64 - caps.PhysicalMin as i64) as f64;
+    if logical <= 0.0 || physical <= 0.0 {
+        return None;
+    }
+    // The unit exponent is a signed 4-bit value.
+    let exp = ((caps.UnitsExp as i32) << 28) >> 28;
+    let per_unit = logical / (physical * 10f64.powi(exp));
+    match caps.Units {
+        UNIT_INCH => Some(per_unit.round() as u32),
+        UNIT_CENTIMETER => Some((per_unit * 2.54).round() as u32),
+        _ => None,
+    }
+}
+
 /// Return information of the device.
 pub fn get_device_info(device: &Device) -> Option<DeviceInfo> {
     unsafe {
@@ -236,6 +602,9 @@ pub fn get_device_info(device: &Device) -> Option<DeviceInfo> {
             RIM_TYPEKEYBOARD => {
                 let keyboard = info.Anonymous.keyboard;
                 Some(DeviceInfo::Keyboard(KeyboardInfo {
+                    keyboard_type: keyboard.dwType,
+                    keyboard_subtype: keyboard.dwSubType,
+                    scan_code_mode: keyboard.dwKeyboardMode,
                     function_num: keyboard.dwNumberOfFunctionKeys,
                     indicator_num: keyboard.dwNumberOfIndicators,
                     keys_total: keyboard.dwNumberOfKeysTotal,
@@ -247,6 +616,7 @@ pub fn get_device_info(device: &Device) -> Option<DeviceInfo> {
                     button_num: mouse.dwNumberOfButtons,
                     sample_rate: mouse.dwSampleRate,
                     has_hwheel: mouse.fHasHorizontalWheel.0 != 0,
+                    cpi: get_mouse_cpi(device.handle),
                 }))
             }
             RIM_TYPEHID => {
@@ -351,18 +721,25 @@ pub fn get_device_info(device: &Device) -> Option<DeviceInfo> {
 }
 
 struct GamePadContext {
-    device: Device,
     preparsed: Vec<u8>,
     button_caps: Vec<HIDP_BUTTON_CAPS>,
     value_caps: Vec<HIDP_VALUE_CAPS>,
     usage: Vec<u16>,
     buttons: Rc<Vec<bool>>,
+    /// Opened by `poll_gamepad`.
+    hid: Option<HidDevice>,
 }
 
 thread_local! {
     static RAW_INPUT_DATA: Rc<RefCell<Vec<u8>>> = Rc::new(RefCell::new(Vec::new()));
-    static GAMEPAD_CONTEXTS: RefCell<Vec<GamePadContext>> = RefCell::new(Vec::new());
-    static DEVICE_LIST: RefCell<Vec<Device>> = RefCell::new(Vec::new());
+    static GAMEPAD_CONTEXTS: RefCell<HashMap<isize, GamePadContext>> = RefCell::new(HashMap::new());
+    static DEVICE_LIST: RefCell<HashMap<isize, Device>> = RefCell::new(HashMap::new());
+    static PLAYERS: RefCell<Vec<Option<PlayerSlot>>> = RefCell::new(Vec::new());
+    static DEVICES_ENUMERATED: Cell<bool> = Cell::new(false);
+}
+
+fn find_device(handle: HANDLE) -> Option<Device> {
+    DEVICE_LIST.with(|dl| dl.borrow().get(&handle.0).cloned())
 }
 
 unsafe fn register_gamepad_context(device: &Device) {
@@ -400,14 +777,101 @@ unsafe fn register_gamepad_context(device: &Device) {
         let button_range = button_caps[0].Anonymous.Range;
         let button_num = (button_range.UsageMax - button_range.UsageMin + 1) as usize;
         let usage_num = HidP_MaxUsageListLength(HidP_Input, button_caps[0].UsagePage, p) as usize;
-        ctxs.push(GamePadContext {
-            device: device.clone(),
-            preparsed,
-            button_caps,
-            value_caps,
-            usage: vec![0u16; usage_num],
-            buttons: Rc::new(vec![false; button_num]),
+        ctxs.insert(
+            device.handle.0,
+            GamePadContext {
+                preparsed,
+                button_caps,
+                value_caps,
+                usage: vec![0u16; usage_num],
+                buttons: Rc::new(vec![false; button_num]),
+                hid: None,
+            },
+        );
+    });
+}
+
+struct PlayerSlot {
+    path: String,
+    device: Option<Device>,
+}
+
+/// A player slot assigned to a game pad.
+#[derive(Clone, Debug)]
+pub struct Player {
+    pub index: usize,
+    /// `None` while the game pad is disconnected.
+    pub device: Option<Device>,
+}
+
+unsafe fn connect_player(device: &Device) {
+    let path = match get_device_interface(device.handle) {
+        Some(interface) => {
+            let end = interface
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(interface.len());
+            String::from_utf16_lossy(&interface[..end])
+        }
+        None => return,
+    };
+    PLAYERS.with(|players| {
+        let mut players = players.borrow_mut();
+        if let Some(slot) = players.iter_mut().flatten().find(|slot| slot.path == path) {
+            slot.device = Some(device.clone());
+            return;
+        }
+        let slot = Some(PlayerSlot {
+            path,
+            device: Some(device.clone()),
         });
+        match players.iter_mut().find(|slot| slot.is_none()) {
+            Some(empty) => *empty = slot,
+            None => players.push(slot),
+        }
+    });
+}
+
+fn disconnect_player(handle: HANDLE) {
+    PLAYERS.with(|players| {
+        for slot in players.borrow_mut().iter_mut().flatten() {
+            if slot
+                .device
+                .as_ref()
+                .map_or(false, |d| d.handle.0 == handle.0)
+            {
+                slot.device = None;
+            }
+        }
+    });
+}
+
+/// Returns the player slots of game pads.
+///
+/// A slot is kept while the game pad is disconnected, and the same game pad gets the same slot again
+/// when it is reconnected. Game pads are matched by the device interface path.
+pub fn players() -> Vec<Player> {
+    PLAYERS.with(|players| {
+        players
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                slot.as_ref().map(|slot| Player {
+                    index,
+                    device: slot.device.clone(),
+                })
+            })
+            .collect()
+    })
+}
+
+/// Releases the player slot so that other game pads can take it.
+pub fn release_player(index: usize) {
+    PLAYERS.with(|players| {
+        if let Some(slot) = players.borrow_mut().get_mut(index) {
+            *slot = None;
+        }
     });
 }
 
@@ -430,61 +894,144 @@ impl From<WPARAM> for WindowState {
     }
 }
 
-pub(crate) fn register_devices(wnd: &Window, state: WindowState) {
-    let flags = RAWINPUTDEVICE_FLAGS(
-        RIDEV_DEVNOTIFY.0
-            | if state == WindowState::Background {
-                RIDEV_INPUTSINK.0
-            } else {
-                0
-            },
-    );
-    let mut device = [
-        RAWINPUTDEVICE {
-            usUsagePage: HID_USAGE_PAGE_GENERIC,
-            usUsage: HID_USAGE_GENERIC_KEYBOARD,
-            dwFlags: flags,
-            hwndTarget: HWND(wnd.raw_handle() as _),
-        },
-        RAWINPUTDEVICE {
-            usUsagePage: HID_USAGE_PAGE_GENERIC,
-            usUsage: HID_USAGE_GENERIC_MOUSE,
-            dwFlags: flags,
-            hwndTarget: HWND(wnd.raw_handle() as _),
-        },
-        RAWINPUTDEVICE {
-            usUsagePage: HID_USAGE_PAGE_GENERIC,
-            usUsage: HID_USAGE_GENERIC_JOYSTICK,
-            dwFlags: flags,
-            hwndTarget: HWND(wnd.raw_handle() as _),
-        },
-        RAWINPUTDEVICE {
+/// The windows that have requested the raw input.
+///
+/// The raw input is registered for the whole process and only one window can be the target,
+/// so the target is selected from the windows and is registered again when a window is closed.
+#[derive(Default)]
+struct Registration {
+    windows: Vec<(isize, WindowState)>,
+    /// The target selected by `set_target`.
+    target: Option<isize>,
+}
+
+impl Registration {
+    /// The explicit target, or the first window in `Background`.
+    ///
+    /// `None` delivers the input to the window that has the keyboard focus.
+    fn target(&self) -> Option<(isize, WindowState)> {
+        match self.target {
+            Some(target) => Some(
+                self.windows
+                    .iter()
+                    .find(|(hwnd, _)| *hwnd == target)
+                    .copied()
+                    .unwrap_or((target, WindowState::Foreground)),
+            ),
+            None => self
+                .windows
+                .iter()
+                .find(|(_, state)| *state == WindowState::Background)
+                .copied(),
+        }
+    }
+
+    fn apply(&self) {
+        let (hwnd, flags) = if self.windows.is_empty() && self.target.is_none() {
+            (HWND::NULL, RIDEV_REMOVE.0)
+        } else {
+            match self.target() {
+                Some((hwnd, WindowState::Background)) => {
+                    (HWND(hwnd), RIDEV_DEVNOTIFY.0 | RIDEV_INPUTSINK.0)
+                }
+                Some((hwnd, WindowState::Foreground)) => (HWND(hwnd), RIDEV_DEVNOTIFY.0),
+                None => (HWND::NULL, RIDEV_DEVNOTIFY.0),
+            }
+        };
+        let flags = RAWINPUTDEVICE_FLAGS(flags);
+        let entry = |usage| RAWINPUTDEVICE {
             usUsagePage: HID_USAGE_PAGE_GENERIC,
-            usUsage: HID_USAGE_GENERIC_GAMEPAD,
+            usUsage: usage,
             dwFlags: flags,
-            hwndTarget: HWND(wnd.raw_handle() as _),
-        },
-    ];
-    unsafe {
-        let ret = RegisterRawInputDevices(
-            device.as_mut_ptr(),
-            device.len() as _,
-            size_of::<RAWINPUTDEVICE>() as _,
-        );
-        if !ret.as_bool() {
-            last_error!("RegisterRawInputDevices");
+            hwndTarget: hwnd,
+        };
+        let mut device = [
+            entry(HID_USAGE_GENERIC_KEYBOARD),
+            entry(HID_USAGE_GENERIC_MOUSE),
+            entry(HID_USAGE_GENERIC_JOYSTICK),
+            entry(HID_USAGE_GENERIC_GAMEPAD),
+        ];
+        unsafe {
+            let ret = RegisterRawInputDevices(
+                device.as_mut_ptr(),
+                device.len() as _,
+                size_of::<RAWINPUTDEVICE>() as _,
+            );
+            if !ret.as_bool() {
+                last_error!("RegisterRawInputDevices");
+            }
         }
+    }
+}
+
+fn registration() -> &'static Mutex<Registration> {
+    static INIT: Once = Once::new();
+    static mut REGISTRATION: Option<Mutex<Registration>> = None;
+    unsafe {
+        INIT.call_once(|| REGISTRATION = Some(Mutex::new(Registration::default())));
+        REGISTRATION.as_ref().unwrap()
+    }
+}
+
+/// Selects the window that receives the raw input regardless of the focus.
+///
+/// `None` restores the default selection: the first window in `WindowState::Background`,
+/// otherwise the window that has the keyboard focus.
+pub fn set_target(window: Option<&Window>) {
+    let mut reg = registration().lock().unwrap();
+    reg.target = window.map(|w| w.raw_handle() as isize);
+    reg.apply();
+}
+
+/// Returns `true` if the raw mouse input is delivered to the window while it has the focus.
+pub(crate) fn is_mouse_delivered_to(hwnd: HWND) -> bool {
+    let reg = registration().lock().unwrap();
+    if reg.windows.is_empty() && reg.target.is_none() {
+        return false;
+    }
+    reg.target().map_or(true, |(target, _)| target == hwnd.0)
+}
+
+/// Unregisters the window when it is destroyed, and registers the raw input for the next target.
+pub(crate) fn unregister_window(hwnd: HWND) {
+    let mut reg = registration().lock().unwrap();
+    let len = reg.windows.len();
+    reg.windows.retain(|(h, _)| *h != hwnd.0);
+    let was_target = reg.target == Some(hwnd.0);
+    if was_target {
+        reg.target = None;
+    }
+    if was_target || reg.windows.len() != len {
+        reg.apply();
+    }
+}
+
+pub(crate) fn register_devices(wnd: &Window, state: WindowState) {
+    {
+        let mut reg = registration().lock().unwrap();
+        reg.windows.push((wnd.raw_handle() as isize, state));
+        reg.apply();
+    }
+    // The devices connected later are added by `WM_INPUT_DEVICE_CHANGE`.
+    if DEVICES_ENUMERATED.with(|enumerated| enumerated.replace(true)) {
+        return;
+    }
+    unsafe {
         let device_list = get_device_list();
         for device in &device_list {
             if get_device_type(device.raw_handle()) == Some(DeviceType::GamePad) {
                 register_gamepad_context(device);
+                connect_player(device);
             }
         }
         GAMEPAD_CONTEXTS.with(|ctxs| {
             log::debug!("GAMEPAD_CONTEXTS.len = {}", ctxs.borrow().len());
         });
         DEVICE_LIST.with(move |dl| {
-            *dl.borrow_mut() = device_list;
+            *dl.borrow_mut() = device_list
+                .into_iter()
+                .map(|device| (device.handle.0, device))
+                .collect();
         });
     }
 }
@@ -538,7 +1085,9 @@ pub fn get_device_list() -> Vec<Device> {
                 Some(Device {
                     handle: device.hDevice,
                     ty: get_device_type(device.hDevice)?,
-                    name: get_device_interface(device.hDevice).and_then(|i| get_device_name(&i)),
+                    name: get_device_interface(device.hDevice)
+                        .and_then(|i| get_device_name(&i))
+                        .map(Arc::from),
                 })
             })
             .collect::<Vec<_>>()
@@ -645,12 +1194,7 @@ unsafe fn input_data_keyboard(input: &mut RAWINPUT) -> Option<InputData> {
     };
     let handle = input.header.hDevice;
     Some(InputData::Keyboard(KeyboardData {
-        device: DEVICE_LIST.with(|dl| {
-            dl.borrow()
-                .iter()
-                .find(|d| d.raw_handle() == handle)
-                .cloned()
-        })?,
+        device: find_device(handle)?,
         code,
         state,
         extra: keyboard.ExtraInformation,
@@ -679,12 +1223,7 @@ unsafe fn input_data_mouse(input: &mut RAWINPUT) -> Option<InputData> {
     };
     let handle = input.header.hDevice;
     Some(InputData::Mouse(MouseData {
-        device: DEVICE_LIST.with(|dl| {
-            dl.borrow()
-                .iter()
-                .find(|d| d.raw_handle() == handle)
-                .cloned()
-        })?,
+        device: find_device(handle)?,
         position,
         wheel,
         hwheel,
@@ -693,106 +1232,139 @@ unsafe fn input_data_mouse(input: &mut RAWINPUT) -> Option<InputData> {
     }))
 }
 
-unsafe fn input_data_gamepad(input: &mut RAWINPUT) -> Option<InputData> {
-    GAMEPAD_CONTEXTS.with(|ctxs| {
-        let handle = input.header.hDevice;
-        let hid = &mut input.data.hid;
-        let mut ctxs = ctxs.borrow_mut();
-        let ctx = ctxs
-            .iter_mut()
-            .find(|ctx| ctx.device.raw_handle() == handle)?;
-        get_preparsed_data(handle, &mut ctx.preparsed)?;
-        let p = ctx.preparsed.as_mut_ptr() as _;
-        let mut len = ctx.usage.len() as _;
-        let ret = HidP_GetUsages(
+/// Parses an input report of the game pad.
+unsafe fn parse_gamepad_report(
+    ctx: &mut GamePadContext,
+    device: Device,
+    report: &mut [u8],
+) -> Option<GamePadData> {
+    let p = ctx.preparsed.as_mut_ptr() as _;
+    let mut len = ctx.usage.len() as _;
+    let ret = HidP_GetUsages(
+        HidP_Input,
+        ctx.button_caps[0].UsagePage,
+        0,
+        ctx.usage.as_mut_ptr(),
+        &mut len,
+        p,
+        PSTR(report.as_mut_ptr()),
+        report.len() as _,
+    );
+    if ret != HIDP_STATUS_SUCCESS {
+        return None;
+    }
+    {
+        // Clones the buttons if the previous data is still alive.
+        let buttons = Rc::make_mut(&mut ctx.buttons);
+        for btn in buttons.iter_mut() {
+            *btn = false;
+        }
+        let range = if ctx.button_caps[0].IsRange != 0 {
+            ctx.button_caps[0].Anonymous.Range.UsageMin
+        } else {
+            ctx.button_caps[0].Anonymous.NotRange.Usage
+        };
+        for i in 0..(len as usize) {
+            buttons[(ctx.usage[i] - range) as usize] = true;
+        }
+    }
+    let mut x = 0;
+    let mut y = 0;
+    let mut z = 0;
+    let mut rx = 0;
+    let mut ry = 0;
+    let mut rz = 0;
+    let mut hat = 0;
+    for caps in &ctx.value_caps {
+        let mut value = 0;
+        let usage = if caps.IsRange != 0 {
+            caps.Anonymous.Range.UsageMin
+        } else {
+            caps.Anonymous.NotRange.Usage
+        };
+        let ret = HidP_GetUsageValue(
             HidP_Input,
-            ctx.button_caps[0].UsagePage,
+            caps.UsagePage,
             0,
-            ctx.usage.as_mut_ptr(),
-            &mut len,
+            usage,
+            &mut value,
             p,
-            PSTR(hid.bRawData.as_mut_ptr()),
-            hid.dwSizeHid,
+            PSTR(report.as_mut_ptr()),
+            report.len() as _,
         );
         if ret != HIDP_STATUS_SUCCESS {
-            return None;
-        }
-        {
-            let buttons = Rc::get_mut(&mut ctx.buttons).unwrap();
-            for btn in buttons.iter_mut() {
-                *btn = false;
-            }
-            let range = if ctx.button_caps[0].IsRange != 0 {
-                ctx.button_caps[0].Anonymous.Range.UsageMin
-            } else {
-                ctx.button_caps[0].Anonymous.NotRange.Usage
-            };
-            for i in 0..(len as usize) {
-                buttons[(ctx.usage[i] - range) as usize] = true;
-            }
+            continue;
         }
-        let mut x = 0;
-        let mut y = 0;
-        let mut z = 0;
-        let mut rx = 0;
-        let mut ry = 0;
-        let mut rz = 0;
-        let mut hat = 0;
-        for caps in &ctx.value_caps {
-            let mut value = 0;
-            let usage = if caps.IsRange != 0 {
-                caps.Anonymous.Range.UsageMin
-            } else {
-                caps.Anonymous.NotRange.Usage
-            };
-            let ret = HidP_GetUsageValue(
-                HidP_Input,
-                caps.UsagePage,
-                0,
-                usage,
-                &mut value,
-                p,
-                PSTR(hid.bRawData.as_mut_ptr()),
-                hid.dwSizeHid,
-            );
-            if ret != HIDP_STATUS_SUCCESS {
-                continue;
-            }
-            let value = value as i32;
-            if usage == 0x39 {
-                hat = value;
-            } else {
-                match usage {
-                    0x30 => x = value,
-                    0x31 => y = value,
-                    0x32 => z = value,
-                    0x33 => rx = value,
-                    0x34 => ry = value,
-                    0x35 => rz = value,
-                    _ => (),
-                }
+        let value = value as i32;
+        if usage == 0x39 {
+            hat = value;
+        } else {
+            match usage {
+                0x30 => x = value,
+                0x31 => y = value,
+                0x32 => z = value,
+                0x33 => rx = value,
+                0x34 => ry = value,
+                0x35 => rz = value,
+                _ => (),
             }
         }
+    }
+    Some(GamePadData {
+        device,
+        x,
+        y,
+        z,
+        rx,
+        ry,
+        rz,
+        hat,
+        buttons: ctx.buttons.clone(),
+    })
+}
+
+unsafe fn input_data_gamepad(input: &mut RAWINPUT) -> Option<InputData> {
+    GAMEPAD_CONTEXTS.with(|ctxs| {
         let handle = input.header.hDevice;
-        Some(InputData::GamePad(GamePadData {
-            device: DEVICE_LIST.with(|dl| {
-                dl.borrow()
-                    .iter()
-                    .find(|d| d.raw_handle() == handle)
-                    .cloned()
-            })?,
-            x,
-            y,
-            z,
-            rx,
-            ry,
-            rz,
-            hat,
-            buttons: ctx.buttons.clone(),
-        }))
+        let hid = &mut input.data.hid;
+        let mut ctxs = ctxs.borrow_mut();
+        let ctx = ctxs.get_mut(&handle.0)?;
+        get_preparsed_data(handle, &mut ctx.preparsed)?;
+        let report = std::slice::from_raw_parts_mut(hid.bRawData.as_mut_ptr(), hid.dwSizeHid as _);
+        parse_gamepad_report(ctx, find_device(handle)?, report).map(InputData::GamePad)
     })
 }
 
+/// Samples the current state of the game pad without waiting for `WM_INPUT`.
+///
+/// This is for fixed-timestep loops. The input report is got with `HidD_GetInputReport`,
+/// so this returns `None` for devices that do not support input reports on the control pipe.
+pub fn poll_gamepad(device: &Device) -> Option<GamePadData> {
+    if device.device_type() != DeviceType::GamePad {
+        return None;
+    }
+    unsafe {
+        if !GAMEPAD_CONTEXTS.with(|ctxs| ctxs.borrow().contains_key(&device.handle.0)) {
+            register_gamepad_context(device);
+        }
+        GAMEPAD_CONTEXTS.with(|ctxs| {
+            let mut ctxs = ctxs.borrow_mut();
+            let ctx = ctxs.get_mut(&device.handle.0)?;
+            if ctx.hid.is_none() {
+                ctx.hid = Some(device.open().ok()?);
+            }
+            let mut report = {
+                let hid = ctx.hid.as_ref().unwrap();
+                let mut report = vec![0u8; hid.input_report_len()];
+                report[0] = ctx.button_caps[0].ReportID;
+                hid.get_input_report(&mut report).ok()?;
+                report
+            };
+            parse_gamepad_report(ctx, device.clone(), &mut report)
+        })
+    }
+}
+
 pub(crate) unsafe fn wm_input<T>(
     window: &Window,
     hwnd: HWND,
@@ -802,6 +1374,7 @@ pub(crate) unsafe fn wm_input<T>(
 where
     T: EventHandler + 'static,
 {
+    trace_span!("raw_input::wm_input");
     const HEADER_SIZE: u32 = size_of::<RAWINPUTHEADER>() as u32;
     let input_handle = HRAWINPUT(lparam.0);
     let data = RAW_INPUT_DATA.with(|data| {
@@ -840,6 +1413,15 @@ where
             _ => unreachable!(),
         };
         if let Some(data) = data {
+            if let InputData::Mouse(MouseData {
+                position: MousePosition::Relative { x, y },
+                ..
+            }) = &data
+            {
+                if (*x != 0 || *y != 0) && window.is_relative_mouse_mode() {
+                    eh.mouse_delta(window, *x, *y);
+                }
+            }
             eh.raw_input(window, &data);
         }
     });
@@ -862,11 +1444,14 @@ pub(crate) unsafe fn wm_input_device_change<T>(
 where
     T: EventHandler + 'static,
 {
+    trace_span!("raw_input::wm_input_device_change");
     let handle = HANDLE(lparam.0 as _);
     match wparam.0 as u32 {
         GIDC_ARRIVAL => {
             let ty = get_device_type(handle);
-            let name = get_device_interface(handle).and_then(|i| get_device_name(&i));
+            let name = get_device_interface(handle)
+                .and_then(|i| get_device_name(&i))
+                .map(Arc::<str>::from);
             if ty.is_none() || name.is_none() {
                 return LRESULT(0);
             }
@@ -877,39 +1462,23 @@ where
             };
             if device.ty == DeviceType::GamePad {
                 register_gamepad_context(&device);
+                connect_player(&device);
             }
-            DEVICE_LIST.with(|dl| dl.borrow_mut().push(device.clone()));
+            DEVICE_LIST.with(|dl| dl.borrow_mut().insert(handle.0, device.clone()));
             call_handler(|eh: &mut T, _| {
                 eh.raw_input_device_change(window, &device, DeviceChangeState::Arrival);
             });
             debug!("device arrival: {:?} {:?}", handle, device.name);
         }
         GIDC_REMOVAL => {
-            if let Some(device) = DEVICE_LIST.with(|dl| {
-                dl.borrow()
-                    .iter()
-                    .find(|d| d.raw_handle() == handle)
-                    .cloned()
-            }) {
+            if let Some(device) = DEVICE_LIST.with(|dl| dl.borrow_mut().remove(&handle.0)) {
                 call_handler(|eh: &mut T, _| {
                     eh.raw_input_device_change(window, &device, DeviceChangeState::Removal);
                 });
                 debug!("device removal: {:?} {:?}", handle, device.name);
             }
-            GAMEPAD_CONTEXTS.with(|ctxs| {
-                let mut ctxs = ctxs.borrow_mut();
-                let index = ctxs.iter().position(|ctx| ctx.device.handle == handle);
-                if let Some(index) = index {
-                    ctxs.remove(index);
-                }
-            });
-            DEVICE_LIST.with(|dl| {
-                let mut dl = dl.borrow_mut();
-                let index = dl.iter().position(|d| d.raw_handle() == handle);
-                if let Some(index) = index {
-                    dl.remove(index);
-                }
-            });
+            GAMEPAD_CONTEXTS.with(|ctxs| ctxs.borrow_mut().remove(&handle.0));
+            disconnect_player(handle);
         }
         _ => unreachable!(),
     }
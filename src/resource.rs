@@ -1,4 +1,5 @@
 use crate::bindings::Windows::Win32::{Foundation::*, UI::Controls::*, UI::WindowsAndMessaging::*};
+use crate::error::ApiError;
 use std::path::{Path, PathBuf};
 
 #[inline]
@@ -21,7 +22,7 @@ impl Icon {
     }
 }
 
-fn load_icon_impl(hinst: HINSTANCE, icon: &Icon, cx: i32, cy: i32) -> HICON {
+fn load_icon_impl(hinst: HINSTANCE, icon: &Icon, cx: i32, cy: i32) -> Result<HICON, ApiError> {
     let icon = unsafe {
         match icon {
             Icon::Resource(id) => {
@@ -38,12 +39,12 @@ fn load_icon_impl(hinst: HINSTANCE, icon: &Icon, cx: i32, cy: i32) -> HICON {
         }
     };
     if icon == HANDLE::NULL {
-        panic!("cannot load the icon");
+        return Err(ApiError::new().with_function("LoadImageW"));
     }
-    HICON(icon.0)
+    Ok(HICON(icon.0))
 }
 
-pub(crate) fn load_icon(icon: &Icon, hinst: HINSTANCE) -> HICON {
+pub(crate) fn load_icon(icon: &Icon, hinst: HINSTANCE) -> Result<HICON, ApiError> {
     unsafe {
         load_icon_impl(
             hinst,
@@ -54,7 +55,7 @@ pub(crate) fn load_icon(icon: &Icon, hinst: HINSTANCE) -> HICON {
     }
 }
 
-pub(crate) fn load_small_icon(icon: &Icon, hinst: HINSTANCE) -> HICON {
+pub(crate) fn load_small_icon(icon: &Icon, hinst: HINSTANCE) -> Result<HICON, ApiError> {
     unsafe {
         load_icon_impl(
             hinst,
@@ -0,0 +1,136 @@
+use crate::bindings::Windows::Win32::{Foundation::*, UI::WindowsAndMessaging::*};
+
+const SB_LINEUP: u32 = 0;
+const SB_LINEDOWN: u32 = 1;
+const SB_PAGEUP: u32 = 2;
+const SB_PAGEDOWN: u32 = 3;
+const SB_THUMBPOSITION: u32 = 4;
+const SB_THUMBTRACK: u32 = 5;
+const SB_TOP: u32 = 6;
+const SB_BOTTOM: u32 = 7;
+const SB_ENDSCROLL: u32 = 8;
+
+/// Describes the standard scroll bars of a window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum ScrollBar {
+    Vertical,
+    Horizontal,
+}
+
+impl ScrollBar {
+    pub(crate) fn raw(self) -> SCROLLBAR_CONSTANTS {
+        match self {
+            Self::Vertical => SB_VERT,
+            Self::Horizontal => SB_HORZ,
+        }
+    }
+}
+
+/// Describes an operation of a scroll bar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum ScrollAction {
+    /// Up or left by one line.
+    LineUp,
+    /// Down or right by one line.
+    LineDown,
+    /// Up or left by one page.
+    PageUp,
+    /// Down or right by one page.
+    PageDown,
+    /// The thumb is being dragged.
+    ThumbTrack,
+    /// The thumb has been released.
+    ThumbPosition,
+    Top,
+    Bottom,
+    /// The scroll operation has been finished.
+    EndScroll,
+}
+
+/// The range, the page size and the position of a scroll bar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ScrollInfo {
+    pub min: i32,
+    pub max: i32,
+    /// The size of the visible part in the units of `min` and `max`.
+    pub page: u32,
+    pub position: i32,
+}
+
+impl Default for ScrollInfo {
+    fn default() -> Self {
+        Self {
+            min: 0,
+            max: 100,
+            page: 0,
+            position: 0,
+        }
+    }
+}
+
+pub(crate) fn get_scroll_info(hwnd: HWND, bar: ScrollBar) -> Option<SCROLLINFO> {
+    unsafe {
+        let mut si = SCROLLINFO {
+            cbSize: std::mem::size_of::<SCROLLINFO>() as _,
+            fMask: SIF_ALL,
+            ..Default::default()
+        };
+        GetScrollInfo(hwnd, bar.raw(), &mut si)
+            .as_bool()
+            .then(|| si)
+    }
+}
+
+pub(crate) fn set_scroll_info(hwnd: HWND, bar: ScrollBar, info: &ScrollInfo) {
+    unsafe {
+        let si = SCROLLINFO {
+            cbSize: std::mem::size_of::<SCROLLINFO>() as _,
+            fMask: SCROLLINFO_MASK(SIF_RANGE.0 | SIF_PAGE.0 | SIF_POS.0),
+            nMin: info.min,
+            nMax: info.max,
+            nPage: info.page,
+            nPos: info.position,
+            nTrackPos: 0,
+        };
+        SetScrollInfo(hwnd, bar.raw(), &si, true);
+    }
+}
+
+/// Moves the scroll bar for `WM_VSCROLL` and `WM_HSCROLL` in the standard way.
+///
+/// Returns the action and the new position.
+pub(crate) fn apply(hwnd: HWND, bar: ScrollBar, wparam: WPARAM) -> Option<(ScrollAction, i32)> {
+    let si = get_scroll_info(hwnd, bar)?;
+    let page = si.nPage as i32;
+    let (action, position) = match (wparam.0 & 0xffff) as u32 {
+        SB_LINEUP => (ScrollAction::LineUp, si.nPos - 1),
+        SB_LINEDOWN => (ScrollAction::LineDown, si.nPos + 1),
+        SB_PAGEUP => (ScrollAction::PageUp, si.nPos - page.max(1)),
+        SB_PAGEDOWN => (ScrollAction::PageDown, si.nPos + page.max(1)),
+        SB_THUMBTRACK => (ScrollAction::ThumbTrack, si.nTrackPos),
+        SB_THUMBPOSITION => (ScrollAction::ThumbPosition, si.nTrackPos),
+        SB_TOP => (ScrollAction::Top, si.nMin),
+        SB_BOTTOM => (ScrollAction::Bottom, si.nMax),
+        SB_ENDSCROLL => (ScrollAction::EndScroll, si.nPos),
+        _ => return None,
+    };
+    // The maximum position is `max - page + 1` when the page size is set.
+    let max = if page > 0 {
+        si.nMax - page + 1
+    } else {
+        si.nMax
+    };
+    let position = position.min(max).max(si.nMin);
+    if position != si.nPos {
+        unsafe {
+            let si = SCROLLINFO {
+                cbSize: std::mem::size_of::<SCROLLINFO>() as _,
+                fMask: SIF_POS,
+                nPos: position,
+                ..Default::default()
+            };
+            SetScrollInfo(hwnd, bar.raw(), &si, true);
+        }
+    }
+    Some((action, position))
+}
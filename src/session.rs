@@ -0,0 +1,74 @@
+use crate::bindings::Windows::Win32::{Foundation::*, System::Shutdown::*};
+use crate::last_error;
+
+const ENDSESSION_CLOSEAPP: u32 = 0x00000001;
+const ENDSESSION_CRITICAL: u32 = 0x40000000;
+const ENDSESSION_LOGOFF: u32 = 0x80000000;
+
+/// Describes why the session is ending.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Hash)]
+pub struct SessionEndReason {
+    /// The user is logging off.
+    pub logoff: bool,
+    /// The application is forced to shut down.
+    pub critical: bool,
+    /// The application must close for an update or a restart.
+    pub close_app: bool,
+}
+
+impl SessionEndReason {
+    pub(crate) fn from_lparam(value: u32) -> Self {
+        Self {
+            logoff: value & ENDSESSION_LOGOFF != 0,
+            critical: value & ENDSESSION_CRITICAL != 0,
+            close_app: value & ENDSESSION_CLOSEAPP != 0,
+        }
+    }
+}
+
+/// Describes the response to the ending session.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum SessionEndResponse {
+    /// Allows the session to end.
+    Allow,
+    /// Blocks the shutdown with the reason that is shown to the user.
+    ///
+    /// Call `Window::unblock_shutdown` after saving data.
+    Block(String),
+}
+
+impl Default for SessionEndResponse {
+    #[inline]
+    fn default() -> Self {
+        Self::Allow
+    }
+}
+
+pub(crate) fn block_shutdown(hwnd: HWND, reason: &str) {
+    unsafe {
+        if !ShutdownBlockReasonCreate(hwnd, reason).as_bool() {
+            last_error!("ShutdownBlockReasonCreate");
+        }
+    }
+}
+
+pub(crate) fn unblock_shutdown(hwnd: HWND) {
+    unsafe {
+        ShutdownBlockReasonDestroy(hwnd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_end_reason() {
+        let reason = SessionEndReason::from_lparam(0);
+        assert_eq!(reason, SessionEndReason::default());
+        let reason = SessionEndReason::from_lparam(ENDSESSION_LOGOFF | ENDSESSION_CLOSEAPP);
+        assert!(reason.logoff);
+        assert!(!reason.critical);
+        assert!(reason.close_app);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::bindings::Windows::Win32::UI::WindowsAndMessaging::*;
+
+/// Describes categories of changed system settings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum SettingKind {
+    Colors,
+    WorkArea,
+    Input,
+    Locale,
+    Other,
+}
+
+const INPUT_ACTIONS: &[SYSTEM_PARAMETERS_INFO_ACTION] = &[
+    SPI_SETKEYBOARDDELAY,
+    SPI_SETKEYBOARDSPEED,
+    SPI_SETKEYBOARDPREF,
+    SPI_SETMOUSE,
+    SPI_SETMOUSESPEED,
+    SPI_SETMOUSEBUTTONSWAP,
+    SPI_SETDOUBLECLICKTIME,
+    SPI_SETWHEELSCROLLLINES,
+    SPI_SETWHEELSCROLLCHARS,
+];
+
+pub(crate) fn setting_kind(action: u32, area: Option<&str>) -> SettingKind {
+    match area {
+        Some("ImmersiveColorSet") => return SettingKind::Colors,
+        Some("intl") => return SettingKind::Locale,
+        _ => (),
+    }
+    if action == SPI_SETWORKAREA.0 {
+        SettingKind::WorkArea
+    } else if action == SPI_SETHIGHCONTRAST.0 {
+        SettingKind::Colors
+    } else if INPUT_ACTIONS.iter().any(|a| a.0 == action) {
+        SettingKind::Input
+    } else {
+        SettingKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_setting_kind() {
+        assert_eq!(
+            setting_kind(0, Some("ImmersiveColorSet")),
+            SettingKind::Colors
+        );
+        assert_eq!(setting_kind(0, Some("intl")), SettingKind::Locale);
+        assert_eq!(setting_kind(SPI_SETWORKAREA.0, None), SettingKind::WorkArea);
+        assert_eq!(setting_kind(SPI_SETMOUSESPEED.0, None), SettingKind::Input);
+        assert_eq!(
+            setting_kind(SPI_SETKEYBOARDDELAY.0, None),
+            SettingKind::Input
+        );
+        assert_eq!(setting_kind(0, Some("Policy")), SettingKind::Other);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, System::LibraryLoader::GetModuleHandleW, UI::KeyboardAndMouseInput::*,
+    UI::WindowsAndMessaging::*,
+};
+use crate::last_error;
+use std::cell::RefCell;
+
+const VK_TAB: u32 = 0x09;
+const VK_CONTROL: i32 = 0x11;
+const VK_ESCAPE: u32 = 0x1b;
+const VK_LWIN: u32 = 0x5b;
+const VK_RWIN: u32 = 0x5c;
+const LLKHF_ALTDOWN: u32 = 0x20;
+
+#[repr(C)]
+struct KbdLlHookStruct {
+    vk_code: u32,
+    scan_code: u32,
+    flags: u32,
+    time: u32,
+    extra_info: usize,
+}
+
+struct Blocker {
+    hook: HHOOK,
+    windows: Vec<isize>,
+}
+
+thread_local! {
+    static BLOCKER: RefCell<Blocker> = RefCell::new(Blocker {
+        hook: HHOOK(0),
+        windows: Vec::new(),
+    });
+}
+
+fn is_system_shortcut(kb: &KbdLlHookStruct) -> bool {
+    let alt = kb.flags & LLKHF_ALTDOWN != 0;
+    match kb.vk_code {
+        VK_LWIN | VK_RWIN => true,
+        VK_TAB => alt,
+        VK_ESCAPE => alt || unsafe { GetAsyncKeyState(VK_CONTROL) < 0 },
+        _ => false,
+    }
+}
+
+extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe {
+        if code == HC_ACTION as i32 {
+            let kb = &*(lparam.0 as *const KbdLlHookStruct);
+            let foreground = GetForegroundWindow();
+            let focused = BLOCKER.with(|blocker| {
+                blocker
+                    .borrow()
+                    .windows
+                    .iter()
+                    .any(|hwnd| GetAncestor(HWND(*hwnd), GA_ROOT) == foreground)
+            });
+            if focused && is_system_shortcut(kb) {
+                return LRESULT(1);
+            }
+        }
+        CallNextHookEx(HHOOK(0), code, wparam, lparam)
+    }
+}
+
+/// Blocks the Windows key, Alt+Tab, Alt+Esc and Ctrl+Esc while the window is in the foreground.
+///
+/// The low-level keyboard hook is installed while any window blocks shortcuts.
+pub(crate) fn set_blocked(hwnd: HWND, block: bool) {
+    BLOCKER.with(|blocker| {
+        let mut blocker = blocker.borrow_mut();
+        blocker.windows.retain(|h| *h != hwnd.0);
+        if block {
+            blocker.windows.push(hwnd.0);
+        }
+        unsafe {
+            if !blocker.windows.is_empty() && blocker.hook.0 == 0 {
+                blocker.hook = SetWindowsHookExW(
+                    WH_KEYBOARD_LL,
+                    Some(hook_proc),
+                    GetModuleHandleW(PWSTR::NULL),
+                    0,
+                );
+                if blocker.hook.0 == 0 {
+                    last_error!("SetWindowsHookExW");
+                }
+            } else if blocker.windows.is_empty() && blocker.hook.0 != 0 {
+                UnhookWindowsHookEx(blocker.hook);
+                blocker.hook = HHOOK(0);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kb(vk_code: u32, flags: u32) -> KbdLlHookStruct {
+        KbdLlHookStruct {
+            vk_code,
+            scan_code: 0,
+            flags,
+            time: 0,
+            extra_info: 0,
+        }
+    }
+
+    #[test]
+    fn system_shortcut() {
+        assert!(is_system_shortcut(&kb(VK_LWIN, 0)));
+        assert!(is_system_shortcut(&kb(VK_RWIN, 0)));
+        assert!(is_system_shortcut(&kb(VK_TAB, LLKHF_ALTDOWN)));
+        assert!(!is_system_shortcut(&kb(VK_TAB, 0)));
+        assert!(is_system_shortcut(&kb(VK_ESCAPE, LLKHF_ALTDOWN)));
+        assert!(!is_system_shortcut(&kb(b'A' as u32, LLKHF_ALTDOWN)));
+    }
+}
@@ -0,0 +1,111 @@
+//! Injects input messages into windows for testing event handlers.
+//!
+//! To use, specify `"test_util"` feature.
+//!
+//! The messages are posted to the window, so they are handled in the event loop like real input.
+//! The system state such as `GetKeyState` is not changed.
+
+use crate::bindings::Windows::Win32::{
+    Foundation::*, UI::KeyboardAndMouseInput::*, UI::WindowsAndMessaging::*,
+};
+use crate::device::*;
+use crate::error::ApiError;
+use crate::geometry::*;
+use crate::window::Window;
+
+fn post(window: &Window, msg: u32, wparam: usize, lparam: isize) -> Result<(), ApiError> {
+    unsafe {
+        if PostMessageW(
+            HWND(window.raw_handle() as _),
+            msg,
+            WPARAM(wparam),
+            LPARAM(lparam),
+        )
+        .as_bool()
+        {
+            Ok(())
+        } else {
+            Err(ApiError::new().with_function("PostMessageW"))
+        }
+    }
+}
+
+#[inline]
+fn mk_button(button: MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => MK_LBUTTON,
+        MouseButton::Right => MK_RBUTTON,
+        MouseButton::Middle => MK_MBUTTON,
+        MouseButton::Ex(0) => MK_XBUTTON1,
+        MouseButton::Ex(_) => MK_XBUTTON2,
+    }
+}
+
+#[inline]
+fn position_lparam(position: PhysicalPosition<i32>) -> isize {
+    ((position.x & 0xffff) | ((position.y & 0xffff) << 16)) as isize
+}
+
+impl Window {
+    /// Posts `WM_KEYDOWN` or `WM_KEYUP` to the window.
+    pub fn inject_key(&self, key: VirtualKey, state: KeyState) -> Result<(), ApiError> {
+        let vkey = to_raw_virtual_key(key);
+        let scan_code = unsafe { MapVirtualKeyW(vkey, MAPVK_VK_TO_VSC) };
+        let mut lparam = 1 | ((scan_code & 0xff) << 16);
+        let msg = match state {
+            KeyState::Pressed => WM_KEYDOWN,
+            KeyState::Released => {
+                lparam |= (1 << 30) | (1 << 31);
+                WM_KEYUP
+            }
+        };
+        post(self, msg, vkey as _, lparam as i32 as _)
+    }
+
+    /// Posts `WM_CHAR` to the window.
+    pub fn inject_char(&self, c: char) -> Result<(), ApiError> {
+        post(self, WM_CHAR, c as _, 1)
+    }
+
+    /// Posts `WM_MOUSEMOVE` to the window.
+    ///
+    /// `pressed` is the buttons that are regarded as being pressed.
+    pub fn inject_mouse_move(
+        &self,
+        position: impl Into<PhysicalPosition<i32>>,
+        pressed: MouseButtons,
+    ) -> Result<(), ApiError> {
+        let wparam = pressed.iter().fold(0, |w, b| w | mk_button(b));
+        post(
+            self,
+            WM_MOUSEMOVE,
+            wparam as _,
+            position_lparam(position.into()),
+        )
+    }
+
+    /// Posts the message of the mouse button to the window.
+    pub fn inject_mouse(
+        &self,
+        button: MouseButton,
+        state: KeyState,
+        position: impl Into<PhysicalPosition<i32>>,
+    ) -> Result<(), ApiError> {
+        let pressed = state == KeyState::Pressed;
+        let msg = match (button, pressed) {
+            (MouseButton::Left, true) => WM_LBUTTONDOWN,
+            (MouseButton::Left, false) => WM_LBUTTONUP,
+            (MouseButton::Right, true) => WM_RBUTTONDOWN,
+            (MouseButton::Right, false) => WM_RBUTTONUP,
+            (MouseButton::Middle, true) => WM_MBUTTONDOWN,
+            (MouseButton::Middle, false) => WM_MBUTTONUP,
+            (MouseButton::Ex(_), true) => WM_XBUTTONDOWN,
+            (MouseButton::Ex(_), false) => WM_XBUTTONUP,
+        };
+        let mut wparam = if pressed { mk_button(button) } else { 0 } as usize;
+        if let MouseButton::Ex(n) = button {
+            wparam |= if n == 0 { 0x0001 } else { 0x0002 } << 16;
+        }
+        post(self, msg, wparam, position_lparam(position.into()))
+    }
+}
@@ -0,0 +1,98 @@
+use crate::api::get_registry_dword;
+use crate::bindings::Windows::Win32::{UI::Accessibility::*, UI::WindowsAndMessaging::*};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const PERSONALIZE_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize";
+const DWM_KEY: &str = "Software\\Microsoft\\Windows\\DWM";
+
+/// Describes the system theme.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+/// A RGB color.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    #[inline]
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    #[inline]
+    pub(crate) fn from_abgr(value: u32) -> Self {
+        Self::new(
+            (value & 0xff) as u8,
+            ((value >> 8) & 0xff) as u8,
+            ((value >> 16) & 0xff) as u8,
+        )
+    }
+}
+
+/// Returns the theme that applications use.
+pub fn system_theme() -> Theme {
+    match get_registry_dword(PERSONALIZE_KEY, "AppsUseLightTheme") {
+        Some(0) => Theme::Dark,
+        _ => Theme::Light,
+    }
+}
+
+/// Returns the accent color of the system.
+pub fn accent_color() -> Option<Color> {
+    get_registry_dword(DWM_KEY, "AccentColor").map(Color::from_abgr)
+}
+
+/// A high contrast scheme.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HighContrastScheme {
+    pub name: String,
+}
+
+/// Returns the high contrast scheme if the high contrast mode is enabled.
+pub fn high_contrast() -> Option<HighContrastScheme> {
+    unsafe {
+        let mut hc = HIGHCONTRASTW {
+            cbSize: std::mem::size_of::<HIGHCONTRASTW>() as _,
+            ..Default::default()
+        };
+        let ret = SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            hc.cbSize,
+            &mut hc as *mut HIGHCONTRASTW as _,
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        );
+        if !ret.as_bool() || hc.dwFlags.0 & HCF_HIGHCONTRASTON.0 == 0 {
+            return None;
+        }
+        let name = if hc.lpszDefaultScheme.is_null() {
+            String::new()
+        } else {
+            let p = hc.lpszDefaultScheme.0;
+            let len = (0..isize::MAX).position(|i| *p.offset(i) == 0).unwrap();
+            String::from_utf16_lossy(std::slice::from_raw_parts(p, len))
+        };
+        Some(HighContrastScheme { name })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_from_abgr() {
+        let c = Color::from_abgr(0xff332211);
+        assert_eq!(c, Color::new(0x11, 0x22, 0x33));
+    }
+}
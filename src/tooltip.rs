@@ -0,0 +1,201 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, System::LibraryLoader::*, UI::Controls::*, UI::WindowsAndMessaging::*,
+};
+use crate::error::ApiError;
+use crate::geometry::*;
+use crate::window::Window;
+
+const TTS_ALWAYSTIP: u32 = 0x01;
+const TTS_NOPREFIX: u32 = 0x02;
+
+const TTF_IDISHWND: u32 = 0x0001;
+const TTF_SUBCLASS: u32 = 0x0010;
+
+const TTM_ACTIVATE: u32 = WM_USER + 1;
+const TTM_SETMAXTIPWIDTH: u32 = WM_USER + 24;
+const TTM_ADDTOOLW: u32 = WM_USER + 50;
+const TTM_DELTOOLW: u32 = WM_USER + 51;
+const TTM_NEWTOOLRECTW: u32 = WM_USER + 52;
+const TTM_UPDATETIPTEXTW: u32 = WM_USER + 57;
+
+const E_FAIL: HRESULT = HRESULT(0x80004005);
+
+/// `TTTOOLINFOW`
+#[repr(C)]
+struct ToolInfo {
+    size: u32,
+    flags: u32,
+    hwnd: HWND,
+    id: usize,
+    rect: RECT,
+    hinst: HINSTANCE,
+    text: PWSTR,
+    lparam: LPARAM,
+    reserved: *mut std::ffi::c_void,
+}
+
+/// `TTTOOLINFOW_V2_SIZE`
+///
+/// comctl32.dll v5 used without a manifest rejects the full size that contains `reserved`.
+const TTTOOLINFOW_V2_SIZE: usize =
+    std::mem::size_of::<ToolInfo>() - std::mem::size_of::<*mut std::ffi::c_void>();
+
+impl ToolInfo {
+    fn new(hwnd: HWND, id: usize, flags: u32) -> Self {
+        Self {
+            size: TTTOOLINFOW_V2_SIZE as _,
+            flags,
+            hwnd,
+            id,
+            rect: RECT::default(),
+            hinst: HINSTANCE::NULL,
+            text: PWSTR::NULL,
+            lparam: LPARAM(0),
+            reserved: std::ptr::null_mut(),
+        }
+    }
+}
+
+fn rect(position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> RECT {
+    RECT {
+        left: position.x,
+        top: position.y,
+        right: position.x + size.width as i32,
+        bottom: position.y + size.height as i32,
+    }
+}
+
+/// Identifies a region of a `Tooltip`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ToolId(usize);
+
+/// A native tooltip that is shown while the cursor hovers over the window or its regions.
+///
+/// `Tooltip` must be created and used on the thread that runs the event loop.
+pub struct Tooltip {
+    hwnd: HWND,
+    owner: HWND,
+    has_window_tool: bool,
+    next_id: usize,
+}
+
+impl Tooltip {
+    pub fn new(window: &Window) -> Result<Self, ApiError> {
+        unsafe {
+            let icc = INITCOMMONCONTROLSEX {
+                dwSize: std::mem::size_of::<INITCOMMONCONTROLSEX>() as _,
+                dwICC: ICC_BAR_CLASSES,
+            };
+            InitCommonControlsEx(&icc);
+            let owner = HWND(window.raw_handle() as _);
+            let hwnd = CreateWindowExW(
+                WS_EX_TOPMOST,
+                "tooltips_class32",
+                PWSTR::NULL,
+                WINDOW_STYLE(WS_POPUP.0 | TTS_ALWAYSTIP | TTS_NOPREFIX),
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                owner,
+                HMENU::NULL,
+                GetModuleHandleW(PWSTR::NULL),
+                std::ptr::null_mut(),
+            );
+            if hwnd == HWND::NULL {
+                return Err(ApiError::new().with_function("CreateWindowExW"));
+            }
+            Ok(Self {
+                hwnd,
+                owner,
+                has_window_tool: false,
+                next_id: 1,
+            })
+        }
+    }
+
+    fn send(&self, msg: u32, wparam: usize, info: &mut ToolInfo) -> LRESULT {
+        unsafe { SendMessageW(self.hwnd, msg, WPARAM(wparam), LPARAM(info as *mut _ as _)) }
+    }
+
+    /// Sets the text that is shown over the whole client area.
+    pub fn set_text(&mut self, text: &str) -> Result<(), ApiError> {
+        let mut text = text.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+        let mut info = ToolInfo::new(self.owner, self.owner.0 as _, TTF_IDISHWND | TTF_SUBCLASS);
+        info.text = PWSTR(text.as_mut_ptr());
+        if self.has_window_tool {
+            self.send(TTM_UPDATETIPTEXTW, 0, &mut info);
+            return Ok(());
+        }
+        if self.send(TTM_ADDTOOLW, 0, &mut info).0 == 0 {
+            return Err(ApiError::from_hresult(E_FAIL).with_function("TTM_ADDTOOLW"));
+        }
+        self.has_window_tool = true;
+        Ok(())
+    }
+
+    /// Adds a region in the client area that has its own text.
+    pub fn add_region(
+        &mut self,
+        position: impl Into<PhysicalPosition<i32>>,
+        size: impl Into<PhysicalSize<u32>>,
+        text: &str,
+    ) -> Result<ToolId, ApiError> {
+        let mut text = text.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+        let mut info = ToolInfo::new(self.owner, self.next_id, TTF_SUBCLASS);
+        info.rect = rect(position.into(), size.into());
+        info.text = PWSTR(text.as_mut_ptr());
+        if self.send(TTM_ADDTOOLW, 0, &mut info).0 == 0 {
+            return Err(ApiError::from_hresult(E_FAIL).with_function("TTM_ADDTOOLW"));
+        }
+        let id = ToolId(self.next_id);
+        self.next_id += 1;
+        Ok(id)
+    }
+
+    /// Moves the region such as after the window has been resized.
+    pub fn set_region_rect(
+        &self,
+        id: ToolId,
+        position: impl Into<PhysicalPosition<i32>>,
+        size: impl Into<PhysicalSize<u32>>,
+    ) {
+        let mut info = ToolInfo::new(self.owner, id.0, 0);
+        info.rect = rect(position.into(), size.into());
+        self.send(TTM_NEWTOOLRECTW, 0, &mut info);
+    }
+
+    pub fn set_region_text(&self, id: ToolId, text: &str) {
+        let mut text = text.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+        let mut info = ToolInfo::new(self.owner, id.0, 0);
+        info.text = PWSTR(text.as_mut_ptr());
+        self.send(TTM_UPDATETIPTEXTW, 0, &mut info);
+    }
+
+    pub fn remove_region(&mut self, id: ToolId) {
+        let mut info = ToolInfo::new(self.owner, id.0, 0);
+        self.send(TTM_DELTOOLW, 0, &mut info);
+    }
+
+    /// Sets the maximum width in pixels. The text that exceeds the width is wrapped to multiple lines.
+    pub fn set_max_width(&self, width: u32) {
+        unsafe {
+            SendMessageW(self.hwnd, TTM_SETMAXTIPWIDTH, WPARAM(0), LPARAM(width as _));
+        }
+    }
+
+    /// Enables or disables showing the tooltip.
+    pub fn set_active(&self, active: bool) {
+        unsafe {
+            SendMessageW(self.hwnd, TTM_ACTIVATE, WPARAM(active as _), LPARAM(0));
+        }
+    }
+}
+
+impl Drop for Tooltip {
+    fn drop(&mut self) {
+        unsafe {
+            DestroyWindow(self.hwnd);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::bindings::Windows::Win32::{
+    Foundation::*, UI::Controls::WM_MOUSELEAVE, UI::WindowsAndMessaging::*,
+};
+use crate::device_notify;
+use crate::dwm;
+use crate::procedure::UserMessage;
+use crate::tray::WM_TRAY_NOTIFY;
+
+macro_rules! message_names {
+    ($msg:expr, $($name:ident),* $(,)?) => {
+        match $msg {
+            $($name => Some(stringify!($name)),)*
+            _ => None,
+        }
+    };
+}
+
+fn message_name(msg: u32) -> Option<&'static str> {
+    let name = message_names!(
+        msg,
+        WM_NCCREATE,
+        WM_CREATE,
+        WM_NCCALCSIZE,
+        WM_NCHITTEST,
+        WM_NCACTIVATE,
+        WM_NCPAINT,
+        WM_NCMOUSEMOVE,
+        WM_NCLBUTTONDOWN,
+        WM_NCLBUTTONUP,
+        WM_NCDESTROY,
+        WM_DESTROY,
+        WM_CLOSE,
+        WM_QUIT,
+        WM_PAINT,
+        WM_ERASEBKGND,
+        WM_SHOWWINDOW,
+        WM_ACTIVATE,
+        WM_ACTIVATEAPP,
+        WM_SETFOCUS,
+        WM_KILLFOCUS,
+        WM_ENABLE,
+        WM_SIZE,
+        WM_MOVE,
+        WM_SIZING,
+        WM_MOVING,
+        WM_ENTERSIZEMOVE,
+        WM_EXITSIZEMOVE,
+        WM_WINDOWPOSCHANGING,
+        WM_WINDOWPOSCHANGED,
+        WM_GETMINMAXINFO,
+        WM_DPICHANGED,
+        WM_SETCURSOR,
+        WM_MOUSEMOVE,
+        WM_MOUSELEAVE,
+        WM_MOUSEWHEEL,
+        WM_MOUSEHWHEEL,
+        WM_LBUTTONDOWN,
+        WM_LBUTTONUP,
+        WM_LBUTTONDBLCLK,
+        WM_RBUTTONDOWN,
+        WM_RBUTTONUP,
+        WM_MBUTTONDOWN,
+        WM_MBUTTONUP,
+        WM_XBUTTONDOWN,
+        WM_XBUTTONUP,
+        WM_CAPTURECHANGED,
+        WM_KEYDOWN,
+        WM_KEYUP,
+        WM_SYSKEYDOWN,
+        WM_SYSKEYUP,
+        WM_CHAR,
+        WM_SYSCHAR,
+        WM_DEADCHAR,
+        WM_IME_SETCONTEXT,
+        WM_IME_STARTCOMPOSITION,
+        WM_IME_COMPOSITION,
+        WM_IME_ENDCOMPOSITION,
+        WM_IME_NOTIFY,
+        WM_INPUT,
+        WM_INPUT_DEVICE_CHANGE,
+        WM_DROPFILES,
+        WM_TIMER,
+        WM_SYSCOMMAND,
+        WM_VSCROLL,
+        WM_HSCROLL,
+        WM_ENTERMENULOOP,
+        WM_EXITMENULOOP,
+        WM_ENTERIDLE,
+        WM_SETTINGCHANGE,
+        WM_THEMECHANGED,
+        WM_DISPLAYCHANGE,
+        WM_POWERBROADCAST,
+        WM_QUERYENDSESSION,
+        WM_ENDSESSION,
+        WM_COPYDATA,
+        WM_SETICON,
+        WM_GETICON,
+        WM_SETTEXT,
+        WM_GETTEXT,
+        WM_GETTEXTLENGTH,
+        WM_GETOBJECT,
+    );
+    name.or(match msg {
+        dwm::WM_DWMCOMPOSITIONCHANGED => Some("WM_DWMCOMPOSITIONCHANGED"),
+        dwm::WM_DWMSENDICONICTHUMBNAIL => Some("WM_DWMSENDICONICTHUMBNAIL"),
+        dwm::WM_DWMSENDICONICLIVEPREVIEWBITMAP => Some("WM_DWMSENDICONICLIVEPREVIEWBITMAP"),
+        device_notify::WM_DEVICECHANGE => Some("WM_DEVICECHANGE"),
+        WM_TRAY_NOTIFY => Some("WM_TRAY_NOTIFY"),
+        _ => None,
+    })
+}
+
+fn user_message_name(wparam: usize) -> &'static str {
+    const NAMES: &[(UserMessage, &str)] = &[
+        (UserMessage::SetTitle, "SetTitle"),
+        (UserMessage::SetPosition, "SetPosition"),
+        (UserMessage::SetInnerSize, "SetInnerSize"),
+        (UserMessage::EnableIme, "EnableIme"),
+        (UserMessage::DisableIme, "DisableIme"),
+        (UserMessage::SetStyle, "SetStyle"),
+        (UserMessage::SetStyleBits, "SetStyleBits"),
+        (UserMessage::AcceptDragFiles, "AcceptDragFiles"),
+        (UserMessage::FirstDraw, "FirstDraw"),
+        (UserMessage::UpdateVisibility, "UpdateVisibility"),
+        (UserMessage::SetRelativeMouseMode, "SetRelativeMouseMode"),
+        (UserMessage::RestoreSession, "RestoreSession"),
+        (UserMessage::Destroy, "Destroy"),
+        (UserMessage::FocusChild, "FocusChild"),
+        #[cfg(feature = "block_shortcuts")]
+        (UserMessage::BlockShortcuts, "BlockShortcuts"),
+    ];
+    NAMES
+        .iter()
+        .find(|(m, _)| *m as usize == wparam)
+        .map_or("Unknown", |(_, name)| name)
+}
+
+#[inline]
+fn words(lparam: LPARAM) -> (i16, i16) {
+    (
+        (lparam.0 & 0xffff) as i16,
+        ((lparam.0 >> 16) & 0xffff) as i16,
+    )
+}
+
+/// Decodes the parameters of the message that have simple meanings.
+fn decode(msg: u32, wparam: WPARAM, lparam: LPARAM) -> Option<String> {
+    match msg {
+        WM_SIZE => {
+            let (w, h) = words(lparam);
+            Some(format!("kind={} size={}x{}", wparam.0, w as u16, h as u16))
+        }
+        WM_MOVE => {
+            let (x, y) = words(lparam);
+            Some(format!("position=({}, {})", x, y))
+        }
+        WM_MOUSEMOVE | WM_LBUTTONDOWN | WM_LBUTTONUP | WM_RBUTTONDOWN | WM_RBUTTONUP
+        | WM_MBUTTONDOWN | WM_MBUTTONUP | WM_XBUTTONDOWN | WM_XBUTTONUP => {
+            let (x, y) = words(lparam);
+            Some(format!("position=({}, {}) keys=0x{:x}", x, y, wparam.0))
+        }
+        WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => Some(format!(
+            "vkey=0x{:02x} scan_code=0x{:02x} repeat={}",
+            wparam.0,
+            (lparam.0 >> 16) & 0xff,
+            (lparam.0 >> 30) & 0x01 != 0
+        )),
+        WM_CHAR | WM_SYSCHAR => Some(format!(
+            "char={:?}",
+            std::char::from_u32(wparam.0 as u32).unwrap_or(std::char::REPLACEMENT_CHARACTER)
+        )),
+        WM_DPICHANGED => Some(format!("dpi={}", wparam.0 & 0xffff)),
+        WM_ACTIVATE => Some(format!("state={}", wparam.0 & 0xffff)),
+        WM_SHOWWINDOW => Some(format!("shown={}", wparam.0 != 0)),
+        WM_USER => Some(format!("user_message={}", user_message_name(wparam.0))),
+        _ => None,
+    }
+}
+
+/// Logs the message at the trace level.
+pub(crate) fn trace_message(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) {
+    if !log::log_enabled!(log::Level::Trace) {
+        return;
+    }
+    let detail = decode(msg, wparam, lparam)
+        .unwrap_or_else(|| format!("wparam=0x{:x} lparam=0x{:x}", wparam.0, lparam.0));
+    match message_name(msg) {
+        Some(name) => log::trace!("hwnd=0x{:x} {} {}", hwnd.0, name, detail),
+        None => log::trace!("hwnd=0x{:x} 0x{:04x} {}", hwnd.0, msg, detail),
+    }
+}
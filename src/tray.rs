@@ -0,0 +1,83 @@
+use crate::bindings::Windows::Win32::{Foundation::*, UI::Shell::*, UI::WindowsAndMessaging::*};
+use crate::last_error;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+const WM_APP: u32 = 0x8000;
+
+/// The message that the notification area sends to the window.
+pub(crate) const WM_TRAY_NOTIFY: u32 = WM_APP + 1;
+
+const TRAY_ICON_ID: u32 = 1;
+
+static TASKBAR_CREATED: AtomicU32 = AtomicU32::new(0);
+
+/// Returns the message that is broadcasted when the taskbar has been created such as after Explorer restarted.
+pub(crate) fn taskbar_created() -> u32 {
+    let msg = TASKBAR_CREATED.load(Ordering::Relaxed);
+    if msg != 0 {
+        return msg;
+    }
+    let msg = unsafe { RegisterWindowMessageW("TaskbarCreated") };
+    TASKBAR_CREATED.store(msg, Ordering::Relaxed);
+    msg
+}
+
+/// An icon in the notification area that represents a window.
+pub(crate) struct TrayIcon {
+    hwnd: HWND,
+}
+
+impl TrayIcon {
+    /// Adds the icon with the small icon of the window and the tooltip.
+    pub fn new(hwnd: HWND, tip: &str) -> Option<Self> {
+        // Registers the message before the taskbar can be recreated.
+        taskbar_created();
+        add(hwnd, tip).then(|| Self { hwnd })
+    }
+
+    /// Adds the icon again after the taskbar has been recreated.
+    pub fn restore(&self, tip: &str) {
+        add(self.hwnd, tip);
+    }
+}
+
+fn add(hwnd: HWND, tip: &str) -> bool {
+    unsafe {
+        let mut icon = HICON(SendMessageW(hwnd, WM_GETICON, WPARAM(ICON_SMALL as _), LPARAM(0)).0);
+        if icon.0 == 0 {
+            icon = LoadIconW(HINSTANCE::NULL, IDI_APPLICATION);
+        }
+        let mut nid = NOTIFYICONDATAW {
+            cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as _,
+            hWnd: hwnd,
+            uID: TRAY_ICON_ID,
+            uFlags: NOTIFY_ICON_DATA_FLAGS(NIF_ICON.0 | NIF_MESSAGE.0 | NIF_TIP.0),
+            uCallbackMessage: WM_TRAY_NOTIFY,
+            hIcon: icon,
+            ..Default::default()
+        };
+        let len = nid.szTip.len() - 1;
+        for (dst, src) in nid.szTip.iter_mut().zip(tip.encode_utf16().take(len)) {
+            *dst = src;
+        }
+        if !Shell_NotifyIconW(NIM_ADD, &mut nid).as_bool() {
+            last_error!("Shell_NotifyIconW");
+            return false;
+        }
+        true
+    }
+}
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        unsafe {
+            let mut nid = NOTIFYICONDATAW {
+                cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as _,
+                hWnd: self.hwnd,
+                uID: TRAY_ICON_ID,
+                ..Default::default()
+            };
+            Shell_NotifyIconW(NIM_DELETE, &mut nid);
+        }
+    }
+}
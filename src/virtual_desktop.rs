@@ -0,0 +1,84 @@
+use crate::api::initialize_com;
+use crate::bindings::Windows::Win32::{Foundation::*, System::Com::*, UI::Shell::*};
+use crate::error::ApiError;
+use windows::Guid;
+
+/// The identifier of a virtual desktop.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct VirtualDesktopId(pub u128);
+
+impl From<Guid> for VirtualDesktopId {
+    fn from(src: Guid) -> Self {
+        let mut v =
+            (src.data1 as u128) << 96 | (src.data2 as u128) << 80 | (src.data3 as u128) << 64;
+        for (i, b) in src.data4.iter().enumerate() {
+            v |= (*b as u128) << (56 - i * 8);
+        }
+        Self(v)
+    }
+}
+
+impl From<VirtualDesktopId> for Guid {
+    fn from(src: VirtualDesktopId) -> Self {
+        let v = src.0;
+        let mut data4 = [0u8; 8];
+        for (i, b) in data4.iter_mut().enumerate() {
+            *b = (v >> (56 - i * 8)) as u8;
+        }
+        Guid::from_values((v >> 96) as u32, (v >> 80) as u16, (v >> 64) as u16, data4)
+    }
+}
+
+fn manager() -> Result<IVirtualDesktopManager, ApiError> {
+    initialize_com()?;
+    unsafe {
+        Ok(CoCreateInstance(
+            &VirtualDesktopManager,
+            None,
+            CLSCTX_INPROC_SERVER,
+        )?)
+    }
+}
+
+pub(crate) fn is_on_current_desktop(hwnd: HWND) -> Result<bool, ApiError> {
+    unsafe {
+        let mut ret = BOOL(0);
+        manager()?
+            .IsWindowOnCurrentVirtualDesktop(hwnd, &mut ret)
+            .ok()?;
+        Ok(ret.as_bool())
+    }
+}
+
+pub(crate) fn desktop_id(hwnd: HWND) -> Result<VirtualDesktopId, ApiError> {
+    unsafe {
+        let mut id = Guid::zeroed();
+        manager()?.GetWindowDesktopId(hwnd, &mut id).ok()?;
+        Ok(id.into())
+    }
+}
+
+pub(crate) fn move_to_desktop(hwnd: HWND, id: VirtualDesktopId) -> Result<(), ApiError> {
+    unsafe {
+        manager()?.MoveWindowToDesktop(hwnd, &Guid::from(id)).ok()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guid_round_trip() {
+        let guid = Guid::from_values(
+            0x01234567,
+            0x89ab,
+            0xcdef,
+            [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+        );
+        let id = VirtualDesktopId::from(guid);
+        assert_eq!(id.0, 0x0123456789abcdef0123456789abcdef);
+        assert_eq!(Guid::from(id), guid);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::bindings::Windows::Win32::{Foundation::PWSTR, System::LibraryLoader::GetModuleHandleW};
+use crate::window::Window;
+use ash::{extensions::khr, vk};
+use std::ffi::CStr;
+
+/// Returns the instance extensions that are required to create a surface for a window.
+///
+/// To use, specify `"ash"` feature.
+pub fn vulkan_required_extensions() -> [&'static CStr; 2] {
+    [khr::Surface::name(), khr::Win32Surface::name()]
+}
+
+pub(crate) fn create_surface(
+    window: &Window,
+    entry: &ash::Entry,
+    instance: &ash::Instance,
+) -> Result<vk::SurfaceKHR, vk::Result> {
+    unsafe {
+        let info = vk::Win32SurfaceCreateInfoKHR::builder()
+            .hinstance(GetModuleHandleW(PWSTR::NULL).0 as _)
+            .hwnd(window.raw_handle() as _);
+        khr::Win32Surface::new(entry, instance).create_win32_surface(&info, None)
+    }
+}
@@ -1,25 +1,46 @@
+#[cfg(feature = "accessibility")]
+use crate::accessibility;
+#[cfg(feature = "d2d")]
+use crate::bindings::Windows::Win32::System::Threading::GetCurrentThreadId;
 use crate::bindings::Windows::Win32::{
-    Foundation::*, Graphics::Gdi::*, System::LibraryLoader::*, UI::HiDpi::*, UI::Shell::*,
+    Foundation::*, Graphics::Gdi::*, System::DataExchange::COPYDATASTRUCT,
+    System::LibraryLoader::*, UI::HiDpi::*, UI::KeyboardAndMouseInput::*, UI::Shell::*,
     UI::WindowsAndMessaging::*,
 };
+#[cfg(feature = "blur_behind")]
+use crate::blur;
+#[cfg(feature = "d2d")]
+use crate::d2d;
 #[cfg(feature = "raw_input")]
 use crate::raw_input;
+#[cfg(feature = "virtual_desktop")]
+use crate::virtual_desktop::{self, VirtualDesktopId};
 use crate::DEFAULT_DPI;
 use crate::{
     api::*,
+    config::{self, WindowConfig, WindowSession},
     context::*,
-    device::Cursor,
+    device::{Cursor, MouseMovePoint},
+    device_notify::DeviceNotification,
+    dwm,
     error::*,
-    event::EventHandler,
+    event::{EventHandler, VisibilityState},
+    gdi_frame::GdiFrame,
     geometry::*,
     ime,
+    message::MessageId,
+    power::SuspendResumeNotification,
     procedure::{window_proc, UserMessage},
     resource::*,
+    scroll::{self, ScrollBar, ScrollInfo},
+    session,
+    tray::TrayIcon,
 };
 use raw_window_handle::{windows::WindowsHandle, HasRawWindowHandle, RawWindowHandle};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock, Weak};
 
 #[derive(Clone, PartialEq, Eq)]
 pub(crate) struct WindowHandle(HWND);
@@ -27,25 +48,70 @@ pub(crate) struct WindowHandle(HWND);
 unsafe impl Send for WindowHandle {}
 unsafe impl Sync for WindowHandle {}
 
+pub(crate) const AERO_SNAP_BORDERLESS_STYLE: u32 = WS_POPUP.0
+    | WS_CAPTION.0
+    | WS_SYSMENU.0
+    | WS_THICKFRAME.0
+    | WS_MINIMIZEBOX.0
+    | WS_MAXIMIZEBOX.0;
+
+/// The bits of `GWL_STYLE` that `WindowStyle` and `BorderlessStyle` control.
+pub(crate) const WINDOW_STYLE_MASK: u32 = AERO_SNAP_BORDERLESS_STYLE | WS_VSCROLL.0 | WS_HSCROLL.0;
+
 /// A window style and the borderless window style.
 pub trait Style {
     fn value(&self) -> u32;
 
+    /// Returns `true` if the frame bits are kept but the frame is not drawn.
+    fn is_aero_snap_borderless(&self) -> bool {
+        false
+    }
+
     fn is_borderless(&self) -> bool {
-        self.value() == WS_POPUP.0
+        self.value() == WS_POPUP.0 || self.is_aero_snap_borderless()
     }
 }
 
 /// Represents the borderless window style.
 pub struct BorderlessStyle;
 
+impl BorderlessStyle {
+    /// Keeps the frame bits to enable Aero Snap, snap assist and minimize animations
+    /// while the frame is not drawn.
+    ///
+    /// The resize borders are not provided, so specify them by `Window::set_hit_test`.
+    #[inline]
+    pub fn aero_snap(self) -> AeroSnapBorderlessStyle {
+        AeroSnapBorderlessStyle
+    }
+}
+
 impl Style for BorderlessStyle {
     fn value(&self) -> u32 {
         WS_POPUP.0
     }
 }
 
+/// Represents the borderless window style that supports Aero Snap.
+pub struct AeroSnapBorderlessStyle;
+
+impl Style for AeroSnapBorderlessStyle {
+    fn value(&self) -> u32 {
+        AERO_SNAP_BORDERLESS_STYLE
+    }
+
+    fn is_aero_snap_borderless(&self) -> bool {
+        true
+    }
+}
+
 /// Represents a window style.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct WindowStyle(u32);
 
 impl WindowStyle {
@@ -89,9 +155,29 @@ impl WindowStyle {
         self
     }
 
+    #[inline]
+    pub fn has_vertical_scroll_bar(mut self, has_scroll_bar: bool) -> Self {
+        if has_scroll_bar {
+            self.0 |= WS_VSCROLL.0;
+        } else {
+            self.0 &= !WS_VSCROLL.0;
+        }
+        self
+    }
+
+    #[inline]
+    pub fn has_horizontal_scroll_bar(mut self, has_scroll_bar: bool) -> Self {
+        if has_scroll_bar {
+            self.0 |= WS_HSCROLL.0;
+        } else {
+            self.0 &= !WS_HSCROLL.0;
+        }
+        self
+    }
+
     #[inline]
     pub fn is_borderless(&self) -> bool {
-        self.value() == WS_POPUP.0
+        self.value() == WS_POPUP.0 || self.value() == AERO_SNAP_BORDERLESS_STYLE
     }
 }
 
@@ -115,31 +201,175 @@ impl Style for WindowStyle {
     }
 }
 
-const WINDOW_CLASS_NAME: &str = "wita_window_class";
+/// Describes a result of the hit test.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum HitTest {
+    Client,
+    Caption,
+    SysMenu,
+    MinButton,
+    /// Windows 11 shows the Snap Layouts flyout over this area.
+    MaxButton,
+    CloseButton,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Transparent,
+    Nowhere,
+}
+
+impl HitTest {
+    pub(crate) fn value(&self) -> i32 {
+        match self {
+            Self::Client => HTCLIENT as i32,
+            Self::Caption => HTCAPTION as i32,
+            Self::SysMenu => HTSYSMENU as i32,
+            Self::MinButton => HTMINBUTTON as i32,
+            Self::MaxButton => HTMAXBUTTON as i32,
+            Self::CloseButton => HTCLOSE as i32,
+            Self::Left => HTLEFT as i32,
+            Self::Right => HTRIGHT as i32,
+            Self::Top => HTTOP as i32,
+            Self::Bottom => HTBOTTOM as i32,
+            Self::TopLeft => HTTOPLEFT as i32,
+            Self::TopRight => HTTOPRIGHT as i32,
+            Self::BottomLeft => HTBOTTOMLEFT as i32,
+            Self::BottomRight => HTBOTTOMRIGHT as i32,
+            Self::Transparent => HTTRANSPARENT,
+            Self::Nowhere => HTNOWHERE as i32,
+        }
+    }
+}
+
+/// A function that returns the hit test result at the position in client coordinates.
+pub type HitTestFn = fn(PhysicalPosition<i32>) -> HitTest;
+
+const fn class_name(name: &[u8]) -> [u16; 40] {
+    let mut buffer = [0u16; 40];
+    let mut i = 0;
+    while i < name.len() {
+        buffer[i] = name[i] as u16;
+        i += 1;
+    }
+    buffer
+}
+
+// The class names are encoded at compile time to avoid converting them for each window.
+// The index is given by `class_index`.
+const WINDOW_CLASS_NAMES: [[u16; 40]; 4] = [
+    class_name(b"wita_window_class"),
+    class_name(b"wita_window_class_owndc"),
+    class_name(b"wita_window_class_noredraw"),
+    class_name(b"wita_window_class_owndc_noredraw"),
+];
+
+#[inline]
+fn class_index(own_dc: bool, redraw_on_resize: bool) -> usize {
+    (own_dc as usize) | ((!redraw_on_resize as usize) << 1)
+}
+
+#[inline]
+fn class_name_ptr(index: usize) -> PWSTR {
+    PWSTR(WINDOW_CLASS_NAMES[index].as_ptr() as _)
+}
+
+// The atoms of the registered classes in the order of `WINDOW_CLASS_NAMES`.
+static CLASS_ATOMS: [AtomicU16; 4] = [
+    AtomicU16::new(0),
+    AtomicU16::new(0),
+    AtomicU16::new(0),
+    AtomicU16::new(0),
+];
+
+/// Returns `true` if the window is created with a class registered by wita.
+pub(crate) fn is_wita_window(hwnd: HWND) -> bool {
+    let atom = unsafe { GetClassLongPtrW(hwnd, GCW_ATOM) } as u16;
+    atom != 0
+        && CLASS_ATOMS
+            .iter()
+            .any(|a| a.load(Ordering::Relaxed) == atom)
+}
+
+const ERROR_INVALID_WINDOW_HANDLE: u32 = 1400;
+#[cfg(feature = "d2d")]
+const ERROR_INVALID_THREAD_ID: u32 = 1444;
+const HWND_MESSAGE: HWND = HWND(-3);
+
+/// Registers a window class for each combination of `CS_OWNDC` and `CS_VREDRAW | CS_HREDRAW`.
+pub(crate) fn register_class<T: EventHandler + 'static>() -> Result<(), ApiError> {
+    for &own_dc in &[false, true] {
+        for &redraw_on_resize in &[true, false] {
+            let mut style = 0;
+            if own_dc {
+                style |= CS_OWNDC.0;
+            }
+            if redraw_on_resize {
+                style |= CS_VREDRAW.0 | CS_HREDRAW.0;
+            }
+            // The white class brush flashes in the area exposed by resizing,
+            // so the classes without the redraw styles leave it to `WM_ERASEBKGND`.
+            let background = if redraw_on_resize {
+                unsafe { HBRUSH(GetStockObject(WHITE_BRUSH).0) }
+            } else {
+                HBRUSH::NULL
+            };
+            let index = class_index(own_dc, redraw_on_resize);
+            let atom = register_class_with_style::<T>(
+                WNDCLASS_STYLES(style),
+                background,
+                class_name_ptr(index),
+            )?;
+            CLASS_ATOMS[index].store(atom, Ordering::Relaxed);
+        }
+    }
+    Ok(())
+}
 
-pub(crate) fn register_class<T: EventHandler + 'static>() {
+fn register_class_with_style<T: EventHandler + 'static>(
+    style: WNDCLASS_STYLES,
+    background: HBRUSH,
+    class_name: PWSTR,
+) -> Result<u16, ApiError> {
     unsafe {
-        let class_name = WINDOW_CLASS_NAME
-            .encode_utf16()
-            .chain(Some(0))
-            .collect::<Vec<_>>();
         let wc = WNDCLASSEXW {
             cbSize: std::mem::size_of::<WNDCLASSEXW>() as _,
-            style: WNDCLASS_STYLES(CS_VREDRAW.0 | CS_HREDRAW.0),
+            style,
             lpfnWndProc: Some(window_proc::<T>),
             cbClsExtra: 0,
             cbWndExtra: 0,
             hInstance: GetModuleHandleW(PWSTR::NULL),
             hIcon: HICON::NULL,
             hCursor: HCURSOR::NULL,
-            hbrBackground: HBRUSH(GetStockObject(WHITE_BRUSH).0),
+            hbrBackground: background,
             lpszMenuName: PWSTR::NULL,
-            lpszClassName: PWSTR(class_name.as_ptr() as _),
+            lpszClassName: class_name,
             hIconSm: HICON::NULL,
         };
-        if RegisterClassExW(&wc) == 0 {
-            panic!("cannot register the window class");
+        let atom = RegisterClassExW(&wc);
+        if atom == 0 {
+            return Err(ApiError::new().with_function("RegisterClassExW"));
         }
+        Ok(atom)
+    }
+}
+
+/// Describes how the client area of a window is erased.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Background {
+    /// The client area is not erased.
+    None,
+    /// The client area is filled with a RGB color.
+    SolidColor(u8, u8, u8),
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::SolidColor(0xff, 0xff, 0xff)
     }
 }
 
@@ -150,6 +380,7 @@ pub struct WindowBuilder<Ti, S> {
     inner_size: S,
     visibility: bool,
     style: u32,
+    aero_snap_borderless: bool,
     enabled_ime: bool,
     visible_ime_composition_window: bool,
     visible_ime_candidate_window: bool,
@@ -159,6 +390,12 @@ pub struct WindowBuilder<Ti, S> {
     icon: Option<Icon>,
     cursor: Cursor,
     no_redirection_bitmap: bool,
+    maximized: bool,
+    minimized: bool,
+    show_after_first_draw: bool,
+    background: Background,
+    own_dc: bool,
+    redraw_on_resize: bool,
     #[cfg(feature = "raw_input")]
     raw_input_window_state: raw_input::WindowState,
 }
@@ -171,6 +408,7 @@ impl WindowBuilder<(), ()> {
             position: ScreenPosition::new(0, 0),
             inner_size: LogicalSize::new(640, 480),
             style: WindowStyle::default().value(),
+            aero_snap_borderless: false,
             visibility: true,
             enabled_ime: false,
             visible_ime_composition_window: true,
@@ -181,10 +419,44 @@ impl WindowBuilder<(), ()> {
             icon: None,
             cursor: Cursor::default(),
             no_redirection_bitmap: false,
+            maximized: false,
+            minimized: false,
+            show_after_first_draw: false,
+            background: Background::default(),
+            own_dc: false,
+            redraw_on_resize: true,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: raw_input::WindowState::Foreground,
         }
     }
+
+    /// Creates the builder from the settings such as loaded from a file.
+    pub fn from_config(config: &WindowConfig) -> WindowBuilder<String, LogicalSize<u32>> {
+        let mut builder = WindowBuilder::new()
+            .title(config.title.clone())
+            .inner_size(config.inner_size)
+            .visible(config.visible)
+            .maximized(config.maximized)
+            .ime(config.ime)
+            .accept_drag_files(config.accept_drag_files);
+        builder = if config.borderless {
+            builder.style(WindowStyle::borderless())
+        } else {
+            builder.style(
+                WindowStyle::default()
+                    .resizable(config.resizable)
+                    .has_minimize_box(config.has_minimize_box)
+                    .has_maximize_box(config.has_maximize_box),
+            )
+        };
+        if let Some(position) = config.position {
+            builder = builder.position(position);
+        }
+        if let Some(icon) = &config.icon {
+            builder = builder.icon(Icon::from_path(icon));
+        }
+        builder
+    }
 }
 
 impl<Ti, S> WindowBuilder<Ti, S> {
@@ -194,6 +466,7 @@ impl<Ti, S> WindowBuilder<Ti, S> {
             position: self.position,
             inner_size: self.inner_size,
             style: self.style,
+            aero_snap_borderless: self.aero_snap_borderless,
             visibility: self.visibility,
             enabled_ime: self.enabled_ime,
             visible_ime_composition_window: self.visible_ime_composition_window,
@@ -204,6 +477,12 @@ impl<Ti, S> WindowBuilder<Ti, S> {
             icon: self.icon,
             cursor: self.cursor,
             no_redirection_bitmap: self.no_redirection_bitmap,
+            maximized: self.maximized,
+            minimized: self.minimized,
+            show_after_first_draw: self.show_after_first_draw,
+            background: self.background,
+            own_dc: self.own_dc,
+            redraw_on_resize: self.redraw_on_resize,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: self.raw_input_window_state,
         }
@@ -220,6 +499,7 @@ impl<Ti, S> WindowBuilder<Ti, S> {
             position: self.position,
             inner_size,
             style: self.style,
+            aero_snap_borderless: self.aero_snap_borderless,
             visibility: self.visibility,
             enabled_ime: self.enabled_ime,
             visible_ime_composition_window: self.visible_ime_composition_window,
@@ -230,6 +510,12 @@ impl<Ti, S> WindowBuilder<Ti, S> {
             icon: self.icon,
             cursor: self.cursor,
             no_redirection_bitmap: self.no_redirection_bitmap,
+            maximized: self.maximized,
+            minimized: self.minimized,
+            show_after_first_draw: self.show_after_first_draw,
+            background: self.background,
+            own_dc: self.own_dc,
+            redraw_on_resize: self.redraw_on_resize,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: self.raw_input_window_state,
         }
@@ -237,6 +523,7 @@ impl<Ti, S> WindowBuilder<Ti, S> {
 
     pub fn style(mut self, style: impl Style) -> WindowBuilder<Ti, S> {
         self.style = style.value();
+        self.aero_snap_borderless = style.is_aero_snap_borderless();
         self
     }
 
@@ -303,6 +590,78 @@ impl<Ti, S> WindowBuilder<Ti, S> {
         self
     }
 
+    /// Opens the window maximized.
+    ///
+    /// The window is created with `WS_MAXIMIZE` so that it never appears at the restored size first.
+    pub fn maximized(mut self, maximized: bool) -> WindowBuilder<Ti, S> {
+        self.maximized = maximized;
+        self
+    }
+
+    /// Opens the window minimized.
+    ///
+    /// If `maximized` is also set, `maximized` takes precedence.
+    pub fn minimized(mut self, minimized: bool) -> WindowBuilder<Ti, S> {
+        self.minimized = minimized;
+        self
+    }
+
+    /// Keeps the window hidden until the first `EventHandler::draw` has returned.
+    ///
+    /// This avoids showing the background before a renderer has presented its first frame.
+    /// It has no effect if `visible(false)` is specified.
+    pub fn show_after_first_draw(mut self, enable: bool) -> WindowBuilder<Ti, S> {
+        self.show_after_first_draw = enable;
+        self
+    }
+
+    /// Specifies how the client area is erased.
+    ///
+    /// The default is white.
+    pub fn background(mut self, background: Background) -> WindowBuilder<Ti, S> {
+        self.background = background;
+        self
+    }
+
+    /// Does not erase the client area on `WM_ERASEBKGND`.
+    ///
+    /// This is the same as `background(Background::None)` and is for windows whose whole client area
+    /// is repainted by a renderer every frame.
+    pub fn no_erase_background(mut self, enable: bool) -> WindowBuilder<Ti, S> {
+        if enable {
+            self.background = Background::None;
+        } else if self.background == Background::None {
+            self.background = Background::default();
+        }
+        self
+    }
+
+    /// Gives the window its own device context with `CS_OWNDC`.
+    ///
+    /// WGL requires the device context to stay the same while the rendering context is used.
+    pub fn own_dc(mut self, enable: bool) -> WindowBuilder<Ti, S> {
+        self.own_dc = enable;
+        self
+    }
+
+    /// Redraws the whole client area when the window is resized with `CS_VREDRAW | CS_HREDRAW`.
+    ///
+    /// The default is `true`.
+    pub fn redraw_on_resize(mut self, enable: bool) -> WindowBuilder<Ti, S> {
+        self.redraw_on_resize = enable;
+        self
+    }
+
+    /// Prevents white flashes in the area exposed by resizing.
+    ///
+    /// This disables `CS_VREDRAW | CS_HREDRAW` and the class brush,
+    /// and fills only the exposed area with `background`.
+    pub fn resize_background(mut self, background: Background) -> WindowBuilder<Ti, S> {
+        self.redraw_on_resize = false;
+        self.background = background;
+        self
+    }
+
     #[cfg(feature = "raw_input")]
     pub fn raw_input_window_state(mut self, state: raw_input::WindowState) -> WindowBuilder<Ti, S> {
         self.raw_input_window_state = state;
@@ -316,23 +675,65 @@ where
     S: ToPhysicalSize<u32>,
 {
     pub fn build(self) -> Result<Window, ApiError> {
+        trace_span!("WindowBuilder::build");
         if is_context_null() {
             panic!("The window can be created after run");
         }
         unsafe {
             let dpi = get_dpi_from_point(self.position);
             let inner_size = self.inner_size.to_physical(dpi);
-            let rc = adjust_window_rect(inner_size, self.style, 0, dpi);
+            // The frame of the Aero Snap borderless style is removed by WM_NCCALCSIZE.
+            let frame_style = if self.aero_snap_borderless {
+                WS_POPUP.0
+            } else {
+                self.style
+            };
+            let rc = adjust_window_rect(inner_size, frame_style, 0, dpi);
+            let create_style = if self.maximized {
+                self.style | WS_MAXIMIZE.0
+            } else if self.minimized {
+                self.style | WS_MINIMIZE.0
+            } else {
+                self.style
+            };
             let hinst = GetModuleHandleW(PWSTR::NULL);
+            let icons = match self.icon.as_ref() {
+                Some(icon) => Some((load_icon(icon, hinst)?, load_small_icon(icon, hinst)?)),
+                None => None,
+            };
+            let mut params: CreateParams = Some((
+                WindowState {
+                    title: self.title.as_ref().to_string(),
+                    set_position: (self.position.x, self.position.y),
+                    set_inner_size: inner_size,
+                    ime_position: PhysicalPosition::new(0, 0),
+                    children: self.children.iter().map(Window::downgrade).collect(),
+                    background: self.background,
+                    session: None,
+                    focused_child: None,
+                    #[cfg(feature = "accessibility")]
+                    accessible_tree: Default::default(),
+                },
+                WindowFlags {
+                    aero_snap_borderless: AtomicBool::new(self.aero_snap_borderless),
+                    ..WindowFlags::new(
+                        self.style,
+                        self.cursor,
+                        self.enabled_ime,
+                        self.visible_ime_composition_window,
+                        self.visible_ime_candidate_window,
+                    )
+                },
+            ));
             let hwnd = CreateWindowExW(
                 if self.no_redirection_bitmap {
                     WS_EX_NOREDIRECTIONBITMAP
                 } else {
                     WINDOW_EX_STYLE(0)
                 },
-                WINDOW_CLASS_NAME,
+                class_name_ptr(class_index(self.own_dc, self.redraw_on_resize)),
                 self.title.as_ref(),
-                WINDOW_STYLE(self.style),
+                WINDOW_STYLE(create_style),
                 self.position.x,
                 self.position.y,
                 (rc.right - rc.left) as i32,
@@ -340,42 +741,32 @@ where
                 HWND::NULL,
                 HMENU::NULL,
                 hinst,
-                std::ptr::null_mut(),
+                &mut params as *mut CreateParams as _,
             );
             if hwnd == HWND::NULL {
-                return Err(ApiError::new());
+                return Err(ApiError::new().with_function("CreateWindowExW"));
             }
-            let window = LocalWindow::new(
-                hwnd,
-                WindowState {
-                    title: self.title.as_ref().to_string(),
-                    style: self.style,
-                    set_position: (self.position.x, self.position.y),
-                    set_inner_size: inner_size,
-                    enabled_ime: self.enabled_ime,
-                    visible_ime_composition_window: self.visible_ime_composition_window,
-                    visible_ime_candidate_window: self.visible_ime_candidate_window,
-                    ime_position: PhysicalPosition::new(0, 0),
-                    children: self.children,
-                    closed: false,
-                    cursor: self.cursor,
-                },
-            );
-            self.cursor.set();
+            let window = LocalWindow::get(hwnd).unwrap();
             let handle = window.handle.clone();
             if let Some(parent) = self.parent {
                 let mut state = parent.state.write().unwrap();
-                state.children.push(handle.clone());
+                state.push_child(&handle);
             }
-            if self.visibility {
-                window.handle.show();
+            if self.visibility && self.show_after_first_draw {
+                handle.show_after_first_draw();
+            } else if self.visibility {
+                if self.maximized {
+                    ShowWindow(hwnd, SW_SHOWMAXIMIZED);
+                } else if self.minimized {
+                    ShowWindow(hwnd, SW_SHOWMINIMIZED);
+                } else {
+                    window.handle.show();
+                }
             }
             if self.accept_drag_files {
                 DragAcceptFiles(hwnd, true);
             }
-            if let Some(icon) = self.icon {
-                let big = load_icon(&icon, hinst);
-                let small = load_small_icon(&icon, hinst);
+            if let Some((big, small)) = icons {
                 SendMessageW(
                     HWND(handle.raw_handle() as _),
                     WM_SETICON,
@@ -394,7 +785,7 @@ where
             }
             #[cfg(feature = "raw_input")]
             raw_input::register_devices(&window.handle, self.raw_input_window_state);
-            push_window(hwnd, window);
+            push_window(hwnd);
             Ok(handle)
         }
     }
@@ -410,6 +801,8 @@ pub struct InnerWindowBuilder<W = (), P = (), S = ()> {
     visible_ime_candidate_window: bool,
     accept_drag_files: bool,
     cursor: Cursor,
+    no_erase_background: bool,
+    enabled_ime: Option<bool>,
     #[cfg(feature = "raw_input")]
     raw_input_window_state: raw_input::WindowState,
 }
@@ -426,6 +819,8 @@ impl InnerWindowBuilder<(), (), ()> {
             visible_ime_candidate_window: true,
             accept_drag_files: false,
             cursor: Cursor::Arrow,
+            no_erase_background: false,
+            enabled_ime: None,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: raw_input::WindowState::Foreground,
         }
@@ -443,6 +838,8 @@ impl<W, P, S> InnerWindowBuilder<W, P, S> {
             visible_ime_candidate_window: self.visible_ime_candidate_window,
             accept_drag_files: self.accept_drag_files,
             cursor: self.cursor,
+            no_erase_background: self.no_erase_background,
+            enabled_ime: self.enabled_ime,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: self.raw_input_window_state,
         }
@@ -458,6 +855,8 @@ impl<W, P, S> InnerWindowBuilder<W, P, S> {
             visible_ime_candidate_window: self.visible_ime_candidate_window,
             accept_drag_files: self.accept_drag_files,
             cursor: self.cursor,
+            no_erase_background: self.no_erase_background,
+            enabled_ime: self.enabled_ime,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: self.raw_input_window_state,
         }
@@ -473,6 +872,8 @@ impl<W, P, S> InnerWindowBuilder<W, P, S> {
             visible_ime_candidate_window: self.visible_ime_candidate_window,
             accept_drag_files: self.accept_drag_files,
             cursor: self.cursor,
+            no_erase_background: self.no_erase_background,
+            enabled_ime: self.enabled_ime,
             #[cfg(feature = "raw_input")]
             raw_input_window_state: self.raw_input_window_state,
         }
@@ -487,6 +888,27 @@ impl<W, P, S> InnerWindowBuilder<W, P, S> {
         self.accept_drag_files = true;
         self
     }
+
+    pub fn cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Does not erase the client area on `WM_ERASEBKGND`.
+    ///
+    /// This is for windows whose whole client area is repainted by a renderer every frame.
+    pub fn no_erase_background(mut self, enable: bool) -> Self {
+        self.no_erase_background = enable;
+        self
+    }
+
+    /// Enables or disables IME independently of the parent.
+    ///
+    /// If this is not specified, the window inherits the state of the parent when it is built.
+    pub fn ime(mut self, enable: bool) -> Self {
+        self.enabled_ime = Some(enable);
+        self
+    }
 }
 
 impl<P, S> InnerWindowBuilder<Window, P, S>
@@ -495,15 +917,44 @@ where
     S: ToPhysicalSize<u32>,
 {
     pub fn build(self) -> Result<Window, ApiError> {
+        trace_span!("InnerWindowBuilder::build");
         unsafe {
             let dpi = self.parent.dpi();
             let position = self.position.to_physical(dpi as i32);
             let size = self.size.to_physical(dpi);
             let rc = adjust_window_rect(size, WS_CHILD.0, 0, dpi);
+            let enabled_ime = self
+                .enabled_ime
+                .unwrap_or_else(|| self.parent.is_enabled_ime());
             let hinst = GetModuleHandleW(PWSTR::NULL);
+            let mut params: CreateParams = Some((
+                WindowState {
+                    title: String::new(),
+                    set_position: (position.x, position.y),
+                    set_inner_size: size,
+                    ime_position: PhysicalPosition::new(0, 0),
+                    children: vec![],
+                    background: if self.no_erase_background {
+                        Background::None
+                    } else {
+                        Background::default()
+                    },
+                    session: None,
+                    focused_child: None,
+                    #[cfg(feature = "accessibility")]
+                    accessible_tree: Default::default(),
+                },
+                WindowFlags::new(
+                    WS_CHILD.0,
+                    self.cursor,
+                    enabled_ime,
+                    self.visible_ime_composition_window,
+                    self.visible_ime_candidate_window,
+                ),
+            ));
             let hwnd = CreateWindowExW(
                 WINDOW_EX_STYLE(0),
-                WINDOW_CLASS_NAME,
+                class_name_ptr(class_index(false, true)),
                 PWSTR::NULL,
                 WS_CHILD,
                 position.x,
@@ -513,70 +964,481 @@ where
                 HWND(self.parent.raw_handle() as _),
                 HMENU::NULL,
                 hinst,
-                std::ptr::null_mut(),
+                &mut params as *mut CreateParams as _,
             );
             if hwnd == HWND::NULL {
-                return Err(ApiError::new());
+                return Err(ApiError::new().with_function("CreateWindowExW"));
             }
-            let window = LocalWindow::new(
-                hwnd,
+            let window = LocalWindow::get(hwnd).unwrap();
+            let handle = window.handle.clone();
+            if self.visibility {
+                window.handle.show();
+            }
+            if self.accept_drag_files {
+                DragAcceptFiles(hwnd, true);
+            }
+            #[cfg(feature = "raw_input")]
+            raw_input::register_devices(&window.handle, self.raw_input_window_state);
+            push_window(hwnd);
+            Ok(handle)
+        }
+    }
+}
+
+/// The object to build a popup window such as tooltips, autocompletion lists and context UI.
+///
+/// The popup is placed at the right bottom of `anchor`. It is flipped above `anchor` or shifted
+/// so that it fits in the work area of the monitor.
+pub struct PopupWindowBuilder<S = LogicalSize<u32>> {
+    owner: Option<Window>,
+    anchor: ScreenPosition,
+    size: S,
+    visibility: bool,
+    topmost: bool,
+    no_activate: bool,
+    cursor: Cursor,
+    background: Background,
+}
+
+impl PopupWindowBuilder<()> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PopupWindowBuilder<LogicalSize<u32>> {
+        PopupWindowBuilder {
+            owner: None,
+            anchor: ScreenPosition::new(0, 0),
+            size: LogicalSize::new(200, 100),
+            visibility: true,
+            topmost: true,
+            no_activate: true,
+            cursor: Cursor::default(),
+            background: Background::default(),
+        }
+    }
+}
+
+impl<S> PopupWindowBuilder<S> {
+    /// The popup is always above the owner and is destroyed with the owner.
+    pub fn owner(mut self, owner: &Window) -> Self {
+        self.owner = Some(owner.clone());
+        self
+    }
+
+    /// The screen point such as the cursor position or the caret position.
+    pub fn anchor(mut self, anchor: impl Into<ScreenPosition>) -> Self {
+        self.anchor = anchor.into();
+        self
+    }
+
+    pub fn size<T>(self, size: T) -> PopupWindowBuilder<T> {
+        PopupWindowBuilder {
+            owner: self.owner,
+            anchor: self.anchor,
+            size,
+            visibility: self.visibility,
+            topmost: self.topmost,
+            no_activate: self.no_activate,
+            cursor: self.cursor,
+            background: self.background,
+        }
+    }
+
+    pub fn visible(mut self, visibility: bool) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Adds `WS_EX_TOPMOST`. The default is `true`.
+    pub fn topmost(mut self, enable: bool) -> Self {
+        self.topmost = enable;
+        self
+    }
+
+    /// Adds `WS_EX_NOACTIVATE` so that the popup does not take the focus from the owner.
+    /// The default is `true`.
+    pub fn no_activate(mut self, enable: bool) -> Self {
+        self.no_activate = enable;
+        self
+    }
+
+    pub fn cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+/// Places the rect at the right bottom of `anchor` in `work`.
+fn popup_position(anchor: ScreenPosition, width: i32, height: i32, work: &RECT) -> (i32, i32) {
+    let mut x = anchor.x;
+    let mut y = anchor.y;
+    if x + width > work.right {
+        x = work.right - width;
+    }
+    if y + height > work.bottom {
+        y = anchor.y - height;
+    }
+    (x.max(work.left), y.max(work.top))
+}
+
+impl<S> PopupWindowBuilder<S>
+where
+    S: ToPhysicalSize<u32>,
+{
+    pub fn build(self) -> Result<Window, ApiError> {
+        trace_span!("PopupWindowBuilder::build");
+        if is_context_null() {
+            panic!("The window can be created after run");
+        }
+        unsafe {
+            let dpi = get_dpi_from_point(self.anchor);
+            let size = self.size.to_physical(dpi);
+            let rc = adjust_window_rect(size, WS_POPUP.0, 0, dpi);
+            let width = rc.right - rc.left;
+            let height = rc.bottom - rc.top;
+            let mut info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as _,
+                ..Default::default()
+            };
+            GetMonitorInfoW(
+                MonitorFromPoint(
+                    POINT {
+                        x: self.anchor.x,
+                        y: self.anchor.y,
+                    },
+                    MONITOR_DEFAULTTONEAREST,
+                ),
+                &mut info,
+            );
+            let (x, y) = popup_position(self.anchor, width, height, &info.rcWork);
+            let mut ex_style = WS_EX_TOOLWINDOW.0;
+            if self.topmost {
+                ex_style |= WS_EX_TOPMOST.0;
+            }
+            if self.no_activate {
+                ex_style |= WS_EX_NOACTIVATE.0;
+            }
+            let hinst = GetModuleHandleW(PWSTR::NULL);
+            let mut params: CreateParams = Some((
                 WindowState {
                     title: String::new(),
-                    style: WS_CHILD.0,
-                    set_position: (position.x, position.y),
+                    set_position: (x, y),
                     set_inner_size: size,
-                    enabled_ime: self.parent.is_enabled_ime(),
-                    visible_ime_composition_window: self.visible_ime_composition_window,
-                    visible_ime_candidate_window: self.visible_ime_candidate_window,
                     ime_position: PhysicalPosition::new(0, 0),
                     children: vec![],
-                    cursor: self.cursor,
-                    closed: false,
+                    background: self.background,
+                    session: None,
+                    focused_child: None,
+                    #[cfg(feature = "accessibility")]
+                    accessible_tree: Default::default(),
                 },
+                WindowFlags::new(WS_POPUP.0, self.cursor, false, true, true),
+            ));
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE(ex_style),
+                class_name_ptr(class_index(false, true)),
+                PWSTR::NULL,
+                WS_POPUP,
+                x,
+                y,
+                width,
+                height,
+                self.owner
+                    .as_ref()
+                    .map_or(HWND::NULL, |owner| HWND(owner.raw_handle() as _)),
+                HMENU::NULL,
+                hinst,
+                &mut params as *mut CreateParams as _,
             );
-            let handle = window.handle.clone();
+            if hwnd == HWND::NULL {
+                return Err(ApiError::new().with_function("CreateWindowExW"));
+            }
+            let handle = LocalWindow::get(hwnd).unwrap().handle.clone();
+            if let Some(owner) = self.owner {
+                let mut state = owner.state.write().unwrap();
+                state.push_child(&handle);
+            }
             if self.visibility {
-                window.handle.show();
+                ShowWindow(
+                    hwnd,
+                    if self.no_activate {
+                        SW_SHOWNOACTIVATE
+                    } else {
+                        SW_SHOW
+                    },
+                );
             }
-            if self.accept_drag_files {
-                DragAcceptFiles(hwnd, true);
+            push_window(hwnd);
+            Ok(handle)
+        }
+    }
+}
+
+/// The object to build a message-only window.
+///
+/// A message-only window is never shown, and receives messages such as `Window::post_message`, hotkeys
+/// and device changes.
+/// The event loop keeps running while message-only windows exist, so they are suitable for tray-only utilities.
+pub struct MessageWindowBuilder {
+    title: String,
+}
+
+impl MessageWindowBuilder {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+        }
+    }
+
+    /// The title can be used to find the window from other processes.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn build(self) -> Result<Window, ApiError> {
+        trace_span!("MessageWindowBuilder::build");
+        if is_context_null() {
+            panic!("The window can be created after run");
+        }
+        unsafe {
+            let hinst = GetModuleHandleW(PWSTR::NULL);
+            let mut params: CreateParams = Some((
+                WindowState {
+                    title: self.title.clone(),
+                    set_position: (0, 0),
+                    set_inner_size: PhysicalSize::new(0, 0),
+                    ime_position: PhysicalPosition::new(0, 0),
+                    children: vec![],
+                    background: Background::None,
+                    session: None,
+                    focused_child: None,
+                    #[cfg(feature = "accessibility")]
+                    accessible_tree: Default::default(),
+                },
+                WindowFlags::new(0, Cursor::default(), false, false, false),
+            ));
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                class_name_ptr(class_index(false, true)),
+                self.title.as_str(),
+                WINDOW_STYLE(0),
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                HMENU::NULL,
+                hinst,
+                &mut params as *mut CreateParams as _,
+            );
+            if hwnd == HWND::NULL {
+                return Err(ApiError::new().with_function("CreateWindowExW"));
             }
-            #[cfg(feature = "raw_input")]
-            raw_input::register_devices(&window.handle, self.raw_input_window_state);
-            push_window(hwnd, window);
+            let handle = LocalWindow::get(hwnd).unwrap().handle.clone();
+            push_window(hwnd);
             Ok(handle)
         }
     }
 }
 
+impl Default for MessageWindowBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub(crate) struct WindowState {
     pub title: String,
-    pub style: u32,
     pub set_position: (i32, i32),
     pub set_inner_size: PhysicalSize<u32>,
-    pub enabled_ime: bool,
+    pub ime_position: PhysicalPosition<i32>,
+    pub children: Vec<WeakWindow>,
+    pub background: Background,
+    pub session: Option<WindowSession>,
+    /// The inner window that had the focus last, restored when the window is activated.
+    pub focused_child: Option<WeakWindow>,
+    #[cfg(feature = "accessibility")]
+    pub accessible_tree: accessibility::SharedTree,
+}
+
+impl WindowState {
+    /// Adds the child and drops the children that have been destroyed.
+    pub fn push_child(&mut self, child: &Window) {
+        self.children
+            .retain(|c| c.upgrade().map_or(false, |c| !c.is_closed()));
+        self.children.push(child.downgrade());
+    }
+}
+
+/// A reference to a window that does not keep the state of the window alive.
+pub(crate) struct WeakWindow {
+    hwnd: WindowHandle,
+    state: Weak<RwLock<WindowState>>,
+    flags: Weak<WindowFlags>,
+}
+
+impl WeakWindow {
+    pub fn upgrade(&self) -> Option<Window> {
+        Some(Window {
+            hwnd: self.hwnd.clone(),
+            state: self.state.upgrade()?,
+            flags: self.flags.upgrade()?,
+        })
+    }
+}
+
+/// The data of a window that is read in the message loop without locking `WindowState`.
+pub(crate) struct WindowFlags {
     pub visible_ime_composition_window: bool,
     pub visible_ime_candidate_window: bool,
-    pub ime_position: PhysicalPosition<i32>,
-    pub children: Vec<Window>,
-    pub closed: bool,
-    pub cursor: Cursor,
+    pub closed: AtomicBool,
+    pub enabled_ime: AtomicBool,
+    pub style: AtomicU32,
+    pub aero_snap_borderless: AtomicBool,
+    pub minimize_to_tray: AtomicBool,
+    pub show_after_first_draw: AtomicBool,
+    pub relative_mouse_mode: AtomicBool,
+    #[cfg(feature = "block_shortcuts")]
+    pub block_shortcuts: AtomicBool,
+    pub tab_navigation: AtomicBool,
+    cursor: AtomicU8,
+    hit_test: AtomicUsize,
 }
 
-#[derive(Clone)]
+impl WindowFlags {
+    fn new(
+        style: u32,
+        cursor: Cursor,
+        enabled_ime: bool,
+        visible_ime_composition_window: bool,
+        visible_ime_candidate_window: bool,
+    ) -> Self {
+        Self {
+            visible_ime_composition_window,
+            visible_ime_candidate_window,
+            closed: AtomicBool::new(false),
+            enabled_ime: AtomicBool::new(enabled_ime),
+            style: AtomicU32::new(style),
+            aero_snap_borderless: AtomicBool::new(false),
+            cursor: AtomicU8::new(cursor.index()),
+            hit_test: AtomicUsize::new(0),
+            minimize_to_tray: AtomicBool::new(false),
+            show_after_first_draw: AtomicBool::new(false),
+            relative_mouse_mode: AtomicBool::new(false),
+            #[cfg(feature = "block_shortcuts")]
+            block_shortcuts: AtomicBool::new(false),
+            tab_navigation: AtomicBool::new(false),
+        }
+    }
+
+    pub fn cursor(&self) -> Cursor {
+        Cursor::from_index(self.cursor.load(Ordering::Relaxed))
+    }
+
+    pub fn set_cursor(&self, cursor: Cursor) {
+        self.cursor.store(cursor.index(), Ordering::Relaxed);
+    }
+
+    pub fn hit_test(&self) -> Option<HitTestFn> {
+        match self.hit_test.load(Ordering::Acquire) {
+            0 => None,
+            // The value is stored by `set_hit_test` and a function pointer is never null.
+            f => Some(unsafe { std::mem::transmute::<usize, HitTestFn>(f) }),
+        }
+    }
+
+    pub fn set_hit_test(&self, f: Option<HitTestFn>) {
+        self.hit_test
+            .store(f.map_or(0, |f| f as usize), Ordering::Release);
+    }
+}
+
+type CreateParams = Option<(WindowState, WindowFlags)>;
+
+/// The thread-local part of a window that is attached to `GWLP_USERDATA`.
 pub(crate) struct LocalWindow {
     pub handle: Window,
-    pub ime_context: Rc<RefCell<ime::ImmContext>>,
+    pub ime_context: RefCell<ime::ImmContext>,
+    pub tray_icon: RefCell<Option<TrayIcon>>,
+    pub visibility: Cell<VisibilityState>,
+    pub _power_notification: Option<SuspendResumeNotification>,
+    pub _device_notification: Option<DeviceNotification>,
+    #[cfg(feature = "d2d")]
+    pub d2d_target: d2d::RenderTarget,
 }
 
 impl LocalWindow {
-    pub(crate) fn new(hwnd: HWND, state: WindowState) -> Self {
+    fn new(hwnd: HWND, state: WindowState, flags: WindowFlags) -> Self {
+        let top_level = flags.style.load(Ordering::Relaxed) & WS_CHILD.0 == 0;
+        let enabled_ime = flags.enabled_ime.load(Ordering::Relaxed);
+        let power_notification = if top_level {
+            SuspendResumeNotification::new(hwnd)
+        } else {
+            None
+        };
+        let device_notification = if top_level {
+            DeviceNotification::new(hwnd)
+        } else {
+            None
+        };
         Self {
             handle: Window {
                 hwnd: WindowHandle(hwnd),
                 state: Arc::new(RwLock::new(state)),
+                flags: Arc::new(flags),
             },
-            ime_context: Rc::new(RefCell::new(ime::ImmContext::new(hwnd))),
+            ime_context: RefCell::new(ime::ImmContext::new(hwnd, enabled_ime)),
+            tray_icon: RefCell::new(None),
+            visibility: Cell::new(VisibilityState::Hidden),
+            _power_notification: power_notification,
+            _device_notification: device_notification,
+            #[cfg(feature = "d2d")]
+            d2d_target: Default::default(),
+        }
+    }
+
+    /// Attaches a new `LocalWindow` to the window in `WM_NCCREATE`.
+    ///
+    /// `params` is `lpCreateParams` that points `CreateParams` passed to `CreateWindowExW`.
+    pub(crate) unsafe fn attach(hwnd: HWND, params: *mut std::ffi::c_void) {
+        if params.is_null() {
+            return;
+        }
+        let params = &mut *(params as *mut CreateParams);
+        if let Some((state, flags)) = params.take() {
+            let window = Rc::new(Self::new(hwnd, state, flags));
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, Rc::into_raw(window) as _);
+        }
+    }
+
+    /// Detaches and releases the `LocalWindow` in `WM_NCDESTROY`.
+    pub(crate) unsafe fn detach(hwnd: HWND) {
+        let p = SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0) as *const LocalWindow;
+        if !p.is_null() {
+            drop(Rc::from_raw(p));
+        }
+    }
+
+    /// Returns the attached `LocalWindow`.
+    ///
+    /// The returned `Rc` keeps it alive even if the window is destroyed while handling a message.
+    /// Returns `None` for windows that wita has not created such as dialogs and common controls,
+    /// because their user data is not a `LocalWindow`.
+    pub(crate) fn get(hwnd: HWND) -> Option<Rc<LocalWindow>> {
+        if !is_wita_window(hwnd) {
+            return None;
+        }
+        unsafe {
+            let p = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const LocalWindow;
+            if p.is_null() {
+                return None;
+            }
+            Rc::increment_strong_count(p);
+            Some(Rc::from_raw(p))
         }
     }
 }
@@ -586,6 +1448,7 @@ impl LocalWindow {
 pub struct Window {
     pub(crate) hwnd: WindowHandle,
     pub(crate) state: Arc<RwLock<WindowState>>,
+    pub(crate) flags: Arc<WindowFlags>,
 }
 
 impl Window {
@@ -594,17 +1457,40 @@ impl Window {
         state.title.clone()
     }
 
-    pub fn set_title(&self, title: impl AsRef<str>) {
-        let mut state = self.state.write().unwrap();
-        state.title = title.as_ref().to_string();
-        unsafe {
-            PostMessageW(
-                self.hwnd.0,
-                WM_USER,
-                WPARAM(UserMessage::SetTitle as _),
-                LPARAM(0),
+    pub(crate) fn downgrade(&self) -> WeakWindow {
+        WeakWindow {
+            hwnd: self.hwnd.clone(),
+            state: Arc::downgrade(&self.state),
+            flags: Arc::downgrade(&self.flags),
+        }
+    }
+
+    fn post_user_message(&self, msg: UserMessage, lparam: isize) -> Result<(), ApiError> {
+        if self.is_closed() {
+            return Err(
+                ApiError::from_win32(ERROR_INVALID_WINDOW_HANDLE).with_function("PostMessageW")
             );
         }
+        unsafe {
+            if PostMessageW(self.hwnd.0, WM_USER, WPARAM(msg as _), LPARAM(lparam)).as_bool() {
+                Ok(())
+            } else {
+                Err(ApiError::new().with_function("PostMessageW"))
+            }
+        }
+    }
+
+    pub fn set_title(&self, title: impl AsRef<str>) {
+        self.try_set_title(title).ok();
+    }
+
+    /// Sets the title and returns an error if the window has been destroyed.
+    pub fn try_set_title(&self, title: impl AsRef<str>) -> Result<(), ApiError> {
+        {
+            let mut state = self.state.write().unwrap();
+            state.title = title.as_ref().to_string();
+        }
+        self.post_user_message(UserMessage::SetTitle, 0)
     }
 
     pub fn position(&self) -> ScreenPosition {
@@ -616,16 +1502,16 @@ impl Window {
     }
 
     pub fn set_position(&self, position: ScreenPosition) {
-        unsafe {
+        self.try_set_position(position).ok();
+    }
+
+    /// Sets the position and returns an error if the window has been destroyed.
+    pub fn try_set_position(&self, position: ScreenPosition) -> Result<(), ApiError> {
+        {
             let mut state = self.state.write().unwrap();
             state.set_position = (position.x, position.y);
-            PostMessageW(
-                self.hwnd.0,
-                WM_USER,
-                WPARAM(UserMessage::SetPosition as _),
-                LPARAM(0),
-            );
         }
+        self.post_user_message(UserMessage::SetPosition, 0)
     }
 
     pub fn inner_size(&self) -> PhysicalSize<u32> {
@@ -637,24 +1523,24 @@ impl Window {
     }
 
     pub fn set_inner_size(&self, size: impl ToPhysicalSize<u32>) {
-        unsafe {
+        self.try_set_inner_size(size).ok();
+    }
+
+    /// Sets the inner size and returns an error if the window has been destroyed.
+    pub fn try_set_inner_size(&self, size: impl ToPhysicalSize<u32>) -> Result<(), ApiError> {
+        {
             let mut state = self.state.write().unwrap();
             state.set_inner_size = size.to_physical(self.dpi());
-            PostMessageW(
-                self.hwnd.0,
-                WM_USER,
-                WPARAM(UserMessage::SetInnerSize as _),
-                LPARAM(0),
-            );
         }
+        self.post_user_message(UserMessage::SetInnerSize, 0)
     }
 
     pub fn dpi(&self) -> u32 {
-        unsafe { GetDpiForWindow(self.hwnd.0) }
+        get_dpi_for_window(self.hwnd.0)
     }
 
     pub fn scale_factor(&self) -> f32 {
-        unsafe { GetDpiForWindow(self.hwnd.0) as f32 / DEFAULT_DPI as f32 }
+        get_dpi_for_window(self.hwnd.0) as f32 / DEFAULT_DPI as f32
     }
 
     pub fn show(&self) {
@@ -669,6 +1555,29 @@ impl Window {
         }
     }
 
+    pub fn is_visible(&self) -> bool {
+        unsafe { IsWindowVisible(self.hwnd.0).as_bool() }
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        unsafe { IsZoomed(self.hwnd.0).as_bool() }
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.hwnd.0).as_bool() }
+    }
+
+    /// Shows the window after `EventHandler::draw` has been called for it.
+    ///
+    /// `draw` is called even though the window is hidden, and the window is shown only if `draw` returns
+    /// without panicking.
+    pub fn show_after_first_draw(&self) {
+        self.flags
+            .show_after_first_draw
+            .store(true, Ordering::Relaxed);
+        self.post_user_message(UserMessage::FirstDraw, 0).ok();
+    }
+
     pub fn redraw(&self) {
         unsafe {
             RedrawWindow(self.hwnd.0, std::ptr::null(), HRGN::NULL, RDW_INTERNALPAINT);
@@ -676,43 +1585,59 @@ impl Window {
     }
 
     pub fn is_closed(&self) -> bool {
-        let state = self.state.read().unwrap();
-        state.closed
+        self.flags.closed.load(Ordering::Acquire)
     }
 
     pub fn close(&self) {
+        self.try_close().ok();
+    }
+
+    /// Requests to close the window and returns an error if the window has been destroyed.
+    pub fn try_close(&self) -> Result<(), ApiError> {
+        if self.is_closed() {
+            return Err(
+                ApiError::from_win32(ERROR_INVALID_WINDOW_HANDLE).with_function("PostMessageW")
+            );
+        }
         unsafe {
-            if !self.is_closed() {
-                PostMessageW(self.hwnd.0, WM_CLOSE, WPARAM(0), LPARAM(0));
+            if PostMessageW(self.hwnd.0, WM_CLOSE, WPARAM(0), LPARAM(0)).as_bool() {
+                Ok(())
+            } else {
+                Err(ApiError::new().with_function("PostMessageW"))
             }
         }
     }
 
+    /// Destroys the window without `WM_CLOSE`.
+    ///
+    /// The window is destroyed asynchronously.
+    pub fn destroy(&self) {
+        self.try_destroy().ok();
+    }
+
+    /// Destroys the window and returns an error if the window has already been destroyed.
+    pub fn try_destroy(&self) -> Result<(), ApiError> {
+        self.post_user_message(UserMessage::Destroy, 0)
+    }
+
     pub fn ime_position(&self) -> PhysicalPosition<i32> {
         let state = self.state.read().unwrap();
         PhysicalPosition::new(state.ime_position.x, state.ime_position.y)
     }
 
     pub fn ime(&self, enable: bool) {
-        unsafe {
-            if enable {
-                PostMessageW(
-                    self.hwnd.0,
-                    WM_USER,
-                    WPARAM(UserMessage::EnableIme as _),
-                    LPARAM(0),
-                );
-            } else {
-                PostMessageW(
-                    self.hwnd.0,
-                    WM_USER,
-                    WPARAM(UserMessage::DisableIme as _),
-                    LPARAM(0),
-                );
-            }
-        }
-        let mut state = self.state.write().unwrap();
-        state.enabled_ime = enable;
+        self.try_ime(enable).ok();
+    }
+
+    /// Enables or disables IME and returns an error if the window has been destroyed.
+    pub fn try_ime(&self, enable: bool) -> Result<(), ApiError> {
+        let ret = if enable {
+            self.post_user_message(UserMessage::EnableIme, 0)
+        } else {
+            self.post_user_message(UserMessage::DisableIme, 0)
+        };
+        self.flags.enabled_ime.store(enable, Ordering::Relaxed);
+        ret
     }
 
     pub fn set_ime_position(&self, position: impl ToPhysicalPosition<i32>) {
@@ -721,55 +1646,496 @@ impl Window {
         state.ime_position.x = position.x;
         state.ime_position.y = position.y;
         let imc = ime::Imc::get(self.hwnd.0);
-        if state.visible_ime_composition_window {
+        if self.flags.visible_ime_composition_window {
             imc.set_composition_window_position(state.ime_position);
         }
-        if state.visible_ime_candidate_window {
+        if self.flags.visible_ime_candidate_window {
             imc.set_candidate_window_position(
                 state.ime_position,
-                state.visible_ime_composition_window,
+                self.flags.visible_ime_composition_window,
             );
         }
     }
 
     pub fn is_enabled_ime(&self) -> bool {
-        let state = self.state.read().unwrap();
-        state.enabled_ime
+        self.flags.enabled_ime.load(Ordering::Relaxed)
+    }
+
+    /// Returns the current composition string of IME, such as to redraw it after a resize.
+    ///
+    /// This returns `None` on threads other than the thread that runs the event loop.
+    pub fn ime_composition(&self) -> Option<ime::Composition> {
+        ime::Imc::get(self.hwnd.0).get_composition()
+    }
+
+    /// Returns `true` if IME has a composition string.
+    ///
+    /// This returns `false` on threads other than the thread that runs the event loop.
+    pub fn is_ime_composing(&self) -> bool {
+        self.ime_composition().is_some()
     }
 
     pub fn style(&self) -> WindowStyle {
-        let state = self.state.read().unwrap();
-        WindowStyle(state.style)
+        WindowStyle(self.flags.style.load(Ordering::Relaxed))
     }
 
     pub fn set_style(&self, style: impl Style) {
-        unsafe {
+        self.try_set_style(style).ok();
+    }
+
+    /// Sets the style and returns an error if the window has been destroyed.
+    pub fn try_set_style(&self, style: impl Style) -> Result<(), ApiError> {
+        self.flags.style.store(style.value(), Ordering::Relaxed);
+        self.flags
+            .aero_snap_borderless
+            .store(style.is_aero_snap_borderless(), Ordering::Relaxed);
+        self.post_user_message(UserMessage::SetStyle, 0)
+    }
+
+    fn set_style_bits(&self, bits: u32, enable: bool) {
+        if enable {
+            self.flags.style.fetch_or(bits, Ordering::Relaxed);
+        } else {
+            self.flags.style.fetch_and(!bits, Ordering::Relaxed);
+        }
+        self.post_user_message(UserMessage::SetStyleBits, bits as _)
+            .ok();
+    }
+
+    /// Adds or removes `WS_THICKFRAME` from the current style.
+    pub fn set_resizable(&self, resizable: bool) {
+        self.set_style_bits(WS_THICKFRAME.0, resizable);
+    }
+
+    /// Adds or removes `WS_MINIMIZEBOX` from the current style.
+    pub fn set_has_minimize_box(&self, has_minimize_box: bool) {
+        self.set_style_bits(WS_MINIMIZEBOX.0, has_minimize_box);
+    }
+
+    /// Adds or removes `WS_MAXIMIZEBOX` from the current style.
+    pub fn set_has_maximize_box(&self, has_maximize_box: bool) {
+        self.set_style_bits(WS_MAXIMIZEBOX.0, has_maximize_box);
+    }
+
+    /// Takes a snapshot of the placement, the style, the monitor and the fullscreen state.
+    pub fn session(&self) -> WindowSession {
+        config::get_session(self.hwnd.0, self.style())
+    }
+
+    /// Restores the layout from `session`. This shows the window.
+    pub fn restore_session(&self, session: &WindowSession) {
+        self.try_restore_session(session).ok();
+    }
+
+    /// Restores the layout from `session` and returns an error if the window has been destroyed.
+    pub fn try_restore_session(&self, session: &WindowSession) -> Result<(), ApiError> {
+        {
             let mut state = self.state.write().unwrap();
-            state.style = style.value();
-            PostMessageW(
+            state.session = Some(session.clone());
+        }
+        self.flags
+            .style
+            .store(session.style.value(), Ordering::Relaxed);
+        self.post_user_message(UserMessage::RestoreSession, 0)
+    }
+
+    /// Sets the range, the page size and the position of the scroll bar.
+    ///
+    /// The window must be created with `WindowStyle::has_vertical_scroll_bar`
+    /// or `WindowStyle::has_horizontal_scroll_bar`.
+    pub fn set_scroll_info(&self, bar: ScrollBar, info: ScrollInfo) {
+        scroll::set_scroll_info(self.hwnd.0, bar, &info);
+    }
+
+    pub fn scroll_info(&self, bar: ScrollBar) -> Option<ScrollInfo> {
+        scroll::get_scroll_info(self.hwnd.0, bar).map(|si| ScrollInfo {
+            min: si.nMin,
+            max: si.nMax,
+            page: si.nPage,
+            position: si.nPos,
+        })
+    }
+
+    /// Returns the position of the scroll bar. While the thumb is dragged, this is the tracking position.
+    pub fn scroll_position(&self, bar: ScrollBar) -> i32 {
+        scroll::get_scroll_info(self.hwnd.0, bar).map_or(0, |si| si.nPos)
+    }
+
+    /// Moves the contents of the client area by `delta` and invalidates only the exposed area.
+    ///
+    /// `clip` is the position and the size of the area to be affected, and `None` means the whole client area.
+    /// This should be called on the thread that runs the event loop such as in `EventHandler::scrolled`.
+    pub fn scroll(
+        &self,
+        delta: impl Into<PhysicalPosition<i32>>,
+        clip: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    ) -> Result<(), ApiError> {
+        let delta = delta.into();
+        let clip = clip.map(|(position, size)| RECT {
+            left: position.x,
+            top: position.y,
+            right: position.x + size.width as i32,
+            bottom: position.y + size.height as i32,
+        });
+        let clip_ptr = clip.as_ref().map_or(std::ptr::null(), |rc| rc as *const _);
+        unsafe {
+            let ret = ScrollWindowEx(
                 self.hwnd.0,
-                WM_USER,
-                WPARAM(UserMessage::SetStyle as _),
-                LPARAM(0),
+                delta.x,
+                delta.y,
+                clip_ptr,
+                clip_ptr,
+                HRGN::NULL,
+                std::ptr::null_mut(),
+                SW_INVALIDATE,
             );
+            if ret == 0 {
+                return Err(ApiError::new().with_function("ScrollWindowEx"));
+            }
         }
+        Ok(())
     }
 
     pub fn accept_drag_files(&self, enabled: bool) {
+        self.try_accept_drag_files(enabled).ok();
+    }
+
+    /// Accepts or rejects dropped files and returns an error if the window has been destroyed.
+    pub fn try_accept_drag_files(&self, enabled: bool) -> Result<(), ApiError> {
+        self.post_user_message(UserMessage::AcceptDragFiles, if enabled { 1 } else { 0 })
+    }
+
+    /// Sets the cursor that is shown while the cursor is in the client area of the window.
+    pub fn set_cursor(&self, cursor: Cursor) {
+        self.flags.set_cursor(cursor);
         unsafe {
-            PostMessageW(
-                self.hwnd.0,
-                WM_USER,
-                WPARAM(UserMessage::AcceptDragFiles as _),
-                LPARAM(if enabled { 1 } else { 0 }),
+            // The cursor does not change until the next `WM_SETCURSOR` without this.
+            let mut pos = POINT::default();
+            GetCursorPos(&mut pos);
+            if WindowFromPoint(pos) == self.hwnd.0 {
+                cursor.set();
+            }
+        }
+    }
+
+    /// Hides and confines the cursor and delivers the mouse movement by `EventHandler::mouse_delta`.
+    ///
+    /// The deltas come from raw input if `"raw_input"` feature is enabled and the raw input is registered for this window.
+    /// Otherwise they are measured by moving the cursor back to the center of the client area.
+    pub fn set_relative_mouse_mode(&self, enable: bool) {
+        if self
+            .flags
+            .relative_mouse_mode
+            .swap(enable, Ordering::Relaxed)
+            != enable
+        {
+            self.post_user_message(UserMessage::SetRelativeMouseMode, enable as _)
+                .ok();
+        }
+    }
+
+    pub fn is_relative_mouse_mode(&self) -> bool {
+        self.flags.relative_mouse_mode.load(Ordering::Relaxed)
+    }
+
+    /// Moves the keyboard focus to the next inner window of this window.
+    ///
+    /// The order is the order of creation, and hidden or disabled inner windows are skipped.
+    pub fn focus_next_child(&self) {
+        self.post_user_message(UserMessage::FocusChild, 1).ok();
+    }
+
+    /// Moves the keyboard focus to the previous inner window of this window.
+    pub fn focus_prev_child(&self) {
+        self.post_user_message(UserMessage::FocusChild, 0).ok();
+    }
+
+    /// Moves the focus among the inner windows with Tab and Shift+Tab.
+    ///
+    /// The Tab key that moves the focus is not passed to `EventHandler::key_input`.
+    pub fn set_tab_navigation(&self, enable: bool) {
+        self.flags.tab_navigation.store(enable, Ordering::Relaxed);
+    }
+
+    pub fn is_tab_navigation(&self) -> bool {
+        self.flags.tab_navigation.load(Ordering::Relaxed)
+    }
+
+    /// Blocks system shortcuts such as the Windows key and Alt+Tab while the window has focus.
+    ///
+    /// This is for kiosk applications. Ctrl+Alt+Delete cannot be blocked.
+    ///
+    /// To use, specify `"block_shortcuts"` feature.
+    #[cfg(feature = "block_shortcuts")]
+    pub fn set_block_system_shortcuts(&self, block: bool) {
+        self.flags.block_shortcuts.store(block, Ordering::Relaxed);
+        self.post_user_message(UserMessage::BlockShortcuts, block as _)
+            .ok();
+    }
+
+    /// To use, specify `"block_shortcuts"` feature.
+    #[cfg(feature = "block_shortcuts")]
+    pub fn is_blocking_system_shortcuts(&self) -> bool {
+        self.flags.block_shortcuts.load(Ordering::Relaxed)
+    }
+
+    /// Returns up to 64 points that the mouse has passed through, oldest first.
+    ///
+    /// This must be called in `EventHandler::cursor_moved`, and the last point is the current position.
+    /// The points can include ones returned by the previous call, so compare `time` with the last returned point.
+    pub fn mouse_move_history(&self) -> Vec<MouseMovePoint> {
+        const MAX_POINTS: usize = 64;
+        unsafe {
+            let pos = GetMessagePos();
+            let current = MOUSEMOVEPOINT {
+                // The display points must be passed as 16-bit values.
+                x: (pos & 0xffff) as i32,
+                y: ((pos >> 16) & 0xffff) as i32,
+                time: GetMessageTime() as u32,
+                dwExtraInfo: 0,
+            };
+            let mut buffer = [MOUSEMOVEPOINT::default(); MAX_POINTS];
+            let len = GetMouseMovePointsEx(
+                std::mem::size_of::<MOUSEMOVEPOINT>() as _,
+                &current,
+                buffer.as_mut_ptr(),
+                MAX_POINTS as _,
+                GMMP_USE_DISPLAY_POINTS,
             );
+            if len < 0 {
+                return Vec::new();
+            }
+            buffer[..len as usize]
+                .iter()
+                .rev()
+                .map(|p| {
+                    // Negative coordinates wrap around.
+                    let mut pt = POINT {
+                        x: if p.x > 0x7fff { p.x - 0x10000 } else { p.x },
+                        y: if p.y > 0x7fff { p.y - 0x10000 } else { p.y },
+                    };
+                    ScreenToClient(self.hwnd.0, &mut pt);
+                    MouseMovePoint {
+                        position: PhysicalPosition::new(pt.x, pt.y),
+                        time: p.time,
+                    }
+                })
+                .collect()
         }
     }
 
-    pub fn set_cursor(&self, cursor: Cursor) {
-        let mut state = self.state.write().unwrap();
-        state.cursor = cursor;
-        cursor.set();
+    /// Sets the function that overrides the hit test of the window.
+    ///
+    /// This is useful to define caption areas and resize borders of custom title bars.
+    pub fn set_hit_test(&self, f: HitTestFn) {
+        self.flags.set_hit_test(Some(f));
+    }
+
+    /// Restores the default hit test.
+    pub fn reset_hit_test(&self) {
+        self.flags.set_hit_test(None);
+    }
+
+    /// Hides the window and shows the icon in the notification area when the window is minimized.
+    ///
+    /// Clicking the icon restores the window.
+    pub fn set_minimize_to_tray(&self, enable: bool) {
+        self.flags.minimize_to_tray.store(enable, Ordering::Relaxed);
+    }
+
+    pub fn is_minimize_to_tray(&self) -> bool {
+        self.flags.minimize_to_tray.load(Ordering::Relaxed)
+    }
+
+    /// Posts the registered message to the window.
+    pub fn post_message(
+        &self,
+        id: MessageId,
+        wparam: usize,
+        lparam: isize,
+    ) -> Result<(), ApiError> {
+        unsafe {
+            if PostMessageW(self.hwnd.0, id.0, WPARAM(wparam), LPARAM(lparam)).as_bool() {
+                Ok(())
+            } else {
+                Err(ApiError::new().with_function("PostMessageW"))
+            }
+        }
+    }
+
+    /// Sends `data` to the window of `target` by `WM_COPYDATA`.
+    ///
+    /// `target` is a raw `HWND` that may belong to another process.
+    /// This blocks until the target window processes the data,
+    /// and returns `true` if the target window has processed it.
+    pub fn send_copy_data(&self, target: *mut std::ffi::c_void, id: usize, data: &[u8]) -> bool {
+        unsafe {
+            let cds = COPYDATASTRUCT {
+                dwData: id,
+                cbData: data.len() as _,
+                lpData: data.as_ptr() as _,
+            };
+            SendMessageW(
+                HWND(target as _),
+                WM_COPYDATA,
+                WPARAM(self.hwnd.0 .0 as _),
+                LPARAM(&cds as *const COPYDATASTRUCT as _),
+            )
+            .0 != 0
+        }
+    }
+
+    /// Removes the reason that blocks the shutdown.
+    pub fn unblock_shutdown(&self) {
+        session::unblock_shutdown(self.hwnd.0);
+    }
+
+    pub fn set_corner_preference(&self, preference: dwm::CornerPreference) {
+        dwm::set_corner_preference(self.hwnd.0, preference);
+    }
+
+    /// Enables the thumbnail and the live preview supplied by
+    /// `EventHandler::iconic_thumbnail` and `EventHandler::iconic_live_preview`.
+    ///
+    /// This is useful for windows created with `no_redirection_bitmap` whose previews are black.
+    pub fn set_iconic_thumbnail(&self, enable: bool) {
+        dwm::set_iconic_representation(self.hwnd.0, enable);
+    }
+
+    /// Requests the taskbar to update the thumbnail and the live preview.
+    pub fn invalidate_iconic_thumbnail(&self) {
+        dwm::invalidate_iconic_bitmaps(self.hwnd.0);
+    }
+
+    /// Returns `true` if the window is on the current virtual desktop.
+    ///
+    /// To use, specify `"virtual_desktop"` feature.
+    #[cfg(feature = "virtual_desktop")]
+    pub fn is_on_current_virtual_desktop(&self) -> Result<bool, ApiError> {
+        virtual_desktop::is_on_current_desktop(self.hwnd.0)
+    }
+
+    /// Returns the virtual desktop that the window is on.
+    ///
+    /// To use, specify `"virtual_desktop"` feature.
+    #[cfg(feature = "virtual_desktop")]
+    pub fn virtual_desktop_id(&self) -> Result<VirtualDesktopId, ApiError> {
+        virtual_desktop::desktop_id(self.hwnd.0)
+    }
+
+    /// Moves the window to the virtual desktop.
+    ///
+    /// To use, specify `"virtual_desktop"` feature.
+    #[cfg(feature = "virtual_desktop")]
+    pub fn move_to_virtual_desktop(&self, id: VirtualDesktopId) -> Result<(), ApiError> {
+        virtual_desktop::move_to_desktop(self.hwnd.0, id)
+    }
+
+    /// Enables the blur behind the window with `DwmEnableBlurBehindWindow`.
+    ///
+    /// To use, specify `"blur_behind"` feature.
+    #[cfg(feature = "blur_behind")]
+    pub fn set_blur_behind(&self, enable: bool) {
+        blur::enable_blur_behind(self.hwnd.0, enable);
+    }
+
+    /// Sets the blur effect with the undocumented `SetWindowCompositionAttribute`.
+    ///
+    /// To use, specify `"blur_behind"` feature.
+    #[cfg(feature = "blur_behind")]
+    pub fn set_blur_effect(&self, effect: blur::BlurEffect) {
+        blur::set_blur_effect(self.hwnd.0, effect);
+    }
+
+    /// Makes screen readers read `text` by `UiaRaiseNotificationEvent`.
+    ///
+    /// This is supported from Windows 10 1709. To use, specify `"accessibility"` feature.
+    #[cfg(feature = "accessibility")]
+    pub fn announce(
+        &self,
+        text: &str,
+        priority: accessibility::AnnouncementPriority,
+    ) -> Result<(), ApiError> {
+        accessibility::announce(self.hwnd.0, text, priority)
+    }
+
+    /// Overrides the name that screen readers read instead of the title.
+    ///
+    /// To use, specify `"accessibility"` feature.
+    #[cfg(feature = "accessibility")]
+    pub fn set_accessible_name(&self, name: &str) -> Result<(), ApiError> {
+        accessibility::set_name(self.hwnd.0, name)
+    }
+
+    /// Overrides the role of the client area for screen readers.
+    ///
+    /// To use, specify `"accessibility"` feature.
+    #[cfg(feature = "accessibility")]
+    pub fn set_accessible_role(&self, role: accessibility::AccessibleRole) -> Result<(), ApiError> {
+        accessibility::set_role(self.hwnd.0, role)
+    }
+
+    /// Exposes the elements drawn in the client area to screen readers through UI Automation.
+    ///
+    /// Replacing the tree makes the elements that screen readers hold unavailable.
+    /// An empty tree leaves the client area to the default provider.
+    /// To use, specify `"accessibility"` feature.
+    #[cfg(feature = "accessibility")]
+    pub fn set_accessible_tree(&self, elements: Vec<accessibility::AccessibleElement>) {
+        let state = self.state.read().unwrap();
+        accessibility::set_tree(&state.accessible_tree, elements);
+    }
+
+    /// Begins a double-buffered GDI frame for the client area.
+    ///
+    /// Call this in `EventHandler::draw`. The frame is copied to the window when it is dropped.
+    pub fn begin_gdi_frame(&self) -> Result<GdiFrame, ApiError> {
+        GdiFrame::new(self.hwnd.0)
+    }
+
+    /// Draws with the `ID2D1HwndRenderTarget` cached for the window.
+    ///
+    /// `f` receives the raw pointer of `ID2D1HwndRenderTarget` between `BeginDraw` and `EndDraw`.
+    /// The target is resized with the window and recreated after the device has been lost.
+    /// This must be called in the thread of the window such as in `EventHandler::draw`.
+    ///
+    /// To use, specify `"d2d"` feature.
+    #[cfg(feature = "d2d")]
+    pub fn draw_d2d<F>(&self, f: F) -> Result<(), ApiError>
+    where
+        F: FnOnce(*mut std::ffi::c_void),
+    {
+        unsafe {
+            if GetWindowThreadProcessId(self.hwnd.0, std::ptr::null_mut()) != GetCurrentThreadId() {
+                return Err(ApiError::from_win32(ERROR_INVALID_THREAD_ID));
+            }
+        }
+        let window = LocalWindow::get(self.hwnd.0).ok_or_else(|| {
+            ApiError::from_win32(ERROR_INVALID_WINDOW_HANDLE).with_function("GetWindowLongPtrW")
+        })?;
+        window.d2d_target.draw(self.hwnd.0, f)
+    }
+
+    /// Creates a Vulkan surface for the window.
+    ///
+    /// The instance must be created with the extensions returned by `vulkan_required_extensions`.
+    ///
+    /// To use, specify `"ash"` feature.
+    #[cfg(feature = "ash")]
+    pub fn create_vk_surface(
+        &self,
+        entry: &ash::Entry,
+        instance: &ash::Instance,
+    ) -> Result<ash::vk::SurfaceKHR, ash::vk::Result> {
+        crate::vulkan::create_surface(self, entry, instance)
+    }
+
+    /// Captures the client area of the window as a BGRA bitmap.
+    ///
+    /// To use, specify `"window_capture"` feature.
+    #[cfg(feature = "window_capture")]
+    pub fn capture(&self) -> Result<crate::bitmap::Bitmap, ApiError> {
+        crate::bitmap::capture_window(self.hwnd.0)
     }
 
     pub fn raw_handle(&self) -> *mut std::ffi::c_void {
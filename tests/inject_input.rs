@@ -0,0 +1,68 @@
+#![cfg(feature = "test_util")]
+
+struct Application {
+    keys: Vec<(wita::VirtualKey, wita::KeyState)>,
+    chars: String,
+}
+
+impl Application {
+    fn new() -> anyhow::Result<Self> {
+        let window = wita::WindowBuilder::new().visible(false).build()?;
+        window.inject_key(wita::VirtualKey::Char('A'), wita::KeyState::Pressed)?;
+        window.inject_char('あ')?;
+        window.inject_key(wita::VirtualKey::Char('A'), wita::KeyState::Released)?;
+        window.inject_mouse(
+            wita::MouseButton::Left,
+            wita::KeyState::Pressed,
+            wita::PhysicalPosition::new(10, 20),
+        )?;
+        Ok(Self {
+            keys: vec![],
+            chars: String::new(),
+        })
+    }
+}
+
+impl wita::EventHandler for Application {
+    fn key_input(
+        &mut self,
+        _: &wita::Window,
+        key_code: wita::KeyCode,
+        state: wita::KeyState,
+        _: bool,
+    ) {
+        self.keys.push((key_code.vkey, state));
+    }
+
+    fn char_input(&mut self, _: &wita::Window, c: char) {
+        self.chars.push(c);
+    }
+
+    fn mouse_input(
+        &mut self,
+        window: &wita::Window,
+        button: wita::MouseButton,
+        state: wita::KeyState,
+        mouse_state: wita::MouseState,
+    ) {
+        assert_eq!(
+            self.keys,
+            vec![
+                (wita::VirtualKey::Char('A'), wita::KeyState::Pressed),
+                (wita::VirtualKey::Char('A'), wita::KeyState::Released),
+            ]
+        );
+        // TranslateMessage can also generate WM_CHAR from WM_KEYDOWN.
+        assert!(self.chars.contains('あ'));
+        assert_eq!(button, wita::MouseButton::Left);
+        assert_eq!(state, wita::KeyState::Pressed);
+        assert_eq!(mouse_state.position, wita::PhysicalPosition::new(10, 20));
+        assert!(mouse_state.pressed().contains(wita::MouseButton::Left));
+        window.close();
+    }
+}
+
+#[test]
+pub fn inject_input() {
+    wita::run(wita::RunType::Wait, Application::new).unwrap();
+}
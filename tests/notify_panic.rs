@@ -0,0 +1,28 @@
+struct Application {
+    window: wita::Window,
+}
+
+impl Application {
+    fn new() -> anyhow::Result<Self> {
+        let window = wita::WindowBuilder::new().build()?;
+        Ok(Self { window })
+    }
+}
+
+impl wita::EventHandler for Application {
+    fn activated(&mut self, _: &wita::Window) {
+        panic!("success");
+    }
+
+    fn handler_panicked(&mut self, payload: Box<dyn std::any::Any + Send>) {
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"success"));
+        self.window.close();
+    }
+}
+
+#[test]
+pub fn notify_panic() {
+    let options =
+        wita::RunOptions::new(wita::RunType::Wait).panic_policy(wita::PanicPolicy::Notify);
+    wita::run_with_options(options, Application::new).unwrap();
+}